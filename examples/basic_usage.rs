@@ -1,3 +1,4 @@
+use clerk_fapi_rs::params::{AttemptSignInFactorOneParams, CreateSignInParams};
 use clerk_fapi_rs::{clerk::Clerk, configuration::ClerkFapiConfiguration};
 use dotenv::dotenv;
 use std::time::Duration;
@@ -31,7 +32,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     )?;
 
     // Initialize Clerk client
-    let clerk = Clerk::new(config);
+    let clerk = Clerk::new(config)?;
 
     // Load the client (this fetches initial data)
     let clerk = clerk.load().await?;
@@ -51,17 +52,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Create sign-in attempt
             let sign_in_response = clerk
                 .get_fapi_client()
-                .create_sign_in(
-                    Some("email_code"),
-                    Some(&email),
-                    None, // password
-                    None, // ticket
-                    None, // redirect_url
-                    None, // action_complete_redirect_url
-                    None, // transfer
-                    None, // code
-                    None, // token
-                )
+                .create_sign_in_with(CreateSignInParams::email_code(&email))
                 .await?;
 
             let sign_in_id = sign_in_response.response.id;
@@ -74,15 +65,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Attempt first factor verification
             let verification_response = clerk
                 .get_fapi_client()
-                .attempt_sign_in_factor_one(
+                .attempt_sign_in_factor_one_with(
                     &sign_in_id,
-                    Some("email_code"),
-                    Some(&code),
-                    None, // password
-                    None, // signature
-                    None, // redirect_url
-                    None, // action_complete_redirect_url
-                    None, // ticket
+                    AttemptSignInFactorOneParams::email_code(&code),
                 )
                 .await?;
 
@@ -104,17 +89,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             let sign_in_response = clerk
                 .get_fapi_client()
-                .create_sign_in(
-                    Some("ticket"),
-                    None, // identifier
-                    None, // password
-                    Some(&ticket),
-                    None, // redirect_url
-                    None, // action_complete_redirect_url
-                    None, // transfer
-                    None, // code
-                    None, // token
-                )
+                .create_sign_in_with(CreateSignInParams::ticket(&ticket))
                 .await?;
 
             if sign_in_response.response.status
@@ -0,0 +1,169 @@
+use super::Error;
+use reqwest::multipart::{Form, Part};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+enum FileSource {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+    Reader(Box<dyn AsyncRead + Send + Unpin>),
+}
+
+/// A file to be sent as the `file` field of a multipart request
+///
+/// Can be created from a path, raw bytes or an async reader. When no content
+/// type is given it is guessed from the file name extension.
+pub struct FileUpload {
+    source: FileSource,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+impl FileUpload {
+    /// Upload the file at `path`, the file name defaults to the last path segment
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+        Self {
+            source: FileSource::Path(path),
+            file_name,
+            content_type: None,
+        }
+    }
+
+    /// Upload the given bytes as a file called `file_name`
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>, file_name: impl Into<String>) -> Self {
+        Self {
+            source: FileSource::Bytes(bytes.into()),
+            file_name: Some(file_name.into()),
+            content_type: None,
+        }
+    }
+
+    /// Upload everything read from `reader` as a file called `file_name`
+    pub fn from_reader<R>(reader: R, file_name: impl Into<String>) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+    {
+        Self {
+            source: FileSource::Reader(Box::new(reader)),
+            file_name: Some(file_name.into()),
+            content_type: None,
+        }
+    }
+
+    /// Overrides the file name sent with the upload
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// Overrides the content type sent with the upload
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    fn guess_content_type(&self) -> &'static str {
+        let extension = self
+            .file_name
+            .as_deref()
+            .and_then(|name| Path::new(name).extension())
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("png") => "image/png",
+            Some("jpg") | Some("jpeg") => "image/jpeg",
+            Some("gif") => "image/gif",
+            Some("webp") => "image/webp",
+            Some("svg") => "image/svg+xml",
+            Some("bmp") => "image/bmp",
+            Some("ico") => "image/x-icon",
+            _ => DEFAULT_CONTENT_TYPE,
+        }
+    }
+
+    /// Reads the file contents and builds the multipart form for the request
+    pub(crate) async fn into_form<T>(self) -> Result<Form, Error<T>> {
+        let content_type = match &self.content_type {
+            Some(content_type) => content_type.clone(),
+            None => self.guess_content_type().to_string(),
+        };
+
+        let bytes = match self.source {
+            FileSource::Path(path) => tokio::fs::read(path).await?,
+            FileSource::Bytes(bytes) => bytes,
+            FileSource::Reader(mut reader) => {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes).await?;
+                bytes
+            }
+        };
+
+        let mut part = Part::bytes(bytes).mime_str(&content_type)?;
+        if let Some(file_name) = self.file_name {
+            part = part.file_name(file_name);
+        }
+
+        Ok(Form::new().part("file", part))
+    }
+}
+
+impl fmt::Debug for FileUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match &self.source {
+            FileSource::Path(path) => format!("Path({})", path.display()),
+            FileSource::Bytes(bytes) => format!("Bytes({} bytes)", bytes.len()),
+            FileSource::Reader(_) => "Reader".to_string(),
+        };
+        f.debug_struct("FileUpload")
+            .field("source", &source)
+            .field("file_name", &self.file_name)
+            .field("content_type", &self.content_type)
+            .finish()
+    }
+}
+
+impl From<PathBuf> for FileUpload {
+    fn from(path: PathBuf) -> Self {
+        Self::from_path(path)
+    }
+}
+
+impl From<&Path> for FileUpload {
+    fn from(path: &Path) -> Self {
+        Self::from_path(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_path_uses_file_name() {
+        let upload = FileUpload::from_path("/tmp/avatars/me.PNG");
+        assert_eq!(upload.file_name.as_deref(), Some("me.PNG"));
+        assert_eq!(upload.guess_content_type(), "image/png");
+    }
+
+    #[test]
+    fn test_unknown_extension_falls_back_to_octet_stream() {
+        let upload = FileUpload::from_bytes(vec![1, 2, 3], "logo");
+        assert_eq!(upload.guess_content_type(), DEFAULT_CONTENT_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_reader_contents_are_read() {
+        let upload =
+            FileUpload::from_reader(&b"image-bytes"[..], "logo.jpg").content_type("image/jpeg");
+        let form = upload.into_form::<()>().await.unwrap();
+        assert!(!form.boundary().is_empty());
+    }
+}
@@ -145,3 +145,6 @@ pub mod web3_wallets_api;
 pub mod well_known_api;
 
 pub mod configuration;
+
+mod file_upload;
+pub use self::file_upload::FileUpload;
@@ -8,7 +8,7 @@
  * Generated by: https://openapi-generator.tech
  */
 
-use super::{configuration, Error};
+use super::{configuration, Error, FileUpload};
 use crate::{apis::ResponseContent, models};
 use reqwest;
 use serde::{Deserialize, Serialize};
@@ -397,7 +397,7 @@ pub async fn update_organization(
 pub async fn update_organization_logo(
     configuration: &configuration::Configuration,
     organization_id: &str,
-    _file: Option<std::path::PathBuf>,
+    file: FileUpload,
 ) -> Result<models::ClientPeriodClientWrappedOrganization, Error<UpdateOrganizationLogoError>> {
     let local_var_configuration = configuration;
 
@@ -434,8 +434,7 @@ pub async fn update_organization_logo(
     if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
         local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
     };
-    let local_var_form = reqwest::multipart::Form::new();
-    // TODO: support file upload for 'file' parameter
+    let local_var_form = file.into_form().await?;
     local_var_req_builder = local_var_req_builder.multipart(local_var_form);
 
     let local_var_req = local_var_req_builder.build()?;
@@ -64,6 +64,8 @@ pub enum ReadPasskeyError {
 pub async fn attempt_passkey_verification(
     configuration: &configuration::Configuration,
     passkey_id: &str,
+    strategy: Option<&str>,
+    public_key_credential: Option<&str>,
 ) -> Result<models::ClientPeriodClientWrappedPasskey, Error<AttemptPasskeyVerificationError>> {
     let local_var_configuration = configuration;
 
@@ -100,6 +102,14 @@ pub async fn attempt_passkey_verification(
     if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
         local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
     };
+    let mut local_var_form_params = std::collections::HashMap::new();
+    if let Some(local_var_param_value) = strategy {
+        local_var_form_params.insert("strategy", local_var_param_value.to_string());
+    }
+    if let Some(local_var_param_value) = public_key_credential {
+        local_var_form_params.insert("public_key_credential", local_var_param_value.to_string());
+    }
+    local_var_req_builder = local_var_req_builder.form(&local_var_form_params);
 
     let local_var_req = local_var_req_builder.build()?;
     let local_var_resp = local_var_client.execute(local_var_req).await?;
@@ -86,7 +86,7 @@ pub async fn acs(
 pub async fn saml_metadata(
     configuration: &configuration::Configuration,
     saml_connection_id: &str,
-) -> Result<(), Error<SamlMetadataError>> {
+) -> Result<String, Error<SamlMetadataError>> {
     let local_var_configuration = configuration;
 
     let local_var_client = &local_var_configuration.client;
@@ -119,7 +119,7 @@ pub async fn saml_metadata(
     let local_var_content = local_var_resp.text().await?;
 
     if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
-        Ok(())
+        Ok(local_var_content)
     } else {
         let local_var_entity: Option<SamlMetadataError> =
             serde_json::from_str(&local_var_content).ok();
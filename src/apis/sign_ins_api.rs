@@ -174,6 +174,7 @@ pub async fn attempt_sign_in_factor_one(
     redirect_url: Option<&str>,
     action_complete_redirect_url: Option<&str>,
     ticket: Option<&str>,
+    public_key_credential: Option<&str>,
 ) -> Result<models::ResponsesPeriodClientPeriodSignIn, Error<AttemptSignInFactorOneError>> {
     let local_var_configuration = configuration;
 
@@ -235,6 +236,9 @@ pub async fn attempt_sign_in_factor_one(
     if let Some(local_var_param_value) = ticket {
         local_var_form_params.insert("ticket", local_var_param_value.to_string());
     }
+    if let Some(local_var_param_value) = public_key_credential {
+        local_var_form_params.insert("public_key_credential", local_var_param_value.to_string());
+    }
     local_var_req_builder = local_var_req_builder.form(&local_var_form_params);
 
     let local_var_req = local_var_req_builder.build()?;
@@ -429,6 +433,7 @@ pub async fn create_sign_in(
 pub async fn get_sign_in(
     configuration: &configuration::Configuration,
     sign_in_id: &str,
+    rotating_token_nonce: Option<&str>,
 ) -> Result<models::ResponsesPeriodClientPeriodSignIn, Error<GetSignInError>> {
     let local_var_configuration = configuration;
 
@@ -442,6 +447,11 @@ pub async fn get_sign_in(
     let mut local_var_req_builder =
         local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
 
+    if let Some(ref local_var_str) = rotating_token_nonce {
+        local_var_req_builder =
+            local_var_req_builder.query(&[("rotating_token_nonce", &local_var_str.to_string())]);
+    }
+
     if let Some(ref local_var_apikey) = local_var_configuration.api_key {
         let local_var_key = local_var_apikey.key.clone();
         let local_var_value = match local_var_apikey.prefix {
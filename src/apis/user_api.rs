@@ -8,7 +8,7 @@
  * Generated by: https://openapi-generator.tech
  */
 
-use super::{configuration, Error};
+use super::{configuration, Error, FileUpload};
 use crate::{apis::ResponseContent, models};
 use reqwest;
 use serde::{Deserialize, Serialize};
@@ -484,7 +484,7 @@ pub async fn remove_password(
 /// Update the current user's profile image.
 pub async fn update_profile_image(
     configuration: &configuration::Configuration,
-    _file: Option<std::path::PathBuf>,
+    file: FileUpload,
 ) -> Result<models::ResponsesPeriodClientPeriodClientWrappedImage, Error<UpdateProfileImageError>> {
     let local_var_configuration = configuration;
 
@@ -517,8 +517,7 @@ pub async fn update_profile_image(
     if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
         local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
     };
-    let local_var_form = reqwest::multipart::Form::new();
-    // TODO: support file upload for 'file' parameter
+    let local_var_form = file.into_form().await?;
     local_var_req_builder = local_var_req_builder.multipart(local_var_form);
 
     let local_var_req = local_var_req_builder.build()?;
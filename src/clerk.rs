@@ -1,18 +1,256 @@
 use crate::apis::configuration::Configuration as ApiConfiguration;
+use crate::apis::Error as ApiError;
 use crate::clerk_fapi::ClerkFapiClient;
-use crate::configuration::ClerkFapiConfiguration;
+use crate::configuration::{ClerkFapiConfiguration, ListenerPolicy, StoreError};
+use crate::enterprise_sso::EnterpriseSsoFlow;
+use crate::events::{ClerkEvent, Events, ListenerHandle, Listeners, Snapshot};
+use crate::jwt::{self, SessionClaims, SessionToken};
+use crate::models::client_period_session::Status as SessionStatus;
 use crate::models::{
-    ClientPeriodClient as Client, ClientPeriodEnvironment as Environment,
-    ClientPeriodOrganization as Organization, ClientPeriodSession as Session,
-    ClientPeriodUser as User,
+    ClerkErrors, ClientPeriodClient as Client, ClientPeriodEnvironment as Environment,
+    ClientPeriodOrganization as Organization, ClientPeriodPasskey as Passkey,
+    ClientPeriodSession as Session, ClientPeriodUser as User,
 };
+use crate::models::{
+    ClientClientWrappedOrganizationMembershipsResponse as MembershipsResponse,
+    ClientPeriodOrganizationMembership as OrganizationMembership,
+};
+use crate::oauth::OAuthFlow;
+use crate::passkey::{self, Authenticator, AuthenticatorError};
+use crate::saml_metadata::{ParseSamlMetadataError, SamlMetadata};
+use crate::sign_in::SignInFlow;
+use crate::sign_up::{FieldError, SignUpFlow};
+use crate::token_cache::{TokenCache, TokenCacheKey};
+use futures::FutureExt;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::error;
+use std::fmt;
 use std::future::Future;
+use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::{Arc, Weak};
 use std::time::Duration;
-use tokio::sync::{RwLock, RwLockWriteGuard};
+use tokio::sync::{broadcast, mpsc, RwLock, RwLockWriteGuard, Semaphore};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// Number of organization memberships fetched per page when resolving an organization
+const MEMBERSHIPS_PAGE_SIZE: i32 = 100;
+
+/// Errors returned by the high level [`Clerk`] API
+#[derive(Debug)]
+pub enum Error {
+    /// The client has not been loaded yet, call [`Clerk::load`] first
+    NotLoaded,
+    /// There is no active session on the client
+    NoActiveSession,
+    /// The session has no user data attached to it
+    NoUser,
+    /// The session with the given ID does not exist on the client
+    SessionNotFound(String),
+    /// The instance only allows a single session, so sessions can't be switched
+    SingleSessionMode,
+    /// The organization with the given ID or slug could not be resolved
+    OrganizationNotFound(String),
+    /// The request never produced a response (connection, middleware, IO, URL errors)
+    Transport(Box<dyn error::Error + Send + Sync>),
+    /// The Frontend API responded with an error status
+    Api(ClerkApiError),
+    /// Serializing or deserializing a payload failed
+    Serialization(serde_json::Error),
+    /// Reading from or writing to the configured store failed
+    Store(StoreError),
+    /// The sign in with the given ID was abandoned and can't be continued
+    SignInAbandoned(String),
+    /// The sign up with the given ID was abandoned and can't be continued
+    SignUpAbandoned(String),
+    /// The sign up fields don't match the user settings of the instance
+    InvalidSignUp(Vec<FieldError>),
+    /// The Frontend API response is missing data the flow needs, with the reason
+    UnexpectedResponse(String),
+    /// The OAuth or SSO strategy is not enabled for the instance
+    StrategyNotEnabled(String),
+    /// The OAuth or SSO provider did not verify the account, with the reason
+    ExternalVerificationFailed(String),
+    /// The browser was not redirected back to the loopback listener in time
+    RedirectTimeout,
+    /// The passkey authenticator failed or the user cancelled it
+    Authenticator(AuthenticatorError),
+    /// The passkey verification has no creation or request options to pass
+    /// to the authenticator
+    MissingPasskeyOptions,
+    /// The session token could not be decoded or verified
+    InvalidToken(Box<jwt::Error>),
+    /// The SAML metadata wasn't a valid Service Provider `EntityDescriptor`
+    InvalidSamlMetadata(ParseSamlMetadataError),
+    /// A listener added with [`Clerk::add_async_listener`] panicked, with the
+    /// panic message
+    ListenerPanicked(String),
+}
+
+/// An error response returned by the Frontend API
+#[derive(Debug, Clone)]
+pub struct ClerkApiError {
+    /// HTTP status of the response
+    pub status: StatusCode,
+    /// Parsed error payload, `None` if the body was not a Clerk error payload
+    pub errors: Option<ClerkErrors>,
+    /// Raw response body
+    pub content: String,
+}
+
+impl ClerkApiError {
+    /// Returns the `code` of the first error in the payload
+    pub fn code(&self) -> Option<&str> {
+        self.errors
+            .as_ref()
+            .and_then(|e| e.errors.first())
+            .map(|e| e.code.as_str())
+    }
+
+    /// Returns the first `clerk_trace_id` found in the payload
+    pub fn clerk_trace_id(&self) -> Option<&str> {
+        self.errors
+            .as_ref()
+            .and_then(|e| e.errors.iter().find_map(|e| e.clerk_trace_id.as_deref()))
+    }
+}
+
+impl fmt::Display for ClerkApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errors.as_ref().and_then(|e| e.errors.first()) {
+            Some(e) => write!(f, "status code {}: {} ({})", self.status, e.message, e.code),
+            None => write!(f, "status code {}", self.status),
+        }
+    }
+}
+
+impl Error {
+    /// Returns the API error details if this is an [`Error::Api`]
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            Error::Api(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Returns the `clerk_trace_id` of an API error, if any
+    pub fn clerk_trace_id(&self) -> Option<&str> {
+        self.api_error().and_then(|e| e.clerk_trace_id())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotLoaded => write!(f, "client is not loaded"),
+            Error::NoActiveSession => write!(f, "no active session"),
+            Error::NoUser => write!(f, "no user data found in session"),
+            Error::SessionNotFound(id) => write!(f, "session {} not found", id),
+            Error::SingleSessionMode => write!(f, "instance is in single session mode"),
+            Error::OrganizationNotFound(id) => write!(f, "organization {} not found", id),
+            Error::Transport(e) => write!(f, "transport error: {}", e),
+            Error::Api(e) => write!(f, "api error: {}", e),
+            Error::Serialization(e) => write!(f, "serialization error: {}", e),
+            Error::Store(e) => write!(f, "store error: {}", e),
+            Error::SignInAbandoned(id) => write!(f, "sign in {} was abandoned", id),
+            Error::SignUpAbandoned(id) => write!(f, "sign up {} was abandoned", id),
+            Error::UnexpectedResponse(reason) => write!(f, "unexpected response: {}", reason),
+            Error::StrategyNotEnabled(strategy) => {
+                write!(f, "strategy {} is not enabled", strategy)
+            }
+            Error::ExternalVerificationFailed(reason) => {
+                write!(f, "external verification failed: {}", reason)
+            }
+            Error::RedirectTimeout => write!(f, "timed out waiting for the redirect"),
+            Error::Authenticator(e) => write!(f, "authenticator error: {}", e),
+            Error::MissingPasskeyOptions => write!(f, "passkey verification has no options"),
+            Error::InvalidToken(e) => write!(f, "invalid token: {}", e),
+            Error::InvalidSamlMetadata(e) => write!(f, "{}", e),
+            Error::ListenerPanicked(message) => write!(f, "async listener panicked: {}", message),
+            Error::InvalidSignUp(errors) => {
+                write!(f, "invalid sign up: ")?;
+                for (i, e) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Transport(e) => Some(e.as_ref()),
+            Error::Serialization(e) => Some(e),
+            Error::Store(e) => Some(e),
+            Error::Authenticator(e) => Some(e),
+            Error::InvalidToken(e) => Some(e.as_ref()),
+            Error::InvalidSamlMetadata(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl<T> From<ApiError<T>> for Error {
+    fn from(e: ApiError<T>) -> Self {
+        match e {
+            ApiError::Reqwest(e) => Error::Transport(Box::new(e)),
+            ApiError::Middleware(e) => match e.downcast::<StoreError>() {
+                Ok(e) => Error::Store(e),
+                Err(e) => Error::Transport(e.into()),
+            },
+            ApiError::Io(e) => Error::Transport(Box::new(e)),
+            ApiError::UrlParsing(e) => Error::Transport(Box::new(e)),
+            ApiError::Serde(e) => Error::Serialization(e),
+            ApiError::ResponseError(content) => Error::Api(ClerkApiError {
+                status: content.status,
+                errors: serde_json::from_str(&content.content).ok(),
+                content: content.content,
+            }),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Serialization(e)
+    }
+}
+
+impl From<StoreError> for Error {
+    fn from(e: StoreError) -> Self {
+        Error::Store(e)
+    }
+}
+
+impl From<AuthenticatorError> for Error {
+    fn from(e: AuthenticatorError) -> Self {
+        Error::Authenticator(e)
+    }
+}
+
+impl From<jwt::Error> for Error {
+    fn from(e: jwt::Error) -> Self {
+        Error::InvalidToken(Box::new(e))
+    }
+}
+
+impl From<ParseSamlMetadataError> for Error {
+    fn from(e: ParseSamlMetadataError) -> Self {
+        Error::InvalidSamlMetadata(e)
+    }
+}
+
+type Listener = dyn Fn(Client, Option<Session>, Option<User>, Option<Organization>) + Send + Sync;
+type ErrorListener = Box<dyn Fn(&Error) + Send + Sync>;
 
 /// The main client for interacting with Clerk's Frontend API
 #[derive(Clone, Default)]
@@ -20,17 +258,78 @@ pub struct Clerk {
     config: Arc<ClerkFapiConfiguration>,
     state: Arc<RwLock<ClerkState>>,
     api_client: Arc<ClerkFapiClient>,
-    listeners: Arc<
-        RwLock<
-            Vec<
-                Box<
-                    dyn Fn(Client, Option<Session>, Option<User>, Option<Organization>)
-                        + Send
-                        + Sync,
-                >,
-            >,
-        >,
-    >,
+    token_cache: Arc<TokenCache>,
+    listeners: Arc<Listeners<Listener>>,
+    error_listeners: Arc<RwLock<Vec<ErrorListener>>>,
+    events: Arc<Events>,
+    session_poller: Arc<parking_lot::Mutex<Option<JoinHandle<()>>>>,
+    refresh: RefreshHandle,
+}
+
+/// Handle to the background environment and client refresh tasks
+///
+/// Dropping the handle does not stop the tasks, use [`RefreshHandle::cancel`].
+#[derive(Clone, Default)]
+pub struct RefreshHandle {
+    tasks: Arc<parking_lot::Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl RefreshHandle {
+    fn push(&self, task: JoinHandle<()>) {
+        let mut tasks = self.tasks.lock();
+        tasks.retain(|t| !t.is_finished());
+        tasks.push(task);
+    }
+
+    /// Aborts the in-flight refresh tasks
+    pub fn cancel(&self) {
+        for task in self.tasks.lock().drain(..) {
+            task.abort();
+        }
+    }
+
+    /// Returns true when no refresh task is running
+    pub fn is_finished(&self) -> bool {
+        self.tasks.lock().iter().all(|t| t.is_finished())
+    }
+
+    /// Waits for the in-flight refresh tasks to finish
+    pub async fn wait(&self) {
+        let tasks = std::mem::take(&mut *self.tasks.lock());
+        for task in tasks {
+            let _ = task.await;
+        }
+    }
+}
+
+/// A non owning handle to a [`Clerk`], used by background tasks so they do
+/// not keep the instance alive after every [`Clerk`] has been dropped
+struct WeakClerk {
+    config: Weak<ClerkFapiConfiguration>,
+    state: Weak<RwLock<ClerkState>>,
+    api_client: Weak<ClerkFapiClient>,
+    token_cache: Weak<TokenCache>,
+    listeners: Weak<Listeners<Listener>>,
+    error_listeners: Weak<RwLock<Vec<ErrorListener>>>,
+    events: Weak<Events>,
+    session_poller: Weak<parking_lot::Mutex<Option<JoinHandle<()>>>>,
+    refresh: RefreshHandle,
+}
+
+impl WeakClerk {
+    fn upgrade(&self) -> Option<Clerk> {
+        Some(Clerk {
+            config: self.config.upgrade()?,
+            state: self.state.upgrade()?,
+            api_client: self.api_client.upgrade()?,
+            token_cache: self.token_cache.upgrade()?,
+            listeners: self.listeners.upgrade()?,
+            error_listeners: self.error_listeners.upgrade()?,
+            events: self.events.upgrade()?,
+            session_poller: self.session_poller.upgrade()?,
+            refresh: self.refresh.clone(),
+        })
+    }
 }
 
 #[derive(Default)]
@@ -41,36 +340,100 @@ struct ClerkState {
     user: Option<User>,
     organization: Option<Organization>,
     loaded: bool,
-    target_organization_id: Option<Option<String>>,
+    target_organization: Option<TargetOrganization>,
+    selected_session_id: Option<String>,
+}
+
+/// Organization set with [`Clerk::set_active`], applied by the next client update
+struct TargetOrganization {
+    session_id: String,
+    /// None for the personal account
+    organization: Option<Organization>,
+}
+
+impl ClerkState {
+    fn single_session_mode(&self) -> bool {
+        self.environment
+            .as_ref()
+            .and_then(|e| e.auth_config.as_ref())
+            .is_some_and(|auth_config| auth_config.single_session_mode)
+    }
+
+    /// Signed in sessions of the client, only the active one in single session mode
+    fn sessions(&self) -> Vec<Session> {
+        if self.single_session_mode() {
+            return self.session.clone().into_iter().collect();
+        }
+        self.client
+            .iter()
+            .flat_map(|client| client.sessions.iter())
+            .filter(|session| session.status.unwrap_or_default() == SessionStatus::Active)
+            .cloned()
+            .collect()
+    }
 }
 
 impl Clerk {
-    /// Creates a new ClerkFapiClient with the provided configuration
-    pub fn new(config: ClerkFapiConfiguration) -> Self {
+    /// Creates a new Clerk with the provided configuration
+    ///
+    /// Fails if the underlying HTTP client can't be created.
+    pub fn new(config: ClerkFapiConfiguration) -> Result<Self, Error> {
         // Create the api_client first without Arc
-        let mut api_client = ClerkFapiClient::new(config.clone()).unwrap();
+        let mut api_client =
+            ClerkFapiClient::new(config.clone()).map_err(|e| Error::Transport(Box::new(e)))?;
 
         // Create new Clerk instance
         let mut clerk = Self {
             config: Arc::new(config),
             state: Arc::new(RwLock::new(ClerkState::default())),
             api_client: Arc::new(api_client.clone()),
-            listeners: Arc::new(RwLock::new(Vec::new())),
+            token_cache: Arc::new(TokenCache::default()),
+            listeners: Arc::default(),
+            error_listeners: Arc::new(RwLock::new(Vec::new())),
+            events: Arc::default(),
+            session_poller: Arc::new(parking_lot::Mutex::new(None)),
+            refresh: RefreshHandle::default(),
         };
 
-        // Create and set the callback
-        let clerk_ref = clerk.clone();
-        api_client.set_update_client_callback(move |client| {
-            let mut clerk_ref = clerk_ref.clone();
-            async move {
-                let _ = clerk_ref.update_client(client).await;
-            }
+        // The callback holds a weak handle to the final api_client, so work it
+        // starts (like the session poller) sees client updates without the
+        // client keeping the instance alive
+        clerk.api_client = Arc::new_cyclic(|api_client_ref| {
+            let mut clerk_ref = clerk.downgrade();
+            clerk_ref.api_client = api_client_ref.clone();
+            api_client.set_update_client_callback(move |client| {
+                let clerk_ref = clerk_ref.upgrade();
+                async move {
+                    let Some(mut clerk_ref) = clerk_ref else {
+                        return;
+                    };
+                    // The API call itself succeeded, so failures are reported
+                    // to the error listeners instead of failing it
+                    if let Err(e) = clerk_ref.update_client(client).await {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(error = %e, "failed to apply client update");
+                        clerk_ref.notify_error_listeners(&e).await;
+                    }
+                }
+            });
+            api_client
         });
 
-        // Now wrap the configured api_client in Arc
-        clerk.api_client = Arc::new(api_client);
+        Ok(clerk)
+    }
 
-        clerk
+    fn downgrade(&self) -> WeakClerk {
+        WeakClerk {
+            config: Arc::downgrade(&self.config),
+            state: Arc::downgrade(&self.state),
+            api_client: Arc::downgrade(&self.api_client),
+            token_cache: Arc::downgrade(&self.token_cache),
+            listeners: Arc::downgrade(&self.listeners),
+            error_listeners: Arc::downgrade(&self.error_listeners),
+            events: Arc::downgrade(&self.events),
+            session_poller: Arc::downgrade(&self.session_poller),
+            refresh: self.refresh.clone(),
+        }
     }
 
     /// getter for the api_client
@@ -83,45 +446,69 @@ impl Clerk {
         &self.config
     }
 
+    /// Returns a handle to the background refresh started by [`Clerk::load`]
+    /// when the environment or client were restored from the store
+    pub fn background_refresh(&self) -> RefreshHandle {
+        self.refresh.clone()
+    }
+
+    /// Runs `refresh` in the background, retrying failures according to the
+    /// configured [`crate::configuration::RefreshPolicy`] and reporting them to the error listeners
+    fn spawn_refresh<F, Fut>(&self, refresh: F)
+    where
+        F: Fn(Clerk) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send,
+    {
+        let policy = self.config.refresh_policy().clone();
+        if !policy.enabled {
+            return;
+        }
+
+        let weak = self.downgrade();
+        let handle = tokio::spawn(async move {
+            let mut attempt = 0;
+            loop {
+                // Stop once every Clerk handle has been dropped
+                let clerk = match weak.upgrade() {
+                    Some(clerk) => clerk,
+                    None => break,
+                };
+                match refresh(clerk.clone()).await {
+                    Ok(()) => break,
+                    Err(e) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(error = %e, attempt, "background refresh failed");
+                        clerk.notify_error_listeners(&e).await
+                    }
+                }
+                drop(clerk);
+
+                attempt += 1;
+                if policy.max_attempts.is_some_and(|max| attempt >= max) {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(attempts = attempt, "giving up background refresh");
+                    break;
+                }
+                tokio::time::sleep(policy.backoff(attempt)).await;
+            }
+        });
+
+        self.refresh.push(handle);
+    }
+
     /// Helper function to load and set the environment
-    async fn load_environment(&self) -> Result<(), String> {
+    async fn load_environment(&self) -> Result<(), Error> {
         // First check if environment exists in store
-        if let Some(stored_env) = self.config.get_store_value("environment") {
+        if let Some(stored_env) = self.config.get_store_value("environment").await? {
             // Try to deserialize the stored environment
             if let Ok(environment) = serde_json::from_value::<Environment>(stored_env) {
                 // Update state and store using update_environment
                 self.update_environment(environment).await?;
 
-                // Clone what we need for background task
-                let api_client = self.api_client.clone();
-                let this = self.clone();
-
-                // Spawn background task to update environment
-                tokio::spawn(async move {
-                    const RETRY_INTERVAL: Duration = Duration::from_secs(15 * 60); // 15 minutes
-
-                    loop {
-                        // Try to fetch fresh environment
-                        match api_client.get_environment().await {
-                            Ok(fresh_env) => {
-                                // Update state and store using update_environment
-                                if let Err(e) = this.update_environment(fresh_env).await {
-                                    eprintln!(
-                                        "Failed to update environment in background task: {}",
-                                        e
-                                    );
-                                    continue;
-                                }
-                                // Success - break the retry loop
-                                break;
-                            }
-                            Err(_) => {
-                                // Failed to fetch - wait before retrying
-                                tokio::time::sleep(RETRY_INTERVAL).await;
-                                continue;
-                            }
-                        }
-                    }
+                // Refresh the stored environment in the background
+                self.spawn_refresh(|clerk| async move {
+                    let environment = clerk.api_client.get_environment().await?;
+                    clerk.update_environment(environment).await
                 });
 
                 return Ok(());
@@ -129,11 +516,7 @@ impl Clerk {
         }
 
         // If no valid environment in store, fetch from API
-        let environment = self
-            .api_client
-            .get_environment()
-            .await
-            .map_err(|e| format!("Failed to fetch environment: {}", e))?;
+        let environment = self.api_client.get_environment().await?;
 
         // Update state and store using update_environment
         self.update_environment(environment).await?;
@@ -142,46 +525,21 @@ impl Clerk {
     }
 
     /// Helper function to load and set the client
-    async fn load_client(&mut self) -> Result<(), String> {
+    async fn load_client(&mut self) -> Result<(), Error> {
         // First check if client exists in store
-        if let Some(stored_client) = self.config.get_store_value("client") {
+        if let Some(stored_client) = self.config.get_store_value("client").await? {
             // Try to deserialize the stored client
             if let Ok(client) = serde_json::from_value::<Client>(stored_client) {
                 // Update state with stored client
                 self.update_client(client).await?;
 
-                // Clone what we need for background task
-                let api_client = self.api_client.clone();
-                let mut this = self.clone();
-
-                // Spawn background task to update client
-                tokio::spawn(async move {
-                    const RETRY_INTERVAL: Duration = Duration::from_secs(15 * 60); // 15 minutes
-
-                    loop {
-                        // Try to fetch fresh client
-                        match api_client.get_client().await {
-                            Ok(fresh_client_response) => {
-                                if let Some(Some(fresh_client)) = fresh_client_response.response {
-                                    // Update state and store using update_client
-                                    if let Err(e) = this.update_client(*fresh_client).await {
-                                        eprintln!(
-                                            "Failed to update client in background task: {}",
-                                            e
-                                        );
-                                        continue;
-                                    }
-                                    // Success - break the retry loop
-                                    break;
-                                }
-                            }
-                            Err(_) => {
-                                // Failed to fetch - wait before retrying
-                                tokio::time::sleep(RETRY_INTERVAL).await;
-                                continue;
-                            }
-                        }
+                // Refresh the stored client in the background
+                self.spawn_refresh(|mut clerk| async move {
+                    let response = clerk.api_client.get_client().await?;
+                    if let Some(Some(client)) = response.response {
+                        clerk.update_client(*client).await?;
                     }
+                    Ok(())
                 });
 
                 return Ok(());
@@ -189,11 +547,7 @@ impl Clerk {
         }
 
         // If no valid client in store, fetch from API
-        let client_response = self
-            .api_client
-            .get_client()
-            .await
-            .map_err(|e| format!("Failed to fetch client: {}", e))?;
+        let client_response = self.api_client.get_client().await?;
 
         // Update client state if response contains client data
         if let Some(Some(client)) = client_response.response {
@@ -203,6 +557,19 @@ impl Clerk {
         Ok(())
     }
 
+    /// Loads the given environment and client instead of fetching them
+    ///
+    /// Makes no requests, so code that only reads the state can be tested
+    /// without a server, see [`crate::testing::fixtures`]. Later calls that
+    /// reach the API still use the configured URL.
+    #[cfg(feature = "testing")]
+    pub async fn load_with(&self, environment: Environment, client: Client) -> Result<Self, Error> {
+        self.update_environment(environment).await?;
+        self.clone().update_client(client).await?;
+        self.state.write().await.loaded = true;
+        Ok(self.clone())
+    }
+
     /// Initialize the client by fetching environment and client data
     ///
     /// This method must be called before using other client methods.
@@ -215,7 +582,7 @@ impl Clerk {
     /// # Errors
     ///
     /// Returns an error if either API call fails
-    pub async fn load(&self) -> Result<Self, String> {
+    pub async fn load(&self) -> Result<Self, Error> {
         // Return early if already loaded
         if self.state.read().await.loaded {
             return Ok(self.clone());
@@ -236,9 +603,76 @@ impl Clerk {
             state.loaded = true;
         }
 
+        if self.session().await.is_some() {
+            self.start_session_poller();
+        }
+
         Ok(self.clone())
     }
 
+    /// Starts the background session poller if it is enabled in the
+    /// configuration and not already running
+    ///
+    /// The poller is started by [`Clerk::load`] and whenever a session becomes
+    /// active, and is stopped by [`Clerk::sign_out`]. Failures are reported to
+    /// the listeners registered with [`Clerk::add_error_listener`].
+    pub fn start_session_poller(&self) {
+        let interval = match self.config.session_poll_interval() {
+            Some(interval) => interval,
+            None => return,
+        };
+
+        let mut poller = self.session_poller.lock();
+        if poller.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            return;
+        }
+
+        let weak = self.downgrade();
+        *poller = Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            // The first tick completes immediately, the session was just loaded
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+                // Stop once every Clerk handle has been dropped
+                let clerk = match weak.upgrade() {
+                    Some(clerk) => clerk,
+                    None => break,
+                };
+                if let Err(e) = clerk.poll_session().await {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(error = %e, "session poll failed");
+                    clerk.notify_error_listeners(&e).await;
+                }
+            }
+        }));
+    }
+
+    /// Stops the background session poller if it is running
+    pub fn stop_session_poller(&self) {
+        if let Some(handle) = self.session_poller.lock().take() {
+            handle.abort();
+        }
+    }
+
+    /// Touches the active session and refreshes its token
+    async fn poll_session(&self) -> Result<(), Error> {
+        let session = match self.session().await {
+            Some(session) => session,
+            None => return Ok(()),
+        };
+        let session_id = session.id.ok_or(Error::NoActiveSession)?;
+
+        self.api_client
+            .touch_session(&session_id, session.last_active_organization_id.as_deref())
+            .await?;
+        self.get_token(None, None).await?;
+
+        Ok(())
+    }
+
     /// Returns whether the client has been initialized
     pub async fn loaded(&self) -> bool {
         self.state.read().await.loaded
@@ -259,6 +693,13 @@ impl Clerk {
         self.state.read().await.session.clone()
     }
 
+    /// Returns the signed in sessions of the client, each with its user
+    ///
+    /// Only the active session is returned when the instance is in single session mode.
+    pub async fn sessions(&self) -> Vec<Session> {
+        self.state.read().await.sessions()
+    }
+
     /// Returns the current user if set
     pub async fn user(&self) -> Option<User> {
         self.state.read().await.user.clone()
@@ -269,6 +710,14 @@ impl Clerk {
         self.state.read().await.organization.clone()
     }
 
+    /// Notifies all registered error listeners about a background failure
+    async fn notify_error_listeners(&self, error: &Error) {
+        let listeners = self.error_listeners.read().await;
+        for listener in listeners.iter() {
+            listener(error);
+        }
+    }
+
     /// Notifies all registered listeners with the current state
     async fn notify_listeners(&self) {
         let state = self.state.read().await;
@@ -286,8 +735,7 @@ impl Clerk {
         drop(state);
 
         // Notify all listeners
-        let listeners = self.listeners.read().await;
-        for listener in listeners.iter() {
+        for listener in self.listeners.snapshot() {
             listener(
                 client.clone(),
                 current_session.clone(),
@@ -299,46 +747,114 @@ impl Clerk {
 
     /// Updates the client state based on the provided client data
     /// This includes updating the client, session, user, and organization state
-    pub async fn update_client(&mut self, client: Client) -> Result<(), String> {
+    pub async fn update_client(&mut self, client: Client) -> Result<(), Error> {
         let mut state = self.state.write().await;
+        let previous = Self::snapshot(&state);
 
         // Update client state
         state.client = Some(client.clone());
         let fresh_client = client.clone();
 
-        // Get the active session from the sessions list
-        let active_session = client.last_active_session_id.and_then(|id| {
-            client
-                .sessions
-                .iter()
-                .find(|s| s.id == Some(id.clone()))
-                .cloned()
+        // A session selected with switch_session stays active while it's signed in
+        let selected_session = state
+            .selected_session_id
+            .clone()
+            .filter(|_| !state.single_session_mode())
+            .and_then(|id| {
+                client.sessions.iter().find(|s| {
+                    s.id.as_deref() == Some(id.as_str())
+                        && s.status.unwrap_or_default() == SessionStatus::Active
+                })
+            })
+            .cloned();
+        if selected_session.is_none() {
+            state.selected_session_id = None;
+        }
+
+        // Otherwise get the active session from the sessions list
+        let active_session = selected_session.or_else(|| {
+            client.last_active_session_id.and_then(|id| {
+                client
+                    .sessions
+                    .iter()
+                    .find(|s| s.id == Some(id.clone()))
+                    .cloned()
+            })
         });
 
+        let previous_session = state
+            .session
+            .as_ref()
+            .map(|s| (s.id.clone(), s.last_active_organization_id.clone()));
+
         // Remove mut self requirement from set_accessors
         Self::set_accessors(&mut state, active_session)?;
+        let events = previous.diff(&Self::snapshot(&state));
+
+        // Cached tokens are only valid for the session and organization they were minted for
+        let current_session = state
+            .session
+            .as_ref()
+            .map(|s| (s.id.clone(), s.last_active_organization_id.clone()));
+        if previous_session != current_session {
+            self.token_cache.clear();
+        } else {
+            self.token_cache.retain_sessions(|session_id| {
+                client
+                    .sessions
+                    .iter()
+                    .any(|s| s.id.as_deref() == Some(session_id))
+            });
+        }
 
-        // Save client to store
-        self.config.set_store_value(
-            "client",
-            serde_json::to_value(fresh_client.clone())
-                .map_err(|e| format!("Failed to serialize client: {}", e))?,
-        );
+        // Resume polling when a session becomes active after a sign out
+        if state.loaded && state.session.is_some() {
+            self.start_session_poller();
+        }
 
-        // Drop the write lock before notifying listeners
+        // Drop the write lock before touching the store and notifying listeners
         drop(state);
 
+        // Save client to store, the in memory state is updated even if this fails
+        let persisted = match serde_json::to_value(fresh_client) {
+            Ok(value) => self
+                .config
+                .set_store_value("client", value)
+                .await
+                .map_err(Error::from),
+            Err(e) => Err(Error::from(e)),
+        };
+        #[cfg(feature = "tracing")]
+        if let Err(e) = &persisted {
+            tracing::warn!(error = %e, "failed to persist client");
+        }
+
         // Notify listeners using the new method
         self.notify_listeners().await;
+        for event in events {
+            self.events.emit(event);
+        }
 
-        Ok(())
+        persisted
+    }
+
+    fn snapshot(state: &ClerkState) -> Snapshot {
+        Snapshot {
+            sessions: state.sessions(),
+            session: state.session.clone(),
+            user: state.user.clone(),
+            organization: state.organization.clone(),
+        }
     }
 
     /// Sets the session, user and organization state based on the provided active session
     fn set_accessors<'a>(
         state: &mut RwLockWriteGuard<'a, ClerkState>,
         active_session: Option<Session>,
-    ) -> Result<(), String> {
+    ) -> Result<(), Error> {
+        #[cfg(feature = "tracing")]
+        let previous = Self::active_ids(state);
+
         match active_session {
             Some(session) => {
                 // Update session state
@@ -347,36 +863,29 @@ impl Clerk {
                 // Update user state from session
                 if let Some(Some(user)) = session.user {
                     state.user = Some(*user.clone());
-                    let target_org_id = state.target_organization_id.clone();
-
-                    let org_id_target = if let Some(org_id) = target_org_id {
-                        org_id
-                    } else if let Some(org_id) = session.last_active_organization_id {
-                        Some(org_id)
-                    } else {
-                        None
+                    // The target of set_active applies once, to the session it was set for
+                    let target = state
+                        .target_organization
+                        .take_if(|target| session.id.as_ref() == Some(&target.session_id))
+                        .map(|target| target.organization);
+                    let organization_id = match &target {
+                        Some(organization) => organization.as_ref().and_then(|o| o.id.clone()),
+                        None => session.last_active_organization_id.clone(),
                     };
 
-                    // We've used the value --> time to reset
-                    state.target_organization_id = None;
-
-                    // Find organization from user's memberships
-                    if let Some(last_active_org_id) = org_id_target {
-                        if let Some(ref memberships) = user.organization_memberships {
-                            if let Some(Some(active_org)) = memberships
-                                .iter()
-                                .find(|m| {
-                                    m.organization.clone().unwrap().id
-                                        == Some(last_active_org_id.clone())
-                                })
-                                .map(|m| m.organization.clone())
-                            {
-                                state.organization = Some(*active_org);
-                            }
-                        }
-                    } else {
-                        state.organization = None;
-                    }
+                    // Organizations that aren't embedded in the user were resolved
+                    // from the API, they are kept for as long as they are active
+                    let previous_organization = state.organization.take();
+                    state.organization = organization_id.and_then(|id| {
+                        let has_id = |o: &Organization| o.id.as_ref() == Some(&id);
+                        find_organization(user.organization_memberships.iter().flatten(), &id)
+                            .or_else(|| target.flatten().filter(has_id))
+                            .or_else(|| previous_organization.filter(has_id))
+                    });
+                } else {
+                    // A session without user data can't have an active user or organization
+                    state.user = None;
+                    state.organization = None;
                 }
             }
             None => {
@@ -387,11 +896,50 @@ impl Clerk {
             }
         }
 
+        #[cfg(feature = "tracing")]
+        {
+            let (session_id, user_id, organization_id) = Self::active_ids(state);
+            if session_id != previous.0 {
+                tracing::info!(
+                    previous_session_id = previous.0.as_deref(),
+                    session_id = session_id.as_deref(),
+                    "active session changed"
+                );
+            }
+            if user_id != previous.1 {
+                tracing::info!(
+                    previous_user_id = previous.1.as_deref(),
+                    user_id = user_id.as_deref(),
+                    "active user changed"
+                );
+            }
+            if organization_id != previous.2 {
+                tracing::info!(
+                    previous_organization_id = previous.2.as_deref(),
+                    organization_id = organization_id.as_deref(),
+                    "active organization changed"
+                );
+            }
+        }
+
         Ok(())
     }
 
+    /// Ids of the active session, user and organization, for logging
+    #[cfg(feature = "tracing")]
+    fn active_ids(state: &ClerkState) -> (Option<String>, Option<String>, Option<String>) {
+        (
+            state.session.as_ref().and_then(|s| s.id.clone()),
+            state.user.as_ref().and_then(|u| u.id.clone()),
+            state.organization.as_ref().and_then(|o| o.id.clone()),
+        )
+    }
+
     /// Get a session JWT token for the current session
     ///
+    /// Tokens are cached per session, organization and template until they are
+    /// within [`ClerkFapiConfiguration::token_refresh_margin`] of expiring.
+    ///
     /// Returns None if:
     /// - Client is not loaded
     /// - No active session exists
@@ -415,7 +963,7 @@ impl Clerk {
         &self,
         organization_id: Option<&str>,
         template: Option<&str>,
-    ) -> Result<Option<String>, String> {
+    ) -> Result<Option<String>, Error> {
         // Check if client is loaded and has active session
         if !self.loaded().await {
             return Ok(None);
@@ -431,28 +979,125 @@ impl Clerk {
             return Ok(None);
         }
 
-        // Call appropriate token creation method based on parameters
-        let result = match template {
-            Some(template_name) => self
-                .api_client
-                .create_session_token_with_template(&session.id.unwrap(), template_name)
-                .await
-                .map_err(|e| format!("Failed to create session token with template: {}", e))?,
-            None => self
-                .api_client
-                .create_session_token(&session.id.unwrap(), organization_id)
-                .await
-                .map_err(|e| format!("Failed to create session token: {}", e))?,
+        let session_id = session.id.ok_or(Error::NoActiveSession)?;
+        self.session_token(session_id, organization_id, template)
+            .await
+    }
+
+    /// Get a session JWT token for any signed in session, not only the active one
+    ///
+    /// Tokens are cached the same way as with [`Clerk::get_token`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client is not loaded, the session is not signed
+    /// in on the client or has no user data attached to it.
+    pub async fn get_session_token(
+        &self,
+        session_id: &str,
+        organization_id: Option<&str>,
+        template: Option<&str>,
+    ) -> Result<Option<String>, Error> {
+        let session = {
+            let state = self.state.read().await;
+            if !state.loaded {
+                return Err(Error::NotLoaded);
+            }
+            state
+                .sessions()
+                .into_iter()
+                .find(|s| s.id.as_deref() == Some(session_id))
+                .ok_or_else(|| Error::SessionNotFound(session_id.to_string()))?
         };
+        if !matches!(session.user, Some(Some(_))) {
+            return Err(Error::NoUser);
+        }
+        self.session_token(session_id.to_string(), organization_id, template)
+            .await
+    }
+
+    /// Returns a cached token of the session, or creates a new one
+    async fn session_token(
+        &self,
+        session_id: String,
+        organization_id: Option<&str>,
+        template: Option<&str>,
+    ) -> Result<Option<String>, Error> {
+        let key = TokenCacheKey::new(&session_id, organization_id, template);
+        let margin = self.config.token_refresh_margin();
+
+        self.token_cache
+            .get_or_refresh(key, margin, || async {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    session_id,
+                    organization_id,
+                    template,
+                    "creating session token"
+                );
+                // Call appropriate token creation method based on parameters
+                let result = match template {
+                    Some(template_name) => {
+                        self.api_client
+                            .create_session_token_with_template(&session_id, template_name)
+                            .await?
+                    }
+                    None => {
+                        self.api_client
+                            .create_session_token(&session_id, organization_id)
+                            .await?
+                    }
+                };
+                self.events.emit(ClerkEvent::TokenRefreshed {
+                    session_id: session_id.clone(),
+                    organization_id: organization_id.map(str::to_string),
+                    template: template.map(str::to_string),
+                });
+                Ok(result.jwt)
+            })
+            .await
+    }
 
-        Ok(result.jwt)
+    /// Same as [`Clerk::get_token`], also decoding the claims of the token
+    ///
+    /// The claims are not verified, use [`crate::jwt::JwtVerifier`] for tokens
+    /// from untrusted sources. Custom claims, e.g. from a JWT template, are
+    /// deserialized into `T`.
+    ///
+    /// Fails with [`Error::InvalidToken`] if the claims can't be decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn example(client: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+    /// use serde_json::{Map, Value};
+    ///
+    /// if let Some(token) = client.get_token_with_claims::<Map<String, Value>>(None, None).await? {
+    ///     println!("{} expires at {}", token.jwt, token.claims.exp);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_token_with_claims<T: DeserializeOwned>(
+        &self,
+        organization_id: Option<&str>,
+        template: Option<&str>,
+    ) -> Result<Option<SessionToken<T>>, Error> {
+        let Some(jwt) = self.get_token(organization_id, template).await? else {
+            return Ok(None);
+        };
+        let claims = SessionClaims::decode(&jwt)?;
+        Ok(Some(SessionToken { jwt, claims }))
     }
 
     /// Signs out either a specific session or all sessions for this client
     ///
+    /// Stops the session poller once no active session remains.
+    ///
     /// # Arguments
     ///
     /// * `session_id` - Optional session ID to sign out. If None, signs out all sessions.
+    ///   Signing out another than the active session keeps the active one.
     ///
     /// # Returns
     ///
@@ -461,34 +1106,35 @@ impl Clerk {
     /// # Errors
     ///
     /// Returns an error if the API call fails
-    pub async fn sign_out(&self, session_id: Option<String>) -> Result<(), String> {
+    pub async fn sign_out(&self, session_id: Option<String>) -> Result<(), Error> {
         match session_id {
-            Some(sid) => {
-                self.api_client
-                    .remove_session(&sid)
-                    .await
-                    .map_err(|e| format!("Failed to remove session: {}", e))?
-                    .client
-            }
+            Some(sid) => self.api_client.remove_session(&sid).await?.client,
             None => {
                 self.api_client
                     .remove_client_sessions_and_retain_cookie()
-                    .await
-                    .map_err(|e| format!("Failed to remove all sessions: {}", e))?
+                    .await?
                     .client
             }
         };
         // The remove sessions calls will update the client state via the callback
 
+        if self.session().await.is_none() {
+            self.stop_session_poller();
+        }
+
         Ok(())
     }
 
     /// Updates the active session and/or organization
     ///
+    /// Organizations are resolved from the memberships embedded in the session
+    /// user, and paged from the API when the target session is the active one.
+    ///
     /// # Arguments
     ///
-    /// * `session_id` - Optional session ID to set as active
-    /// * `organization_id_or_slug` - Optional organization ID or slug to set as active
+    /// * `session_id` - Optional session ID to set as active, defaults to the active session
+    /// * `organization_id_or_slug` - Optional organization ID or slug to set as active.
+    ///   If None, switches to the personal account of the user.
     ///
     /// # Returns
     ///
@@ -498,117 +1144,238 @@ impl Clerk {
     ///
     /// Returns an error if:
     /// - Client is not loaded
-    /// - Both arguments are None
+    /// - There is no active session and no session ID is given
     /// - Session ID is not found in client sessions
     /// - Organization ID/slug is not found in user's memberships
     pub async fn set_active(
         &self,
         session_id: Option<String>,
         organization_id_or_slug: Option<String>,
-    ) -> Result<(), String> {
-        // Check if client is loaded
-        if !self.loaded().await {
-            return Err("Cannot set active session before client is loaded".to_string());
-        }
-
-        let mut state = self.state.write().await;
-        let client = state.client.as_ref().ok_or("Client not found")?;
-
-        // Get the target session either from the argument or current session
-        let target_session = if let Some(sid) = session_id {
-            client
-                .sessions
-                .iter()
-                .find(|s| s.id.as_ref() == Some(&sid))
-                .cloned()
-                .ok_or_else(|| format!("Session with ID {} not found", sid))?
-        } else {
-            state
-                .session
-                .clone()
-                .ok_or("No active session and no session_id provided")?
+    ) -> Result<(), Error> {
+        let (target_session, is_active_session) = {
+            let state = self.state.read().await;
+            if !state.loaded {
+                return Err(Error::NotLoaded);
+            }
+            let client = state.client.as_ref().ok_or(Error::NotLoaded)?;
+
+            // Get the target session either from the argument or current session
+            let target_session = if let Some(sid) = session_id {
+                client
+                    .sessions
+                    .iter()
+                    .find(|s| s.id.as_ref() == Some(&sid))
+                    .cloned()
+                    .ok_or(Error::SessionNotFound(sid))?
+            } else {
+                state.session.clone().ok_or(Error::NoActiveSession)?
+            };
+            let is_active_session =
+                state.session.as_ref().map(|s| &s.id) == Some(&target_session.id);
+            (target_session, is_active_session)
         };
 
         let user = match &target_session.user {
             Some(Some(user_value)) => *user_value.clone(),
-            _ => return Err("No user data found in session".to_string()),
+            _ => return Err(Error::NoUser),
         };
+        let session_id = target_session.id.ok_or(Error::NoActiveSession)?;
+
+        let organization = match organization_id_or_slug {
+            Some(id_or_slug) => Some(
+                self.resolve_organization(&user, &id_or_slug, is_active_session)
+                    .await?,
+            ),
+            None => None,
+        };
+        let organization_id = organization.as_ref().and_then(|o| o.id.clone());
 
-        let target_organization_id = if let Some(org_id_or_slug) = organization_id_or_slug {
-            if org_id_or_slug.starts_with("org_") {
-                // It's an organization ID - verify it exists in user's memberships
-                let org_exists = user
-                    .organization_memberships
-                    .as_ref()
-                    .map(|memberships| {
-                        memberships.iter().any(|m| {
-                            m.organization
-                                .as_ref()
-                                .and_then(|o| o.id.as_ref())
-                                .map(|id| *id == org_id_or_slug)
-                                .unwrap_or(false)
-                        })
-                    })
-                    .unwrap_or(false);
-                if !org_exists {
-                    None
-                } else {
-                    Some(org_id_or_slug)
-                }
-            } else {
-                // Try to find organization by slug
-                let org_id = user
-                    .organization_memberships
-                    .as_ref()
-                    .and_then(|memberships| {
-                        memberships.iter().find_map(|m| {
-                            if m.organization
-                                .as_ref()
-                                .and_then(|o| o.slug.as_ref())
-                                .map(|slug| *slug == org_id_or_slug)
-                                .unwrap_or(false)
-                            {
-                                m.organization.as_ref().and_then(|o| o.id.clone())
-                            } else {
-                                None
-                            }
-                        })
-                    });
+        {
+            let mut state = self.state.write().await;
+            state.target_organization = Some(TargetOrganization {
+                session_id: session_id.clone(),
+                organization,
+            });
+            // The touched session becomes the last active one on the server
+            state.selected_session_id = None;
+        }
+
+        // We rely on the callback to update the state
+        if let Err(e) = self
+            .api_client
+            .touch_session(&session_id, organization_id.as_deref())
+            .await
+        {
+            // Don't leave the target around for an unrelated client update
+            self.state.write().await.target_organization = None;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Switches the active session to the personal account of its user
+    ///
+    /// Same as `set_active(None, None)`.
+    pub async fn set_personal_account(&self) -> Result<(), Error> {
+        self.set_active(None, None).await
+    }
+
+    /// Resolves an organization ID or slug from the memberships of the user
+    ///
+    /// Only part of the memberships may be embedded in the session user, the
+    /// rest are paged from the API when `fetch` is set. The API only returns
+    /// the memberships of the active session.
+    async fn resolve_organization(
+        &self,
+        user: &User,
+        id_or_slug: &str,
+        fetch: bool,
+    ) -> Result<Organization, Error> {
+        if let Some(organization) =
+            find_organization(user.organization_memberships.iter().flatten(), id_or_slug)
+        {
+            return Ok(organization);
+        }
+        let not_found = || Error::OrganizationNotFound(id_or_slug.to_string());
+        if !fetch {
+            return Err(not_found());
+        }
+
+        let mut offset = 0;
+        loop {
+            let page = self
+                .api_client
+                .get_organization_memberships(Some(MEMBERSHIPS_PAGE_SIZE), Some(offset))
+                .await?;
+            let (memberships, total_count) = match *page.response {
+                MembershipsResponse::ClientClientWrappedOrganizationMembershipsResponseOneOf(
+                    page,
+                ) => (page.data.unwrap_or_default(), page.total_count),
+                MembershipsResponse::Array(memberships) => (memberships, None),
+            };
+            if let Some(organization) = find_organization(memberships.iter(), id_or_slug) {
+                return Ok(organization);
+            }
 
-                org_id
+            offset += memberships.len() as i32;
+            if memberships.len() < MEMBERSHIPS_PAGE_SIZE as usize
+                || total_count.is_some_and(|total| i64::from(offset) >= total)
+            {
+                return Err(not_found());
             }
-        } else {
-            None
+        }
+    }
+
+    /// Makes another signed in session the active one, without calling the API
+    ///
+    /// The session stays active on this instance until it's signed out or
+    /// [`Clerk::set_active`] is called, even when the API reports another
+    /// session as the last active one. Use [`Clerk::set_active`] to also make
+    /// it the last active session on the server.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Client is not loaded
+    /// - The instance is in single session mode
+    /// - The session is not signed in on the client
+    pub async fn switch_session(&self, session_id: &str) -> Result<(), Error> {
+        let client = {
+            let mut state = self.state.write().await;
+            if !state.loaded {
+                return Err(Error::NotLoaded);
+            }
+            if state.session.as_ref().and_then(|s| s.id.as_deref()) == Some(session_id) {
+                return Ok(());
+            }
+            if state.single_session_mode() {
+                return Err(Error::SingleSessionMode);
+            }
+            if !state
+                .sessions()
+                .iter()
+                .any(|s| s.id.as_deref() == Some(session_id))
+            {
+                return Err(Error::SessionNotFound(session_id.to_string()));
+            }
+            state.selected_session_id = Some(session_id.to_string());
+            state.client.clone().ok_or(Error::NotLoaded)?
         };
+        self.clone().update_client(client).await
+    }
 
-        state.target_organization_id = Some(target_organization_id.clone());
-        // Need to drop the state before calling api's to avoid deadlocks
-        drop(state);
-        // Touch the target session using the clerk_fapi client
-        let active_organization_id = target_organization_id.as_deref();
-        let session_id = target_session.id.ok_or("No session ID found")?;
-        self.api_client
-            .touch_session(&session_id, active_organization_id)
-            .await
-            .map_err(|e| format!("Failed to touch session: {}", e))?;
-        // We rely on the callback to update the state
+    /// Makes the session created by a sign in or sign up the active one
+    pub(crate) async fn activate_session(&self, session_id: String) -> Result<(), Error> {
+        // The client update usually already made the new session active
+        if self.session().await.and_then(|s| s.id).as_ref() != Some(&session_id) {
+            self.set_active(Some(session_id), None).await?;
+        }
         Ok(())
     }
 
+    /// Starts a typed sign in flow, see [`crate::sign_in`]
+    ///
+    /// A completed sign in activates the created session on this instance,
+    /// which has to be loaded.
+    pub fn sign_in(&self) -> SignInFlow {
+        SignInFlow::new(self.clone())
+    }
+
+    /// Starts a typed sign up flow, see [`crate::sign_up`]
+    pub fn sign_up(&self) -> SignUpFlow {
+        SignUpFlow::new(self.clone())
+    }
+
+    /// Starts an OAuth sign in with a social provider, e.g. `oauth_google`,
+    /// see [`crate::oauth`]
+    pub fn oauth(&self, strategy: &str) -> OAuthFlow {
+        OAuthFlow::new(self.clone(), strategy)
+    }
+
+    /// Registers a passkey for the user of the active session, see
+    /// [`crate::passkey`]
+    pub async fn create_passkey(
+        &self,
+        authenticator: &dyn Authenticator,
+    ) -> Result<Passkey, Error> {
+        passkey::create(self, authenticator).await
+    }
+
+    /// Starts an enterprise SSO sign in for the email address, see
+    /// [`crate::enterprise_sso`]
+    pub fn enterprise_sso(&self, identifier: &str) -> EnterpriseSsoFlow {
+        EnterpriseSsoFlow::new(self.clone(), identifier)
+    }
+
+    /// Fetches the Service Provider metadata of a SAML connection, see
+    /// [`crate::saml_metadata`]
+    ///
+    /// Fails with [`Error::InvalidSamlMetadata`] if the response isn't a
+    /// Service Provider `EntityDescriptor`.
+    pub async fn saml_metadata(&self, saml_connection_id: &str) -> Result<SamlMetadata, Error> {
+        let xml = self.api_client.saml_metadata(saml_connection_id).await?;
+        Ok(SamlMetadata::from_xml(&xml)?)
+    }
+
     /// Add this new method
-    async fn update_environment(&self, environment: Environment) -> Result<(), String> {
+    async fn update_environment(&self, environment: Environment) -> Result<(), Error> {
         // Update state
-        {
+        let changed = {
             let mut state = self.state.write().await;
+            let changed = state.environment.as_ref() != Some(&environment);
             state.environment = Some(environment.clone());
+            changed
+        };
+        if changed {
+            self.events.emit(ClerkEvent::EnvironmentUpdated {
+                environment: Box::new(environment.clone()),
+            });
         }
 
         // Save environment to store
-        self.config.set_store_value(
-            "environment",
-            serde_json::to_value(environment)
-                .map_err(|e| format!("Failed to serialize environment: {}", e))?,
-        );
+        self.config
+            .set_store_value("environment", serde_json::to_value(environment)?)
+            .await?;
 
         Ok(())
     }
@@ -616,7 +1383,10 @@ impl Clerk {
     /// Adds a listener that will be called whenever the client state changes
     /// The listener receives the current Client, Session, User and Organization state
     /// If there's already a loaded client, the callback will be called immediately
-    pub async fn add_listener<F>(&self, callback: F)
+    ///
+    /// The listener is called on every client update, even if nothing changed,
+    /// use [`Clerk::add_event_listener`] or [`Clerk::subscribe`] to only get the changes.
+    pub async fn add_listener<F>(&self, callback: F) -> ListenerHandle
     where
         F: Fn(Client, Option<Session>, Option<User>, Option<Organization>)
             + Send
@@ -624,8 +1394,7 @@ impl Clerk {
             + Clone
             + 'static,
     {
-        let mut listeners = self.listeners.write().await;
-        listeners.push(Box::new(callback.clone()));
+        let handle = self.listeners.add(Arc::new(callback.clone()));
 
         // If we already have a loaded client, call the callback immediately
         if let Ok(state) = self.state.try_read() {
@@ -640,6 +1409,153 @@ impl Clerk {
                 callback(client, session, user, organization);
             }
         }
+        handle
+    }
+
+    /// Adds a listener that runs asynchronously whenever the client state changes
+    ///
+    /// Receives the same arguments as [`Clerk::add_listener`]. The returned
+    /// futures run in a background task according to the configured
+    /// [`ListenerPolicy`], so a slow or panicking listener never blocks
+    /// [`Clerk::update_client`] or the other listeners. A panic is reported
+    /// to the error listeners as [`Error::ListenerPanicked`].
+    ///
+    /// Updates are queued without a limit so none is lost. A listener that is
+    /// slower than the updates keeps growing the queue, one client state per
+    /// update, so keep it reasonably fast or use [`ListenerPolicy::Concurrent`].
+    pub async fn add_async_listener<F, Fut>(&self, callback: F) -> ListenerHandle
+    where
+        F: Fn(Client, Option<Session>, Option<User>, Option<Organization>) -> Fut
+            + Send
+            + Sync
+            + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let (sender, updates) = mpsc::unbounded_channel();
+        tokio::spawn(run_async_listener(
+            self.config.listener_policy(),
+            updates,
+            Arc::downgrade(&self.error_listeners),
+            callback,
+        ));
+        self.add_listener(move |client, session, user, organization| {
+            // Only fails once the listener task is gone
+            let _ = sender.send((client, session, user, organization));
+        })
+        .await
+    }
+
+    /// Adds a listener that is called with every [`ClerkEvent`]
+    ///
+    /// The listener is called synchronously when the state changes, keep it
+    /// short and use [`Clerk::subscribe`] to process events asynchronously.
+    pub fn add_event_listener<F>(&self, callback: F) -> ListenerHandle
+    where
+        F: Fn(&ClerkEvent) + Send + Sync + 'static,
+    {
+        self.events.add_listener(Arc::new(callback))
+    }
+
+    /// Returns a receiver for every [`ClerkEvent`] emitted from now on
+    ///
+    /// Receivers that fall more than 64 events behind skip the oldest events
+    /// and get [`tokio::sync::broadcast::error::RecvError::Lagged`].
+    pub fn subscribe(&self) -> broadcast::Receiver<ClerkEvent> {
+        self.events.subscribe()
+    }
+
+    /// Adds a listener that will be called when a background task, such as the
+    /// session poller, fails
+    pub async fn add_error_listener<F>(&self, callback: F)
+    where
+        F: Fn(&Error) + Send + Sync + 'static,
+    {
+        self.error_listeners.write().await.push(Box::new(callback));
+    }
+}
+
+/// Finds an organization by ID, or by slug when it isn't an ID, from memberships
+fn find_organization<'a>(
+    memberships: impl IntoIterator<Item = &'a OrganizationMembership>,
+    id_or_slug: &str,
+) -> Option<Organization> {
+    let is_id = id_or_slug.starts_with("org_");
+    memberships
+        .into_iter()
+        .filter_map(|m| m.organization.as_deref())
+        .find(|o| {
+            let key = if is_id { &o.id } else { &o.slug };
+            key.as_deref() == Some(id_or_slug)
+        })
+        .cloned()
+}
+
+type ListenerArgs = (Client, Option<Session>, Option<User>, Option<Organization>);
+
+/// Runs an async listener for every update until the listener is removed
+async fn run_async_listener<F, Fut>(
+    policy: ListenerPolicy,
+    mut updates: mpsc::UnboundedReceiver<ListenerArgs>,
+    error_listeners: Weak<RwLock<Vec<ErrorListener>>>,
+    callback: F,
+) where
+    F: Fn(Client, Option<Session>, Option<User>, Option<Organization>) -> Fut
+        + Send
+        + Sync
+        + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let callback = Arc::new(callback);
+    let run = move |(client, session, user, organization): ListenerArgs| {
+        let callback = callback.clone();
+        let error_listeners = error_listeners.clone();
+        async move {
+            // A panic only loses the update it happened on
+            let result =
+                AssertUnwindSafe(
+                    async move { callback(client, session, user, organization).await },
+                )
+                .catch_unwind()
+                .await;
+            let Err(panic) = result else {
+                return;
+            };
+            #[cfg(feature = "tracing")]
+            tracing::warn!("async listener panicked");
+            let message = match panic.downcast::<String>() {
+                Ok(message) => *message,
+                Err(panic) => panic
+                    .downcast_ref::<&str>()
+                    .map_or_else(|| "unknown panic".to_string(), |m| m.to_string()),
+            };
+            if let Some(listeners) = error_listeners.upgrade() {
+                let error = Error::ListenerPanicked(message);
+                for listener in listeners.read().await.iter() {
+                    listener(&error);
+                }
+            }
+        }
+    };
+
+    match policy {
+        ListenerPolicy::Ordered => {
+            while let Some(update) = updates.recv().await {
+                run(update).await;
+            }
+        }
+        ListenerPolicy::Concurrent(limit) => {
+            let permits = Arc::new(Semaphore::new(limit.max(1)));
+            while let Some(update) = updates.recv().await {
+                let Ok(permit) = permits.clone().acquire_owned().await else {
+                    break;
+                };
+                let task = run(update);
+                tokio::spawn(async move {
+                    task.await;
+                    drop(permit);
+                });
+            }
+        }
     }
 }
 
@@ -660,6 +1576,8 @@ mod tests {
     };
 
     use super::*;
+    use crate::configuration::{AsyncStore, RefreshPolicy};
+    use crate::testing::fixtures::{response_body, signed_in_client};
     use mockito::Server;
     use serde_json;
 
@@ -1062,7 +1980,8 @@ mod tests {
                 None,
             )
             .unwrap(),
-        );
+        )
+        .unwrap();
 
         let result = clerk.clone().load().await.unwrap();
 
@@ -1089,7 +2008,7 @@ mod tests {
         )
         .unwrap();
 
-        let client = Clerk::new(config);
+        let client = Clerk::new(config).unwrap();
 
         // Test initialization fails
         let result = client.load().await;
@@ -1105,7 +2024,7 @@ mod tests {
             ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
                 .unwrap();
 
-        let client = Clerk::new(config);
+        let client = Clerk::new(config).unwrap();
         let cloned_client = client.clone();
 
         // Verify both clients point to the same configuration
@@ -1691,7 +2610,7 @@ mod tests {
         )
         .unwrap();
 
-        let client = Clerk::new(config);
+        let client = Clerk::new(config).unwrap();
         let initialized_client = client.load().await.unwrap();
 
         // Verify all mocks were called
@@ -1730,7 +2649,7 @@ mod tests {
         )
         .unwrap();
 
-        let client = Clerk::new(config);
+        let client = Clerk::new(config).unwrap();
 
         // Manually set up client state for testing
         {
@@ -1784,7 +2703,7 @@ mod tests {
             ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
                 .unwrap();
 
-        let clerk = Clerk::new(config);
+        let clerk = Clerk::new(config).unwrap();
         let was_called = Arc::new(AtomicBool::new(false));
         let was_called_clone = was_called.clone();
 
@@ -1819,13 +2738,258 @@ mod tests {
         assert!(was_called.load(Ordering::SeqCst));
     }
 
+    #[tokio::test]
+    async fn test_removed_listener_is_not_called() {
+        let config =
+            ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
+                .unwrap();
+        let mut clerk = Clerk::new(config).unwrap();
+
+        let calls = Arc::new(Mutex::new(0));
+        let calls_ref = calls.clone();
+        let handle = clerk
+            .add_listener(move |_, _, _, _| *calls_ref.lock().unwrap() += 1)
+            .await;
+
+        clerk.update_client(Client::default()).await.unwrap();
+        handle.remove();
+        clerk.update_client(Client::default()).await.unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_async_listener_does_not_block_updates() {
+        let config =
+            ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
+                .unwrap();
+        let mut clerk = Clerk::new(config).unwrap();
+
+        let (error_sender, mut errors) = mpsc::unbounded_channel();
+        clerk
+            .add_error_listener(move |e| error_sender.send(e.to_string()).unwrap())
+            .await;
+
+        let gate = Arc::new(Semaphore::new(0));
+        let (sender, mut received) = mpsc::unbounded_channel();
+        let gate_ref = gate.clone();
+        clerk
+            .add_async_listener(move |client, _, _, _| {
+                let gate = gate_ref.clone();
+                let sender = sender.clone();
+                async move {
+                    let _permit = gate.acquire().await.unwrap();
+                    if client.id.as_deref() == Some("panic") {
+                        panic!("listener failed");
+                    }
+                    sender.send(client.id).unwrap();
+                }
+            })
+            .await;
+
+        // The listener is stuck on the gate, updates still go through
+        for id in ["client_1", "panic", "client_2"] {
+            let client = Client {
+                id: Some(id.to_string()),
+                ..Default::default()
+            };
+            tokio::time::timeout(Duration::from_secs(1), clerk.update_client(client))
+                .await
+                .expect("update_client was blocked")
+                .unwrap();
+        }
+
+        gate.add_permits(3);
+        assert_eq!(received.recv().await.unwrap().as_deref(), Some("client_1"));
+        assert_eq!(received.recv().await.unwrap().as_deref(), Some("client_2"));
+        assert_eq!(
+            errors.recv().await.unwrap(),
+            "async listener panicked: listener failed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_client_emits_changes() {
+        let config =
+            ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
+                .unwrap();
+        let mut clerk = Clerk::new(config).unwrap();
+        let mut events = clerk.subscribe();
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_ref = received.clone();
+        clerk.add_event_listener(move |event| received_ref.lock().unwrap().push(event.clone()));
+
+        let signed_in = Client {
+            id: Some("test_client".to_string()),
+            sessions: vec![Session {
+                id: Some("sess_1".to_string()),
+                user: Some(Some(Box::new(User::default()))),
+                ..Default::default()
+            }],
+            last_active_session_id: Some("sess_1".to_string()),
+            ..Default::default()
+        };
+        clerk.update_client(signed_in.clone()).await.unwrap();
+        // Nothing changed, nothing is emitted
+        clerk.update_client(signed_in).await.unwrap();
+        clerk
+            .update_client(Client {
+                id: Some("test_client".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            ClerkEvent::SessionAdded { session } if session.id.as_deref() == Some("sess_1")
+        ));
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            ClerkEvent::SignedIn { session } if session.id.as_deref() == Some("sess_1")
+        ));
+        assert_eq!(
+            events.recv().await.unwrap(),
+            ClerkEvent::SignedOut {
+                session_id: Some("sess_1".to_string())
+            }
+        );
+        assert_eq!(
+            events.recv().await.unwrap(),
+            ClerkEvent::SessionRemoved {
+                session_id: "sess_1".to_string()
+            }
+        );
+        assert!(events.try_recv().is_err());
+        assert_eq!(received.lock().unwrap().len(), 4);
+    }
+
+    fn multi_session_client() -> Client {
+        let session = |id: &str| Session {
+            id: Some(id.to_string()),
+            user: Some(Some(Box::new(User {
+                id: Some(format!("user_{}", id)),
+                ..Default::default()
+            }))),
+            ..Default::default()
+        };
+        Client {
+            id: Some("test_client".to_string()),
+            sessions: vec![
+                session("sess_1"),
+                session("sess_2"),
+                Session {
+                    status: Some(SessionStatus::Ended),
+                    ..session("sess_ended")
+                },
+            ],
+            last_active_session_id: Some("sess_1".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multi_session() {
+        let mut server = Server::new_async().await;
+        let token_mock = server
+            .mock("POST", "/v1/client/sessions/sess_2/tokens?_is_native=1")
+            .with_status(200)
+            .with_body(serde_json::json!({ "jwt": "sess_2.jwt.token" }).to_string())
+            .create_async()
+            .await;
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+        let mut clerk = Clerk::new(config).unwrap();
+        clerk.update_client(multi_session_client()).await.unwrap();
+        clerk.state.write().await.loaded = true;
+
+        let ids = |sessions: Vec<Session>| sessions.into_iter().map(|s| s.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(clerk.sessions().await),
+            vec![Some("sess_1".to_string()), Some("sess_2".to_string())]
+        );
+
+        // Tokens of the other session don't need switching to it
+        let token = clerk.get_session_token("sess_2", None, None).await.unwrap();
+        assert_eq!(token.as_deref(), Some("sess_2.jwt.token"));
+        token_mock.assert_async().await;
+        let result = clerk.get_session_token("sess_ended", None, None).await;
+        assert!(matches!(result, Err(Error::SessionNotFound(id)) if id == "sess_ended"));
+
+        let mut events = clerk.subscribe();
+        clerk.switch_session("sess_2").await.unwrap();
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            ClerkEvent::SessionChanged { previous_session_id: Some(id), .. } if id == "sess_1"
+        ));
+        // The switch survives client updates reporting another last active session
+        clerk.update_client(multi_session_client()).await.unwrap();
+        assert_eq!(
+            clerk.user().await.unwrap().id.as_deref(),
+            Some("user_sess_2")
+        );
+
+        // Until the selected session is signed out
+        let mut signed_out = multi_session_client();
+        signed_out.sessions.remove(1);
+        clerk.update_client(signed_out).await.unwrap();
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            ClerkEvent::SessionChanged { session, .. } if session.id.as_deref() == Some("sess_1")
+        ));
+        assert_eq!(
+            events.recv().await.unwrap(),
+            ClerkEvent::SessionRemoved {
+                session_id: "sess_2".to_string()
+            }
+        );
+        assert!(matches!(
+            clerk.switch_session("sess_2").await,
+            Err(Error::SessionNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_single_session_mode() {
+        let config =
+            ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
+                .unwrap();
+        let mut clerk = Clerk::new(config).unwrap();
+        {
+            let mut state = clerk.state.write().await;
+            state.loaded = true;
+            state.environment = Some(Environment {
+                auth_config: Some(Box::new(crate::models::ClientPeriodAuthConfig {
+                    single_session_mode: true,
+                    ..Default::default()
+                })),
+                ..Default::default()
+            });
+        }
+        clerk.update_client(multi_session_client()).await.unwrap();
+
+        let sessions = clerk.sessions().await;
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id.as_deref(), Some("sess_1"));
+        assert!(matches!(
+            clerk.switch_session("sess_2").await,
+            Err(Error::SingleSessionMode)
+        ));
+        clerk.switch_session("sess_1").await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_listener_immediate_callback() {
         let config =
             ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
                 .unwrap();
 
-        let clerk = Clerk::new(config);
+        let clerk = Clerk::new(config).unwrap();
 
         // Set up initial state
         let test_client = Client {
@@ -1860,4 +3024,855 @@ mod tests {
         // Verify listener was called immediately
         assert!(was_called.load(Ordering::SeqCst));
     }
+
+    #[tokio::test]
+    async fn test_api_error_carries_clerk_errors() {
+        let mut server = Server::new_async().await;
+
+        let token_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/tokens?_is_native=1")
+            .with_status(401)
+            .with_body(
+                serde_json::json!({
+                    "errors": [{
+                        "message": "Unauthorized",
+                        "long_message": "Session is not active",
+                        "code": "session_not_active",
+                        "clerk_trace_id": "trace_123"
+                    }]
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+
+        let client = Clerk::new(config).unwrap();
+        {
+            let mut state = client.state.write().await;
+            state.loaded = true;
+            state.session = Some(Session {
+                id: Some("sess_123".to_string()),
+                ..Default::default()
+            });
+            state.user = Some(User::default());
+        }
+
+        let err = client.get_token(None, None).await.unwrap_err();
+        token_mock.assert_async().await;
+
+        let api_error = err.api_error().expect("expected an api error");
+        assert_eq!(api_error.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(api_error.code(), Some("session_not_active"));
+        assert_eq!(err.clerk_trace_id(), Some("trace_123"));
+    }
+
+    #[tokio::test]
+    async fn test_set_active_errors() {
+        let config =
+            ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
+                .unwrap();
+        let clerk = Clerk::new(config).unwrap();
+
+        let result = clerk.set_active(None, None).await;
+        assert!(matches!(result, Err(Error::NotLoaded)));
+
+        let mut clerk = clerk.clone();
+        clerk.update_client(Client::default()).await.unwrap();
+        clerk.state.write().await.loaded = true;
+
+        let result = clerk
+            .set_active(Some("sess_missing".to_string()), None)
+            .await;
+        assert!(matches!(result, Err(Error::SessionNotFound(id)) if id == "sess_missing"));
+
+        let result = clerk.set_active(None, None).await;
+        assert!(matches!(result, Err(Error::NoActiveSession)));
+    }
+
+    fn organization_client(last_active_organization_id: Option<&str>) -> Client {
+        Client {
+            id: Some("test_client".to_string()),
+            sessions: vec![Session {
+                id: Some("sess_123".to_string()),
+                last_active_organization_id: last_active_organization_id.map(str::to_string),
+                user: Some(Some(Box::new(User {
+                    organization_memberships: Some(vec![membership("org_1", "acme")]),
+                    ..Default::default()
+                }))),
+                ..Default::default()
+            }],
+            last_active_session_id: Some("sess_123".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn membership(id: &str, slug: &str) -> OrganizationMembership {
+        OrganizationMembership {
+            organization: Some(Box::new(Organization {
+                id: Some(id.to_string()),
+                slug: Some(slug.to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_active_resolves_organizations() {
+        let mut server = Server::new_async().await;
+        let slug_touch_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/touch?_is_native=1")
+            .match_body(mockito::Matcher::UrlEncoded(
+                "active_organization_id".to_string(),
+                "org_1".to_string(),
+            ))
+            .with_status(200)
+            .with_body(response_body(
+                &Session::default(),
+                organization_client(Some("org_1")),
+            ))
+            .create_async()
+            .await;
+        let fetched_touch_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/touch?_is_native=1")
+            .match_body(mockito::Matcher::UrlEncoded(
+                "active_organization_id".to_string(),
+                "org_2".to_string(),
+            ))
+            .with_status(200)
+            .with_body(response_body(
+                &Session::default(),
+                organization_client(Some("org_2")),
+            ))
+            .create_async()
+            .await;
+        let memberships_mock = server
+            .mock(
+                "GET",
+                "/v1/me/organization_memberships?limit=100&offset=0&_is_native=1",
+            )
+            .with_status(200)
+            .with_body(
+                serde_json::json!({
+                    "response": { "data": [membership("org_2", "globex")], "total_count": 1 },
+                    "client": organization_client(Some("org_2")),
+                })
+                .to_string(),
+            )
+            .expect(2)
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+        let mut clerk = Clerk::new(config).unwrap();
+        clerk
+            .update_client(organization_client(None))
+            .await
+            .unwrap();
+        clerk.state.write().await.loaded = true;
+
+        // Embedded in the session user, by slug
+        clerk
+            .set_active(None, Some("acme".to_string()))
+            .await
+            .unwrap();
+        slug_touch_mock.assert_async().await;
+        assert_eq!(
+            clerk.organization().await.unwrap().id.as_deref(),
+            Some("org_1")
+        );
+
+        // Paged from the API, and kept while it stays active
+        clerk
+            .set_active(None, Some("globex".to_string()))
+            .await
+            .unwrap();
+        fetched_touch_mock.assert_async().await;
+        clerk
+            .update_client(organization_client(Some("org_2")))
+            .await
+            .unwrap();
+        assert_eq!(
+            clerk.organization().await.unwrap().id.as_deref(),
+            Some("org_2")
+        );
+
+        // Unknown organizations are an error instead of clearing the organization
+        let result = clerk
+            .set_active(None, Some("org_missing".to_string()))
+            .await;
+        assert!(matches!(result, Err(Error::OrganizationNotFound(id)) if id == "org_missing"));
+        memberships_mock.assert_async().await;
+        assert_eq!(
+            clerk.organization().await.unwrap().id.as_deref(),
+            Some("org_2")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_target_organization_is_applied_once() {
+        let mut server = Server::new_async().await;
+        // The client of the response still reports the previous organization
+        let touch_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/touch?_is_native=1")
+            .with_status(200)
+            .with_body(response_body(
+                &Session::default(),
+                organization_client(Some("org_1")),
+            ))
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+        let mut clerk = Clerk::new(config).unwrap();
+        clerk
+            .update_client(organization_client(Some("org_1")))
+            .await
+            .unwrap();
+        clerk.state.write().await.loaded = true;
+        assert!(clerk.organization().await.is_some());
+
+        clerk.set_personal_account().await.unwrap();
+        touch_mock.assert_async().await;
+        assert!(clerk.organization().await.is_none());
+        assert!(clerk.state.read().await.target_organization.is_none());
+
+        // Later updates follow the session again
+        clerk
+            .update_client(organization_client(Some("org_1")))
+            .await
+            .unwrap();
+        assert!(clerk.organization().await.is_some());
+
+        // A failed switch doesn't leave the target behind
+        touch_mock.remove_async().await;
+        server
+            .mock("POST", "/v1/client/sessions/sess_123/touch?_is_native=1")
+            .with_status(500)
+            .create_async()
+            .await;
+        assert!(clerk.set_personal_account().await.is_err());
+        assert!(clerk.state.read().await.target_organization.is_none());
+        clerk
+            .update_client(organization_client(Some("org_1")))
+            .await
+            .unwrap();
+        assert!(clerk.organization().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_get_token_with_claims() {
+        let mut server = Server::new_async().await;
+        let payload = serde_json::json!({
+            "sid": "sess_123",
+            "sub": "user_123",
+            "exp": 4102444800u64,
+            "org_id": "org_123",
+            "org_slug": "acme",
+            "org_role": "org:admin",
+            "tier": "gold",
+        });
+        let jwt = format!(
+            "e30.{}.sig",
+            base64::Engine::encode(
+                &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+                payload.to_string()
+            )
+        );
+        server
+            .mock("POST", "/v1/client/sessions/sess_123/tokens?_is_native=1")
+            .with_status(200)
+            .with_body(serde_json::json!({ "object": "token", "jwt": jwt }).to_string())
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+        let client = Clerk::new(config).unwrap();
+        {
+            let mut state = client.state.write().await;
+            state.loaded = true;
+            state.session = Some(Session {
+                id: Some("sess_123".to_string()),
+                ..Default::default()
+            });
+            state.user = Some(User::default());
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Custom {
+            tier: String,
+        }
+        let token = client
+            .get_token_with_claims::<Custom>(None, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(token.jwt, jwt);
+        assert_eq!(token.claims.sid.as_deref(), Some("sess_123"));
+        assert_eq!(token.claims.org_slug.as_deref(), Some("acme"));
+        assert_eq!(token.claims.custom.tier, "gold");
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Missing {
+            #[allow(dead_code)]
+            plan: String,
+        }
+        let error = client
+            .get_token_with_claims::<Missing>(None, None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, Error::InvalidToken(e) if matches!(**e, jwt::Error::Malformed(_)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_token_uses_cache_until_sign_out() {
+        let mut server = Server::new_async().await;
+        let exp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 60;
+        let jwt = crate::token_cache::test_jwt(exp);
+
+        let token_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/tokens?_is_native=1")
+            .with_status(200)
+            .with_body(serde_json::json!({ "object": "token", "jwt": jwt }).to_string())
+            .expect(2)
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+        let mut clerk = Clerk::new(config).unwrap();
+
+        let signed_in_client = Client {
+            id: Some("test_client".to_string()),
+            sessions: vec![Session {
+                id: Some("sess_123".to_string()),
+                user: Some(Some(Box::new(User::default()))),
+                ..Default::default()
+            }],
+            last_active_session_id: Some("sess_123".to_string()),
+            ..Default::default()
+        };
+        clerk.update_client(signed_in_client.clone()).await.unwrap();
+        clerk.state.write().await.loaded = true;
+
+        assert_eq!(
+            clerk.get_token(None, None).await.unwrap(),
+            Some(jwt.clone())
+        );
+        assert_eq!(
+            clerk.get_token(None, None).await.unwrap(),
+            Some(jwt.clone())
+        );
+
+        // Signing out drops the cached token
+        clerk.update_client(Client::default()).await.unwrap();
+        clerk.update_client(signed_in_client).await.unwrap();
+
+        assert_eq!(clerk.get_token(None, None).await.unwrap(), Some(jwt));
+        token_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_session_poller_reports_errors_until_stopped() {
+        let mut server = Server::new_async().await;
+        let touch_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/touch?_is_native=1")
+            .with_status(500)
+            .expect_at_least(2)
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap()
+        .with_session_poller(Duration::from_millis(50));
+        let mut clerk = Clerk::new(config).unwrap();
+
+        let errors = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let errors_ref = errors.clone();
+        clerk
+            .add_error_listener(move |e| {
+                assert!(e.api_error().is_some());
+                errors_ref.fetch_add(1, Ordering::SeqCst);
+            })
+            .await;
+
+        clerk.update_client(signed_in_client()).await.unwrap();
+        clerk.state.write().await.loaded = true;
+        clerk.start_session_poller();
+
+        tokio::time::sleep(Duration::from_millis(180)).await;
+        clerk.stop_session_poller();
+        touch_mock.assert_async().await;
+
+        let reported = errors.load(Ordering::SeqCst);
+        assert!(reported >= 2);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(errors.load(Ordering::SeqCst), reported);
+    }
+
+    #[tokio::test]
+    async fn test_session_poller_stops_when_clerk_is_dropped() {
+        let mut server = Server::new_async().await;
+        let touch_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/touch?_is_native=1")
+            .with_status(500)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap()
+        .with_session_poller(Duration::from_millis(50));
+        let mut clerk = Clerk::new(config).unwrap();
+
+        clerk.update_client(signed_in_client()).await.unwrap();
+        clerk.state.write().await.loaded = true;
+        clerk.start_session_poller();
+        drop(clerk);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        touch_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_session_poller_applies_revocation_after_sign_in() {
+        let mut server = Server::new_async().await;
+        let sign_in_mock = server
+            .mock("POST", "/v1/client/sign_ins?_is_native=1")
+            .with_status(200)
+            .with_body(response_body(
+                &client_period_sign_in::ClientPeriodSignIn {
+                    id: "sia_123".to_string(),
+                    status: client_period_sign_in::Status::Complete,
+                    ..Default::default()
+                },
+                signed_in_client(),
+            ))
+            .create_async()
+            .await;
+        let mut revoked_client = signed_in_client();
+        revoked_client.sessions[0].status = Some(Status::Revoked);
+        revoked_client.last_active_session_id = None;
+        let touch_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/touch?_is_native=1")
+            .with_status(200)
+            .with_body(response_body(&Session::default(), revoked_client))
+            .expect_at_least(1)
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap()
+        .with_session_poller(Duration::from_millis(50));
+        let mut clerk = Clerk::new(config).unwrap();
+        clerk.update_client(Client::default()).await.unwrap();
+        clerk.state.write().await.loaded = true;
+
+        // Signing in starts the poller from the update callback
+        clerk
+            .get_fapi_client()
+            .create_sign_in_with(crate::params::CreateSignInParams::password(
+                "user@example.com",
+                "password",
+            ))
+            .await
+            .unwrap();
+        sign_in_mock.assert_async().await;
+        assert!(clerk.session().await.is_some());
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        clerk.stop_session_poller();
+        touch_mock.assert_async().await;
+
+        assert!(clerk.session().await.is_none());
+    }
+
+    async fn config_with_stored_state(server_url: String) -> ClerkFapiConfiguration {
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server_url),
+            None,
+        )
+        .unwrap();
+        config
+            .set_store_value(
+                "environment",
+                serde_json::to_value(Environment::default()).unwrap(),
+            )
+            .await
+            .unwrap();
+        config
+            .set_store_value("client", serde_json::to_value(Client::default()).unwrap())
+            .await
+            .unwrap();
+        config
+    }
+
+    #[tokio::test]
+    async fn test_background_refresh_backs_off_and_gives_up() {
+        let mut server = Server::new_async().await;
+        let env_mock = server
+            .mock("GET", "/v1/environment?_is_native=1")
+            .with_status(503)
+            .expect(3)
+            .create_async()
+            .await;
+        let client_mock = server
+            .mock("GET", "/v1/client?_is_native=1")
+            .with_status(503)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let config = config_with_stored_state(server.url())
+            .await
+            .with_refresh_policy(RefreshPolicy {
+                initial_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(20),
+                max_attempts: Some(3),
+                ..RefreshPolicy::default()
+            });
+        let clerk = Clerk::new(config).unwrap();
+
+        let errors = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let errors_ref = errors.clone();
+        clerk
+            .add_error_listener(move |_| {
+                errors_ref.fetch_add(1, Ordering::SeqCst);
+            })
+            .await;
+
+        let clerk = clerk.load().await.unwrap();
+        clerk.background_refresh().wait().await;
+
+        env_mock.assert_async().await;
+        client_mock.assert_async().await;
+        assert_eq!(errors.load(Ordering::SeqCst), 6);
+        assert!(clerk.background_refresh().is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_background_refresh_can_be_disabled_or_cancelled() {
+        let mut server = Server::new_async().await;
+        let env_mock = server
+            .mock("GET", "/v1/environment?_is_native=1")
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        let client_mock = server
+            .mock("GET", "/v1/client?_is_native=1")
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+
+        // Disabled: loading from the store makes no requests at all
+        let config = config_with_stored_state(server.url())
+            .await
+            .with_refresh_policy(RefreshPolicy::disabled());
+        let clerk = Clerk::new(config).unwrap().load().await.unwrap();
+        assert!(clerk.loaded().await);
+        assert!(clerk.background_refresh().is_finished());
+
+        // Cancelled: the first attempt runs, the retry never happens
+        let config = config_with_stored_state(server.url())
+            .await
+            .with_refresh_policy(RefreshPolicy {
+                initial_backoff: Duration::from_secs(60),
+                ..RefreshPolicy::default()
+            });
+        let clerk = Clerk::new(config).unwrap().load().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        clerk.background_refresh().cancel();
+        assert!(clerk.background_refresh().is_finished());
+
+        env_mock.assert_async().await;
+        client_mock.assert_async().await;
+    }
+
+    #[derive(Debug)]
+    struct FailingStore;
+
+    #[async_trait::async_trait]
+    impl AsyncStore for FailingStore {
+        async fn set(&self, _key: &str, _value: serde_json::Value) -> Result<(), StoreError> {
+            Err(StoreError::new("disk full"))
+        }
+
+        async fn get(&self, _key: &str) -> Result<Option<serde_json::Value>, StoreError> {
+            Err(StoreError::new("disk unavailable"))
+        }
+
+        async fn has(&self, _key: &str) -> Result<bool, StoreError> {
+            Err(StoreError::new("disk unavailable"))
+        }
+
+        async fn delete(&self, _key: &str) -> Result<bool, StoreError> {
+            Err(StoreError::new("disk unavailable"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_errors_are_propagated() {
+        let mut server = Server::new_async().await;
+        let env_mock = server
+            .mock("GET", "/v1/environment?_is_native=1")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new_with_async_store(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+            Some(Arc::new(FailingStore)),
+            None,
+        )
+        .unwrap();
+        let mut clerk = Clerk::new(config).unwrap();
+
+        // Persisting the client fails
+        let result = clerk.update_client(signed_in_client()).await;
+        assert!(matches!(result, Err(Error::Store(e)) if e.to_string() == "disk full"));
+
+        // Reading the authorization header in the middleware fails before the request is sent
+        let result = clerk
+            .api_client
+            .get_environment()
+            .await
+            .map_err(Error::from);
+        assert!(matches!(result, Err(Error::Store(_))));
+        env_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_client_with_malformed_payloads() {
+        let config =
+            ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
+                .unwrap();
+        let mut clerk = Clerk::new(config).unwrap();
+
+        // Memberships without an organization, or an organization without an id
+        let client: Client = serde_json::from_value(serde_json::json!({
+            "object": "client",
+            "id": "client_123",
+            "sign_in": null,
+            "sign_up": null,
+            "sessions": [{
+                "object": "session",
+                "id": "sess_123",
+                "status": "active",
+                "last_active_organization_id": "org_123",
+                "user": {
+                    "object": "user",
+                    "id": "user_123",
+                    "organization_memberships": [
+                        {"object": "organization_membership", "id": "mem_1"},
+                        {"object": "organization_membership", "id": "mem_2", "organization": {}}
+                    ]
+                }
+            }],
+            "last_active_session_id": "sess_123",
+            "created_at": 1704067200,
+            "updated_at": 1704067200
+        }))
+        .unwrap();
+        clerk.update_client(client).await.unwrap();
+        assert_eq!(
+            clerk.session().await.and_then(|s| s.id),
+            Some("sess_123".to_string())
+        );
+        assert_eq!(
+            clerk.user().await.and_then(|u| u.id),
+            Some("user_123".to_string())
+        );
+        assert!(clerk.organization().await.is_none());
+
+        // A session without user data clears the previous user
+        let mut client = signed_in_client();
+        client.sessions[0].user = None;
+        clerk.update_client(client).await.unwrap();
+        assert!(clerk.session().await.is_some());
+        assert!(clerk.user().await.is_none());
+
+        // The last active session is missing from the sessions list
+        let client = Client {
+            last_active_session_id: Some("sess_missing".to_string()),
+            ..signed_in_client()
+        };
+        clerk.update_client(client).await.unwrap();
+        assert!(clerk.session().await.is_none());
+        assert!(clerk.user().await.is_none());
+    }
+
+    /// Store that refuses to persist the client but stores everything else
+    #[derive(Debug, Default)]
+    struct ReadOnlyClientStore(
+        std::sync::Mutex<std::collections::HashMap<String, serde_json::Value>>,
+    );
+
+    #[async_trait::async_trait]
+    impl AsyncStore for ReadOnlyClientStore {
+        async fn set(&self, key: &str, value: serde_json::Value) -> Result<(), StoreError> {
+            if key.ends_with("client") {
+                return Err(StoreError::new("client is read only"));
+            }
+            self.0.lock().unwrap().insert(key.to_string(), value);
+            Ok(())
+        }
+
+        async fn get(&self, key: &str) -> Result<Option<serde_json::Value>, StoreError> {
+            Ok(self.0.lock().unwrap().get(key).cloned())
+        }
+
+        async fn has(&self, key: &str) -> Result<bool, StoreError> {
+            Ok(self.0.lock().unwrap().contains_key(key))
+        }
+
+        async fn delete(&self, key: &str) -> Result<bool, StoreError> {
+            Ok(self.0.lock().unwrap().remove(key).is_some())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_client_update_is_reported_to_error_listeners() {
+        let mut server = Server::new_async().await;
+        let touch_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/touch?_is_native=1")
+            .with_status(200)
+            .with_body(
+                serde_json::json!({
+                    "response": {"object": "session", "id": "sess_123", "status": "active"},
+                    "client": {
+                        "object": "client",
+                        "id": "client_123",
+                        "sign_in": null,
+                        "sign_up": null,
+                        "sessions": [],
+                        "last_active_session_id": null,
+                        "created_at": 1704067200,
+                        "updated_at": 1704067200
+                    }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new_with_async_store(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+            Some(Arc::new(ReadOnlyClientStore::default())),
+            None,
+        )
+        .unwrap();
+        let clerk = Clerk::new(config).unwrap();
+
+        let errors = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let errors_ref = errors.clone();
+        clerk
+            .add_error_listener(move |e| errors_ref.lock().unwrap().push(e.to_string()))
+            .await;
+
+        // The API call succeeds even though the client could not be persisted
+        clerk
+            .get_fapi_client()
+            .touch_session("sess_123", None)
+            .await
+            .unwrap();
+        touch_mock.assert_async().await;
+
+        assert_eq!(
+            clerk.client().await.and_then(|c| c.id),
+            Some("client_123".to_string())
+        );
+        assert_eq!(
+            *errors.lock().unwrap(),
+            vec!["store error: client is read only".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_saml_metadata() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/v1/saml/metadata/samlc_123?_is_native=1")
+            .with_header("content-type", "application/xml")
+            .with_body(
+                r#"<EntityDescriptor xmlns="urn:oasis:names:tc:SAML:2.0:metadata" entityID="sp">
+  <SPSSODescriptor WantAssertionsSigned="true"/>
+</EntityDescriptor>"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/v1/saml/metadata/samlc_456?_is_native=1")
+            .with_header("content-type", "application/xml")
+            .with_body("<html/>")
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+        let clerk = Clerk::new(config).unwrap();
+
+        let metadata = clerk.saml_metadata("samlc_123").await.unwrap();
+        assert_eq!(metadata.entity_id, "sp");
+        assert_eq!(metadata.want_assertions_signed, Some(true));
+
+        let error = clerk.saml_metadata("samlc_456").await.unwrap_err();
+        assert!(matches!(error, Error::InvalidSamlMetadata(_)));
+        assert!(error::Error::source(&error).is_some());
+    }
 }
@@ -1,12 +1,13 @@
 use crate::apis::configuration::Configuration as ApiConfiguration;
 use crate::apis::*;
-use crate::configuration::{ClerkFapiConfiguration, Store};
+use crate::configuration::{AsyncStore, ClerkFapiConfiguration, RetryPolicy};
 use crate::models::*;
+use crate::params::*;
 use async_trait::async_trait;
 use http::Extensions as HttpExtensions;
-use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
 use reqwest::Client;
-use reqwest::{Request, Response};
+use reqwest::{Method, Request, Response, StatusCode};
 use reqwest_middleware::{
     ClientBuilder, ClientWithMiddleware, Middleware, Next, Result as ReqwestResult,
 };
@@ -14,6 +15,7 @@ use serde_json::Value as JsonValue;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
 // Add middleware definitions
@@ -36,12 +38,12 @@ impl Middleware for DefaultQueryMiddleware {
 
 #[derive(Clone)]
 struct AuthorizationMiddleware {
-    store: Arc<dyn Store>,
+    store: Arc<dyn AsyncStore>,
     store_prefix: String,
 }
 
 impl AuthorizationMiddleware {
-    fn new(store: Arc<dyn Store>, store_prefix: String) -> Self {
+    fn new(store: Arc<dyn AsyncStore>, store_prefix: String) -> Self {
         Self {
             store,
             store_prefix,
@@ -61,7 +63,14 @@ impl Middleware for AuthorizationMiddleware {
         extensions: &mut HttpExtensions,
         next: Next<'_>,
     ) -> ReqwestResult<Response> {
-        if let Some(auth) = self.store.get(&self.get_auth_key()) {
+        let auth_key = self.get_auth_key();
+
+        let stored_auth = self
+            .store
+            .get(&auth_key)
+            .await
+            .map_err(anyhow::Error::new)?;
+        if let Some(auth) = stored_auth {
             if let Some(auth_str) = auth.as_str() {
                 if let Ok(value) = HeaderValue::from_str(auth_str) {
                     req.headers_mut().insert("Authorization", value);
@@ -69,14 +78,16 @@ impl Middleware for AuthorizationMiddleware {
             }
         }
 
-        let store = self.store.clone();
-        let auth_key = self.get_auth_key();
-
         let resp = next.run(req, extensions).await?;
 
         if let Some(auth_header) = resp.headers().get("Authorization") {
             if let Ok(auth_str) = auth_header.to_str() {
-                store.set(&auth_key, JsonValue::String(auth_str.to_string()));
+                self.store
+                    .set(&auth_key, JsonValue::String(auth_str.to_string()))
+                    .await
+                    .map_err(anyhow::Error::new)?;
+                #[cfg(feature = "tracing")]
+                tracing::debug!(authorization = "[redacted]", "stored authorization header");
             }
         }
 
@@ -84,6 +95,181 @@ impl Middleware for AuthorizationMiddleware {
     }
 }
 
+#[derive(Clone)]
+struct RetryMiddleware {
+    policy: RetryPolicy,
+}
+
+impl RetryMiddleware {
+    fn new(policy: RetryPolicy) -> Self {
+        Self { policy }
+    }
+
+    fn is_idempotent(method: &Method) -> bool {
+        matches!(
+            *method,
+            Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+        )
+    }
+
+    /// Parses a `Retry-After` header given either in seconds or as an HTTP date
+    fn retry_after(resp: &Response) -> Option<Duration> {
+        let value = resp.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+        let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .ok()
+            .or(Some(Duration::ZERO))
+    }
+
+    /// Returns how long to wait before retrying, `None` if the request should not be retried
+    fn retry_delay(
+        &self,
+        idempotent: bool,
+        result: &ReqwestResult<Response>,
+        retry: u32,
+    ) -> Option<Duration> {
+        match result {
+            Ok(resp) if resp.status() == StatusCode::TOO_MANY_REQUESTS => Some(
+                Self::retry_after(resp)
+                    .map(|d| d.min(self.policy.max_retry_after))
+                    .unwrap_or_else(|| self.policy.backoff(retry)),
+            ),
+            Ok(resp) if idempotent && resp.status().is_server_error() => {
+                Some(self.policy.backoff(retry))
+            }
+            Err(reqwest_middleware::Error::Reqwest(e))
+                if idempotent && (e.is_connect() || e.is_timeout()) =>
+            {
+                Some(self.policy.backoff(retry))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for RetryMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut HttpExtensions,
+        next: Next<'_>,
+    ) -> ReqwestResult<Response> {
+        let idempotent = Self::is_idempotent(req.method());
+        let mut retry = 0;
+
+        loop {
+            // Requests with streaming bodies can't be replayed
+            let attempt = match req.try_clone() {
+                Some(attempt) if retry < self.policy.max_retries => attempt,
+                _ => return next.run(req, extensions).await,
+            };
+
+            let result = next.clone().run(attempt, extensions).await;
+            retry += 1;
+            match self.retry_delay(idempotent, &result, retry) {
+                Some(delay) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        retry,
+                        delay_ms = delay.as_millis() as u64,
+                        "retrying request"
+                    );
+                    tokio::time::sleep(delay).await
+                }
+                None => return result,
+            }
+        }
+    }
+}
+
+/// Wraps every request in a span with the endpoint, status, latency and the
+/// `clerk_trace_id` of error responses. Headers are never recorded, so the
+/// authorization header doesn't end up in the logs.
+#[cfg(feature = "tracing")]
+#[derive(Clone)]
+struct TracingMiddleware;
+
+#[cfg(feature = "tracing")]
+impl TracingMiddleware {
+    /// Reads the body of an error response to find its `clerk_trace_id`,
+    /// returning an equivalent response for the generated code to parse
+    async fn inspect_error(resp: Response) -> ReqwestResult<(Response, Option<String>)> {
+        let status = resp.status();
+        let version = resp.version();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        let clerk_trace_id = serde_json::from_slice::<ClerkErrors>(&body)
+            .ok()
+            .and_then(|e| e.errors.into_iter().find_map(|e| e.clerk_trace_id));
+
+        let mut rebuilt = http::Response::new(body);
+        *rebuilt.status_mut() = status;
+        *rebuilt.version_mut() = version;
+        *rebuilt.headers_mut() = headers;
+
+        Ok((Response::from(rebuilt), clerk_trace_id))
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[async_trait]
+impl Middleware for TracingMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut HttpExtensions,
+        next: Next<'_>,
+    ) -> ReqwestResult<Response> {
+        use tracing::field::Empty;
+        use tracing::Instrument;
+
+        let span = tracing::info_span!(
+            "clerk_fapi.request",
+            method = %req.method(),
+            endpoint = %req.url().path(),
+            status = Empty,
+            latency_ms = Empty,
+            clerk_trace_id = Empty,
+        );
+
+        async move {
+            let started = std::time::Instant::now();
+            let result = next.run(req, extensions).await;
+            let span = tracing::Span::current();
+            span.record("latency_ms", started.elapsed().as_millis() as u64);
+
+            match result {
+                Ok(resp) if resp.status().is_client_error() || resp.status().is_server_error() => {
+                    span.record("status", resp.status().as_u16());
+                    let (resp, clerk_trace_id) = Self::inspect_error(resp).await?;
+                    if let Some(clerk_trace_id) = &clerk_trace_id {
+                        span.record("clerk_trace_id", clerk_trace_id.as_str());
+                    }
+                    tracing::warn!(status = resp.status().as_u16(), "request failed");
+                    Ok(resp)
+                }
+                Ok(resp) => {
+                    span.record("status", resp.status().as_u16());
+                    tracing::debug!("request completed");
+                    Ok(resp)
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "request failed");
+                    Err(e)
+                }
+            }
+        }
+        .instrument(span)
+        .await
+    }
+}
+
 /// The main client for interacting with Clerk's Frontend API
 #[derive(Clone)]
 pub struct ClerkFapiClient {
@@ -104,7 +290,9 @@ pub struct ClerkFapiClient {
 
 impl ClerkFapiClient {
     /// Creates a new ClerkFapiClient with the provided configuration
-    pub fn new(config: ClerkFapiConfiguration) -> Result<Self, String> {
+    ///
+    /// Fails if the underlying HTTP client can't be created.
+    pub fn new(config: ClerkFapiConfiguration) -> Result<Self, reqwest::Error> {
         // Create default headers
         let mut headers = HeaderMap::new();
         headers.insert("x-mobile", HeaderValue::from_static("1"));
@@ -114,10 +302,20 @@ impl ClerkFapiClient {
         let http_client = Client::builder()
             .default_headers(headers)
             .user_agent(&config.user_agent)
-            .build()
-            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+            .build()?;
+
+        let mut builder = ClientBuilder::new(http_client);
+        #[cfg(feature = "tracing")]
+        {
+            // Outermost, so a single span covers a request and all its retries
+            builder = builder.with(TracingMiddleware);
+        }
+        if let Some(policy) = config.retry_policy() {
+            // Before the other middleware so every attempt runs through them
+            builder = builder.with(RetryMiddleware::new(policy.clone()));
+        }
 
-        let client = ClientBuilder::new(http_client)
+        let client = builder
             .with(DefaultQueryMiddleware)
             .with(AuthorizationMiddleware::new(
                 config.store.clone(),
@@ -150,16 +348,14 @@ impl ClerkFapiClient {
         }))));
     }
 
-    async fn handle_client_update(
-        &self,
-        client: client_period_client::ClientPeriodClient,
-    ) -> Result<(), String> {
+    /// Passes the client returned by a mutating call to the update callback
+    ///
+    /// The callback is responsible for reporting its own failures, the API
+    /// call already succeeded and its response is returned either way.
+    async fn handle_client_update(&self, client: client_period_client::ClientPeriodClient) {
         if let Some(cb) = &self.update_client_callback {
             let mut cb = cb.lock().await; // Lock the Mutex to get mutable access
             (cb)(client).await; // Await the async callback
-            Ok(())
-        } else {
-            Ok(())
         }
     }
 
@@ -191,9 +387,7 @@ impl ClerkFapiClient {
     {
         let response =
             active_sessions_api::revoke_session(&self.config, session_id, clerk_session_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -203,9 +397,7 @@ impl ClerkFapiClient {
     ) -> Result<ClientPeriodClientWrappedBackupCodes, Error<backup_codes_api::CreateBackupCodesError>>
     {
         let response = backup_codes_api::create_backup_codes(&self.config).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -214,9 +406,7 @@ impl ClerkFapiClient {
         &self,
     ) -> Result<ClientPeriodDeleteSession, Error<client_api::DeleteClientSessionsError>> {
         let response = client_api::delete_client_sessions(&self.config).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -320,9 +510,7 @@ impl ClerkFapiClient {
             code,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -336,9 +524,7 @@ impl ClerkFapiClient {
     > {
         let response =
             domains_api::create_organization_domain(&self.config, organization_id, name).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -354,7 +540,7 @@ impl ClerkFapiClient {
             domains_api::delete_organization_domain(&self.config, organization_id, domain_id)
                 .await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             None => (),
         }
         Ok(response)
@@ -372,9 +558,7 @@ impl ClerkFapiClient {
         let response =
             domains_api::get_organization_domain(&self.config, organization_id, domain_id, name)
                 .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -390,9 +574,7 @@ impl ClerkFapiClient {
         let response =
             domains_api::list_organization_domains(&self.config, organization_id, limit, offset)
                 .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -412,9 +594,7 @@ impl ClerkFapiClient {
             affiliation_email_address,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -436,9 +616,7 @@ impl ClerkFapiClient {
             delete_pending,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -457,9 +635,7 @@ impl ClerkFapiClient {
             email_address,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -475,7 +651,7 @@ impl ClerkFapiClient {
             email_addresses_api::delete_email_address(&self.config, email_id, clerk_session_id)
                 .await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             None => (),
         }
         Ok(response)
@@ -492,9 +668,7 @@ impl ClerkFapiClient {
         let response =
             email_addresses_api::get_email_address(&self.config, email_id, clerk_session_id)
                 .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -524,9 +698,7 @@ impl ClerkFapiClient {
             redirect_url,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -546,9 +718,7 @@ impl ClerkFapiClient {
             code,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -577,12 +747,13 @@ impl ClerkFapiClient {
             external_accounts_api::delete_external_account(&self.config, external_account_id)
                 .await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             None => (),
         }
         Ok(response)
     }
 
+    #[deprecated(note = "use `post_o_auth_accounts_with` with `PostOAuthAccountsParams`")]
     pub async fn post_o_auth_accounts(
         &self,
         strategy: Option<&str>,
@@ -593,19 +764,34 @@ impl ClerkFapiClient {
     ) -> Result<
         ClientPeriodClientWrappedExternalAccount,
         Error<external_accounts_api::PostOAuthAccountsError>,
+    > {
+        self.post_o_auth_accounts_with(PostOAuthAccountsParams {
+            strategy: strategy.map(str::to_string),
+            redirect_url: redirect_url.map(str::to_string),
+            action_complete_redirect_url: action_complete_redirect_url.map(str::to_string),
+            code: code.map(str::to_string),
+            token: token.map(str::to_string),
+        })
+        .await
+    }
+
+    pub async fn post_o_auth_accounts_with(
+        &self,
+        params: PostOAuthAccountsParams,
+    ) -> Result<
+        ClientPeriodClientWrappedExternalAccount,
+        Error<external_accounts_api::PostOAuthAccountsError>,
     > {
         let response = external_accounts_api::post_o_auth_accounts(
             &self.config,
-            strategy,
-            redirect_url,
-            action_complete_redirect_url,
-            code,
-            token,
+            params.strategy.as_deref(),
+            params.redirect_url.as_deref(),
+            params.action_complete_redirect_url.as_deref(),
+            params.code.as_deref(),
+            params.token.as_deref(),
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -627,9 +813,7 @@ impl ClerkFapiClient {
             action_complete_redirect_url,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -645,9 +829,7 @@ impl ClerkFapiClient {
             external_account_id,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -673,12 +855,13 @@ impl ClerkFapiClient {
             role,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
+    #[deprecated(
+        note = "use `create_organization_invitations_with` with `OrganizationMemberParams`"
+    )]
     pub async fn create_organization_invitations(
         &self,
         organization_id: &str,
@@ -689,19 +872,37 @@ impl ClerkFapiClient {
     ) -> Result<
         ClientPeriodClientWrappedOrganizationInvitation,
         Error<invitations_api::CreateOrganizationInvitationsError>,
+    > {
+        self.create_organization_invitations_with(
+            organization_id,
+            OrganizationMemberParams {
+                user_id: user_id.map(str::to_string),
+                role: role.map(str::to_string),
+                email_address: email_address.map(str::to_string),
+                role2: role2.map(str::to_string),
+            },
+        )
+        .await
+    }
+
+    pub async fn create_organization_invitations_with(
+        &self,
+        organization_id: &str,
+        params: OrganizationMemberParams,
+    ) -> Result<
+        ClientPeriodClientWrappedOrganizationInvitation,
+        Error<invitations_api::CreateOrganizationInvitationsError>,
     > {
         let response = invitations_api::create_organization_invitations(
             &self.config,
             organization_id,
-            user_id,
-            role,
-            email_address,
-            role2,
+            params.user_id.as_deref(),
+            params.role.as_deref(),
+            params.email_address.as_deref(),
+            params.role2.as_deref(),
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -717,9 +918,7 @@ impl ClerkFapiClient {
             organization_id,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -741,9 +940,7 @@ impl ClerkFapiClient {
             status,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -761,13 +958,14 @@ impl ClerkFapiClient {
             invitation_id,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
     // Members API methods
+    #[deprecated(
+        note = "use `create_organization_membership_with` with `OrganizationMemberParams`"
+    )]
     pub async fn create_organization_membership(
         &self,
         organization_id: &str,
@@ -778,19 +976,37 @@ impl ClerkFapiClient {
     ) -> Result<
         ClientPeriodClientWrappedOrganizationMembership,
         Error<members_api::CreateOrganizationMembershipError>,
+    > {
+        self.create_organization_membership_with(
+            organization_id,
+            OrganizationMemberParams {
+                user_id: user_id.map(str::to_string),
+                role: role.map(str::to_string),
+                email_address: email_address.map(str::to_string),
+                role2: role2.map(str::to_string),
+            },
+        )
+        .await
+    }
+
+    pub async fn create_organization_membership_with(
+        &self,
+        organization_id: &str,
+        params: OrganizationMemberParams,
+    ) -> Result<
+        ClientPeriodClientWrappedOrganizationMembership,
+        Error<members_api::CreateOrganizationMembershipError>,
     > {
         let response = members_api::create_organization_membership(
             &self.config,
             organization_id,
-            user_id,
-            role,
-            email_address,
-            role2,
+            params.user_id.as_deref(),
+            params.role.as_deref(),
+            params.email_address.as_deref(),
+            params.role2.as_deref(),
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -810,9 +1026,7 @@ impl ClerkFapiClient {
             offset,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -826,9 +1040,7 @@ impl ClerkFapiClient {
     > {
         let response =
             members_api::remove_organization_member(&self.config, organization_id, user_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -848,9 +1060,7 @@ impl ClerkFapiClient {
             role,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -869,9 +1079,7 @@ impl ClerkFapiClient {
             request_id,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -893,9 +1101,7 @@ impl ClerkFapiClient {
             status,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -913,9 +1119,7 @@ impl ClerkFapiClient {
             request_id,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -966,9 +1170,7 @@ impl ClerkFapiClient {
         Error<organization_api::CreateOrganizationError>,
     > {
         let response = organization_api::create_organization(&self.config, name).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -981,7 +1183,7 @@ impl ClerkFapiClient {
     > {
         let response = organization_api::delete_organization(&self.config, organization_id).await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             None => (),
         }
         Ok(response)
@@ -997,7 +1199,7 @@ impl ClerkFapiClient {
         let response =
             organization_api::delete_organization_logo(&self.config, organization_id).await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             None => (),
         }
         Ok(response)
@@ -1009,9 +1211,7 @@ impl ClerkFapiClient {
     ) -> Result<ClientPeriodClientWrappedOrganization, Error<organization_api::GetOrganizationError>>
     {
         let response = organization_api::get_organization(&self.config, organization_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1027,25 +1227,22 @@ impl ClerkFapiClient {
         let response =
             organization_api::update_organization(&self.config, organization_id, name, slug)
                 .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
     pub async fn update_organization_logo(
         &self,
         organization_id: &str,
-        file: Option<std::path::PathBuf>,
+        file: impl Into<FileUpload>,
     ) -> Result<
         ClientPeriodClientWrappedOrganization,
         Error<organization_api::UpdateOrganizationLogoError>,
     > {
         let response =
-            organization_api::update_organization_logo(&self.config, organization_id, file).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+            organization_api::update_organization_logo(&self.config, organization_id, file.into())
+                .await?;
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1062,9 +1259,7 @@ impl ClerkFapiClient {
             invitation_id,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1080,9 +1275,7 @@ impl ClerkFapiClient {
             suggestion_id,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1099,7 +1292,7 @@ impl ClerkFapiClient {
         )
         .await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             None => (),
         }
         Ok(response)
@@ -1119,9 +1312,7 @@ impl ClerkFapiClient {
             offset,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1141,9 +1332,7 @@ impl ClerkFapiClient {
             status,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1161,13 +1350,14 @@ impl ClerkFapiClient {
             offset,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
     // Passkeys API methods
+    #[deprecated(
+        note = "use `attempt_passkey_verification_with` with `AttemptPasskeyVerificationParams`"
+    )]
     pub async fn attempt_passkey_verification(
         &self,
         passkey_id: &str,
@@ -1175,10 +1365,31 @@ impl ClerkFapiClient {
         ClientPeriodClientWrappedPasskey,
         Error<passkeys_api::AttemptPasskeyVerificationError>,
     > {
-        let response = passkeys_api::attempt_passkey_verification(&self.config, passkey_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.attempt_passkey_verification_with(
+            passkey_id,
+            AttemptPasskeyVerificationParams::default(),
+        )
+        .await
+    }
+
+    /// Verifies a new passkey with the JSON encoded `PublicKeyCredential`
+    /// the authenticator created, see [`crate::passkey`]
+    pub async fn attempt_passkey_verification_with(
+        &self,
+        passkey_id: &str,
+        params: AttemptPasskeyVerificationParams,
+    ) -> Result<
+        ClientPeriodClientWrappedPasskey,
+        Error<passkeys_api::AttemptPasskeyVerificationError>,
+    > {
+        let response = passkeys_api::attempt_passkey_verification(
+            &self.config,
+            passkey_id,
+            params.strategy.as_deref(),
+            params.public_key_credential.as_deref(),
+        )
+        .await?;
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1189,7 +1400,7 @@ impl ClerkFapiClient {
     {
         let response = passkeys_api::delete_passkey(&self.config, passkey_id).await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             None => (),
         }
         Ok(response)
@@ -1201,9 +1412,7 @@ impl ClerkFapiClient {
         name: Option<&str>,
     ) -> Result<ClientPeriodClientWrappedPasskey, Error<passkeys_api::PatchPasskeyError>> {
         let response = passkeys_api::patch_passkey(&self.config, passkey_id, name).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1212,9 +1421,7 @@ impl ClerkFapiClient {
         clerk_session_id: Option<&str>,
     ) -> Result<ClientPeriodClientWrappedPasskey, Error<passkeys_api::PostPasskeyError>> {
         let response = passkeys_api::post_passkey(&self.config, clerk_session_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1223,9 +1430,7 @@ impl ClerkFapiClient {
         passkey_id: &str,
     ) -> Result<ClientPeriodClientWrappedPasskey, Error<passkeys_api::ReadPasskeyError>> {
         let response = passkeys_api::read_passkey(&self.config, passkey_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1242,7 +1447,7 @@ impl ClerkFapiClient {
             phone_numbers_api::delete_phone_number(&self.config, phone_number_id, clerk_session_id)
                 .await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             None => (),
         }
         Ok(response)
@@ -1264,9 +1469,7 @@ impl ClerkFapiClient {
         let response =
             phone_numbers_api::post_phone_numbers(&self.config, clerk_session_id, phone_number)
                 .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1279,9 +1482,7 @@ impl ClerkFapiClient {
         let response =
             phone_numbers_api::read_phone_number(&self.config, phone_number_id, clerk_session_id)
                 .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1301,9 +1502,7 @@ impl ClerkFapiClient {
             strategy,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1325,9 +1524,7 @@ impl ClerkFapiClient {
             default_second_factor,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1347,9 +1544,7 @@ impl ClerkFapiClient {
             code,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1363,9 +1558,7 @@ impl ClerkFapiClient {
         let response =
             roles_api::list_organization_roles(&self.config, organization_id, limit, offset)
                 .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1377,7 +1570,7 @@ impl ClerkFapiClient {
     pub async fn saml_metadata(
         &self,
         saml_connection_id: &str,
-    ) -> Result<(), Error<saml_api::SamlMetadataError>> {
+    ) -> Result<String, Error<saml_api::SamlMetadataError>> {
         saml_api::saml_metadata(&self.config, saml_connection_id).await
     }
 
@@ -1407,9 +1600,7 @@ impl ClerkFapiClient {
         session_id: &str,
     ) -> Result<ResponsesPeriodClientPeriodSession, Error<sessions_api::EndSessionError>> {
         let response = sessions_api::end_session(&self.config, session_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1418,9 +1609,7 @@ impl ClerkFapiClient {
         session_id: &str,
     ) -> Result<ResponsesPeriodClientPeriodSession, Error<sessions_api::GetSessionError>> {
         let response = sessions_api::get_session(&self.config, session_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1431,9 +1620,7 @@ impl ClerkFapiClient {
         Error<sessions_api::RemoveClientSessionsAndRetainCookieError>,
     > {
         let response = sessions_api::remove_client_sessions_and_retain_cookie(&self.config).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1442,9 +1629,7 @@ impl ClerkFapiClient {
         session_id: &str,
     ) -> Result<ResponsesPeriodClientPeriodSession, Error<sessions_api::RemoveSessionError>> {
         let response = sessions_api::remove_session(&self.config, session_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1455,9 +1640,7 @@ impl ClerkFapiClient {
     ) -> Result<ResponsesPeriodClientPeriodSession, Error<sessions_api::TouchSessionError>> {
         let response =
             sessions_api::touch_session(&self.config, session_id, active_organization_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1469,6 +1652,9 @@ impl ClerkFapiClient {
         sign_ins_api::accept_ticket(&self.config, ticket).await
     }
 
+    #[deprecated(
+        note = "use `attempt_sign_in_factor_one_with` with `AttemptSignInFactorOneParams`"
+    )]
     pub async fn attempt_sign_in_factor_one(
         &self,
         sign_in_id: &str,
@@ -1480,22 +1666,43 @@ impl ClerkFapiClient {
         action_complete_redirect_url: Option<&str>,
         ticket: Option<&str>,
     ) -> Result<ResponsesPeriodClientPeriodSignIn, Error<sign_ins_api::AttemptSignInFactorOneError>>
+    {
+        self.attempt_sign_in_factor_one_with(
+            sign_in_id,
+            AttemptSignInFactorOneParams {
+                strategy: strategy.map(str::to_string),
+                code: code.map(str::to_string),
+                password: password.map(str::to_string),
+                signature: signature.map(str::to_string),
+                redirect_url: redirect_url.map(str::to_string),
+                action_complete_redirect_url: action_complete_redirect_url.map(str::to_string),
+                ticket: ticket.map(str::to_string),
+                public_key_credential: None,
+            },
+        )
+        .await
+    }
+
+    pub async fn attempt_sign_in_factor_one_with(
+        &self,
+        sign_in_id: &str,
+        params: AttemptSignInFactorOneParams,
+    ) -> Result<ResponsesPeriodClientPeriodSignIn, Error<sign_ins_api::AttemptSignInFactorOneError>>
     {
         let response = sign_ins_api::attempt_sign_in_factor_one(
             &self.config,
             sign_in_id,
-            strategy,
-            code,
-            password,
-            signature,
-            redirect_url,
-            action_complete_redirect_url,
-            ticket,
+            params.strategy.as_deref(),
+            params.code.as_deref(),
+            params.password.as_deref(),
+            params.signature.as_deref(),
+            params.redirect_url.as_deref(),
+            params.action_complete_redirect_url.as_deref(),
+            params.ticket.as_deref(),
+            params.public_key_credential.as_deref(),
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1509,12 +1716,11 @@ impl ClerkFapiClient {
         let response =
             sign_ins_api::attempt_sign_in_factor_two(&self.config, sign_in_id, strategy, code)
                 .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
+    #[deprecated(note = "use `create_sign_in_with` with `CreateSignInParams`")]
     pub async fn create_sign_in(
         &self,
         strategy: Option<&str>,
@@ -1526,23 +1732,39 @@ impl ClerkFapiClient {
         transfer: Option<bool>,
         code: Option<&str>,
         token: Option<&str>,
+    ) -> Result<ResponsesPeriodClientPeriodSignIn, Error<sign_ins_api::CreateSignInError>> {
+        self.create_sign_in_with(CreateSignInParams {
+            strategy: strategy.map(str::to_string),
+            identifier: identifier.map(str::to_string),
+            password: password.map(str::to_string),
+            ticket: ticket.map(str::to_string),
+            redirect_url: redirect_url.map(str::to_string),
+            action_complete_redirect_url: action_complete_redirect_url.map(str::to_string),
+            transfer,
+            code: code.map(str::to_string),
+            token: token.map(str::to_string),
+        })
+        .await
+    }
+
+    pub async fn create_sign_in_with(
+        &self,
+        params: CreateSignInParams,
     ) -> Result<ResponsesPeriodClientPeriodSignIn, Error<sign_ins_api::CreateSignInError>> {
         let response = sign_ins_api::create_sign_in(
             &self.config,
-            strategy,
-            identifier,
-            password,
-            ticket,
-            redirect_url,
-            action_complete_redirect_url,
-            transfer,
-            code,
-            token,
+            params.strategy.as_deref(),
+            params.identifier.as_deref(),
+            params.password.as_deref(),
+            params.ticket.as_deref(),
+            params.redirect_url.as_deref(),
+            params.action_complete_redirect_url.as_deref(),
+            params.transfer,
+            params.code.as_deref(),
+            params.token.as_deref(),
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1550,13 +1772,27 @@ impl ClerkFapiClient {
         &self,
         sign_in_id: &str,
     ) -> Result<ResponsesPeriodClientPeriodSignIn, Error<sign_ins_api::GetSignInError>> {
-        let response = sign_ins_api::get_sign_in(&self.config, sign_in_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        let response = sign_ins_api::get_sign_in(&self.config, sign_in_id, None).await?;
+        self.handle_client_update(*response.client.clone()).await;
+        Ok(response)
+    }
+
+    /// Fetches the sign in after an OAuth or SSO redirect, with the
+    /// `rotating_token_nonce` query parameter of the redirect
+    pub async fn get_sign_in_with_nonce(
+        &self,
+        sign_in_id: &str,
+        rotating_token_nonce: &str,
+    ) -> Result<ResponsesPeriodClientPeriodSignIn, Error<sign_ins_api::GetSignInError>> {
+        let response =
+            sign_ins_api::get_sign_in(&self.config, sign_in_id, Some(rotating_token_nonce)).await?;
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
+    #[deprecated(
+        note = "use `prepare_sign_in_factor_one_with` with `PrepareSignInFactorOneParams`"
+    )]
     pub async fn prepare_sign_in_factor_one(
         &self,
         sign_in_id: &str,
@@ -1568,22 +1804,41 @@ impl ClerkFapiClient {
         redirect_url: Option<&str>,
         action_complete_redirect_url: Option<&str>,
     ) -> Result<ResponsesPeriodClientPeriodSignIn, Error<sign_ins_api::PrepareSignInFactorOneError>>
+    {
+        self.prepare_sign_in_factor_one_with(
+            sign_in_id,
+            PrepareSignInFactorOneParams {
+                strategy: strategy.map(str::to_string),
+                email_address_id: email_address_id.map(str::to_string),
+                phone_number_id: phone_number_id.map(str::to_string),
+                web3_wallet_id: web3_wallet_id.map(str::to_string),
+                passkey_id: passkey_id.map(str::to_string),
+                redirect_url: redirect_url.map(str::to_string),
+                action_complete_redirect_url: action_complete_redirect_url.map(str::to_string),
+            },
+        )
+        .await
+    }
+
+    pub async fn prepare_sign_in_factor_one_with(
+        &self,
+        sign_in_id: &str,
+        params: PrepareSignInFactorOneParams,
+    ) -> Result<ResponsesPeriodClientPeriodSignIn, Error<sign_ins_api::PrepareSignInFactorOneError>>
     {
         let response = sign_ins_api::prepare_sign_in_factor_one(
             &self.config,
             sign_in_id,
-            strategy,
-            email_address_id,
-            phone_number_id,
-            web3_wallet_id,
-            passkey_id,
-            redirect_url,
-            action_complete_redirect_url,
+            params.strategy.as_deref(),
+            params.email_address_id.as_deref(),
+            params.phone_number_id.as_deref(),
+            params.web3_wallet_id.as_deref(),
+            params.passkey_id.as_deref(),
+            params.redirect_url.as_deref(),
+            params.action_complete_redirect_url.as_deref(),
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1601,9 +1856,7 @@ impl ClerkFapiClient {
             phone_number_id,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1620,9 +1873,7 @@ impl ClerkFapiClient {
             sign_out_of_other_sessions,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1649,12 +1900,11 @@ impl ClerkFapiClient {
             signature,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
+    #[deprecated(note = "use `create_sign_ups_with` with `CreateSignUpParams`")]
     pub async fn create_sign_ups(
         &self,
         transfer: Option<bool>,
@@ -1675,32 +1925,72 @@ impl ClerkFapiClient {
         captcha_error: Option<&str>,
         code: Option<&str>,
         token: Option<&str>,
+    ) -> Result<ResponsesPeriodClientPeriodSignUp, Error<sign_ups_api::CreateSignUpsError>> {
+        self.create_sign_ups_raw(
+            CreateSignUpParams {
+                transfer,
+                password: password.map(str::to_string),
+                first_name: first_name.map(str::to_string),
+                last_name: last_name.map(str::to_string),
+                username: username.map(str::to_string),
+                email_address: email_address.map(str::to_string),
+                phone_number: phone_number.map(str::to_string),
+                email_address_or_phone_number: email_address_or_phone_number.map(str::to_string),
+                unsafe_metadata: None,
+                strategy: strategy.map(str::to_string),
+                action_complete_redirect_url: action_complete_redirect_url.map(str::to_string),
+                redirect_url: redirect_url.map(str::to_string),
+                ticket: ticket.map(str::to_string),
+                web3_wallet: web3_wallet.map(str::to_string),
+                captcha_token: captcha_token.map(str::to_string),
+                captcha_error: captcha_error.map(str::to_string),
+                code: code.map(str::to_string),
+                token: token.map(str::to_string),
+            },
+            unsafe_metadata,
+        )
+        .await
+    }
+
+    pub async fn create_sign_ups_with(
+        &self,
+        params: CreateSignUpParams,
+    ) -> Result<ResponsesPeriodClientPeriodSignUp, Error<sign_ups_api::CreateSignUpsError>> {
+        let unsafe_metadata = params.unsafe_metadata.as_ref().map(JsonValue::to_string);
+        self.create_sign_ups_raw(params, unsafe_metadata.as_deref())
+            .await
+    }
+
+    /// Sends `unsafe_metadata` as given, so the deprecated positional method
+    /// keeps forwarding it without parsing
+    async fn create_sign_ups_raw(
+        &self,
+        params: CreateSignUpParams,
+        unsafe_metadata: Option<&str>,
     ) -> Result<ResponsesPeriodClientPeriodSignUp, Error<sign_ups_api::CreateSignUpsError>> {
         let response = sign_ups_api::create_sign_ups(
             &self.config,
-            transfer,
-            password,
-            first_name,
-            last_name,
-            username,
-            email_address,
-            phone_number,
-            email_address_or_phone_number,
+            params.transfer,
+            params.password.as_deref(),
+            params.first_name.as_deref(),
+            params.last_name.as_deref(),
+            params.username.as_deref(),
+            params.email_address.as_deref(),
+            params.phone_number.as_deref(),
+            params.email_address_or_phone_number.as_deref(),
             unsafe_metadata,
-            strategy,
-            action_complete_redirect_url,
-            redirect_url,
-            ticket,
-            web3_wallet,
-            captcha_token,
-            captcha_error,
-            code,
-            token,
+            params.strategy.as_deref(),
+            params.action_complete_redirect_url.as_deref(),
+            params.redirect_url.as_deref(),
+            params.ticket.as_deref(),
+            params.web3_wallet.as_deref(),
+            params.captcha_token.as_deref(),
+            params.captcha_error.as_deref(),
+            params.code.as_deref(),
+            params.token.as_deref(),
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1709,9 +1999,7 @@ impl ClerkFapiClient {
         id: &str,
     ) -> Result<ResponsesPeriodClientPeriodSignUp, Error<sign_ups_api::GetSignUpsError>> {
         let response = sign_ups_api::get_sign_ups(&self.config, id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1725,12 +2013,11 @@ impl ClerkFapiClient {
     > {
         let response =
             sign_ups_api::prepare_sign_ups_verification(&self.config, id, strategy).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
+    #[deprecated(note = "use `update_sign_ups_with` with `UpdateSignUpParams`")]
     pub async fn update_sign_ups(
         &self,
         id: &str,
@@ -1749,30 +2036,69 @@ impl ClerkFapiClient {
         web3_wallet: Option<&str>,
         code: Option<&str>,
         token: Option<&str>,
+    ) -> Result<ResponsesPeriodClientPeriodSignUp, Error<sign_ups_api::UpdateSignUpsError>> {
+        self.update_sign_ups_raw(
+            id,
+            UpdateSignUpParams {
+                password: password.map(str::to_string),
+                first_name: first_name.map(str::to_string),
+                last_name: last_name.map(str::to_string),
+                username: username.map(str::to_string),
+                email_address: email_address.map(str::to_string),
+                phone_number: phone_number.map(str::to_string),
+                email_address_or_phone_number: email_address_or_phone_number.map(str::to_string),
+                unsafe_metadata: None,
+                strategy: strategy.map(str::to_string),
+                redirect_url: redirect_url.map(str::to_string),
+                action_complete_redirect_url: action_complete_redirect_url.map(str::to_string),
+                ticket: ticket.map(str::to_string),
+                web3_wallet: web3_wallet.map(str::to_string),
+                code: code.map(str::to_string),
+                token: token.map(str::to_string),
+            },
+            unsafe_metadata,
+        )
+        .await
+    }
+
+    pub async fn update_sign_ups_with(
+        &self,
+        id: &str,
+        params: UpdateSignUpParams,
+    ) -> Result<ResponsesPeriodClientPeriodSignUp, Error<sign_ups_api::UpdateSignUpsError>> {
+        let unsafe_metadata = params.unsafe_metadata.as_ref().map(JsonValue::to_string);
+        self.update_sign_ups_raw(id, params, unsafe_metadata.as_deref())
+            .await
+    }
+
+    /// Sends `unsafe_metadata` as given, see [`Self::create_sign_ups_raw`]
+    async fn update_sign_ups_raw(
+        &self,
+        id: &str,
+        params: UpdateSignUpParams,
+        unsafe_metadata: Option<&str>,
     ) -> Result<ResponsesPeriodClientPeriodSignUp, Error<sign_ups_api::UpdateSignUpsError>> {
         let response = sign_ups_api::update_sign_ups(
             &self.config,
             id,
-            password,
-            first_name,
-            last_name,
-            username,
-            email_address,
-            phone_number,
-            email_address_or_phone_number,
+            params.password.as_deref(),
+            params.first_name.as_deref(),
+            params.last_name.as_deref(),
+            params.username.as_deref(),
+            params.email_address.as_deref(),
+            params.phone_number.as_deref(),
+            params.email_address_or_phone_number.as_deref(),
             unsafe_metadata,
-            strategy,
-            redirect_url,
-            action_complete_redirect_url,
-            ticket,
-            web3_wallet,
-            code,
-            token,
+            params.strategy.as_deref(),
+            params.redirect_url.as_deref(),
+            params.action_complete_redirect_url.as_deref(),
+            params.ticket.as_deref(),
+            params.web3_wallet.as_deref(),
+            params.code.as_deref(),
+            params.token.as_deref(),
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1782,7 +2108,7 @@ impl ClerkFapiClient {
     ) -> Result<ClientPeriodClientWrappedDeletedObject, Error<totp_api::DeleteTotpError>> {
         let response = totp_api::delete_totp(&self.config).await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             None => (),
         }
         Ok(response)
@@ -1792,9 +2118,7 @@ impl ClerkFapiClient {
         &self,
     ) -> Result<ClientPeriodClientWrappedTotp, Error<totp_api::PostTotpError>> {
         let response = totp_api::post_totp(&self.config).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1803,9 +2127,7 @@ impl ClerkFapiClient {
         code: Option<&str>,
     ) -> Result<ClientPeriodClientWrappedTotp, Error<totp_api::VerifyTotpError>> {
         let response = totp_api::verify_totp(&self.config, code).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1823,9 +2145,7 @@ impl ClerkFapiClient {
             sign_out_of_other_sessions,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1835,7 +2155,7 @@ impl ClerkFapiClient {
     {
         let response = user_api::delete_profile_image(&self.config).await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             None => (),
         }
         Ok(response)
@@ -1846,7 +2166,7 @@ impl ClerkFapiClient {
     ) -> Result<ClientPeriodClientWrappedDeletedObject, Error<user_api::DeleteUserError>> {
         let response = user_api::delete_user(&self.config).await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             _ => (),
         }
         Ok(response)
@@ -1856,12 +2176,11 @@ impl ClerkFapiClient {
         &self,
     ) -> Result<ClientPeriodClientWrappedUser, Error<user_api::GetUserError>> {
         let response = user_api::get_user(&self.config).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
+    #[deprecated(note = "use `patch_user_with` with `PatchUserParams`")]
     pub async fn patch_user(
         &self,
         username: Option<&str>,
@@ -1871,21 +2190,49 @@ impl ClerkFapiClient {
         primary_phone_number_id: Option<&str>,
         primary_web3_wallet_id: Option<&str>,
         unsafe_metadata: Option<&str>,
+    ) -> Result<ClientPeriodClientWrappedUser, Error<user_api::PatchUserError>> {
+        self.patch_user_raw(
+            PatchUserParams {
+                username: username.map(str::to_string),
+                first_name: first_name.map(str::to_string),
+                last_name: last_name.map(str::to_string),
+                primary_email_address_id: primary_email_address_id.map(str::to_string),
+                primary_phone_number_id: primary_phone_number_id.map(str::to_string),
+                primary_web3_wallet_id: primary_web3_wallet_id.map(str::to_string),
+                unsafe_metadata: None,
+            },
+            unsafe_metadata,
+        )
+        .await
+    }
+
+    pub async fn patch_user_with(
+        &self,
+        params: PatchUserParams,
+    ) -> Result<ClientPeriodClientWrappedUser, Error<user_api::PatchUserError>> {
+        let unsafe_metadata = params.unsafe_metadata.as_ref().map(JsonValue::to_string);
+        self.patch_user_raw(params, unsafe_metadata.as_deref())
+            .await
+    }
+
+    /// Sends `unsafe_metadata` as given, see [`Self::create_sign_ups_raw`]
+    async fn patch_user_raw(
+        &self,
+        params: PatchUserParams,
+        unsafe_metadata: Option<&str>,
     ) -> Result<ClientPeriodClientWrappedUser, Error<user_api::PatchUserError>> {
         let response = user_api::patch_user(
             &self.config,
-            username,
-            first_name,
-            last_name,
-            primary_email_address_id,
-            primary_phone_number_id,
-            primary_web3_wallet_id,
+            params.username.as_deref(),
+            params.first_name.as_deref(),
+            params.last_name.as_deref(),
+            params.primary_email_address_id.as_deref(),
+            params.primary_phone_number_id.as_deref(),
+            params.primary_web3_wallet_id.as_deref(),
             unsafe_metadata,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1894,23 +2241,19 @@ impl ClerkFapiClient {
         current_password: Option<&str>,
     ) -> Result<ClientPeriodClientWrappedUser, Error<user_api::RemovePasswordError>> {
         let response = user_api::remove_password(&self.config, current_password).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
     pub async fn update_profile_image(
         &self,
-        file: Option<std::path::PathBuf>,
+        file: impl Into<FileUpload>,
     ) -> Result<
         ResponsesPeriodClientPeriodClientWrappedImage,
         Error<user_api::UpdateProfileImageError>,
     > {
-        let response = user_api::update_profile_image(&self.config, file).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        let response = user_api::update_profile_image(&self.config, file.into()).await?;
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1925,9 +2268,7 @@ impl ClerkFapiClient {
         let response =
             web3_wallets_api::attempt_web3_wallet_verification(&self.config, web3_wallet_id)
                 .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1940,7 +2281,7 @@ impl ClerkFapiClient {
     > {
         let response = web3_wallets_api::delete_web3_wallet(&self.config, web3_wallet_id).await?;
         match response.client.clone() {
-            Some(client) => self.handle_client_update(*client).await.unwrap(),
+            Some(client) => self.handle_client_update(*client).await,
             None => (),
         }
         Ok(response)
@@ -1967,9 +2308,7 @@ impl ClerkFapiClient {
             redirect_url,
         )
         .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1983,9 +2322,7 @@ impl ClerkFapiClient {
         let response =
             web3_wallets_api::prepare_web3_wallet_verification(&self.config, web3_wallet_id)
                 .await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -1995,9 +2332,7 @@ impl ClerkFapiClient {
     ) -> Result<ClientPeriodClientWrappedWeb3Wallet, Error<web3_wallets_api::ReadWeb3WalletError>>
     {
         let response = web3_wallets_api::read_web3_wallet(&self.config, web3_wallet_id).await?;
-        self.handle_client_update(*response.client.clone())
-            .await
-            .unwrap();
+        self.handle_client_update(*response.client.clone()).await;
         Ok(response)
     }
 
@@ -2058,4 +2393,314 @@ mod tests {
         assert!(!client.config.user_agent.is_none());
         assert!(client.update_client_callback.is_none());
     }
+
+    #[tokio::test]
+    async fn test_update_profile_image_uploads_file() {
+        let mut server = mockito::Server::new_async().await;
+        let image_mock = server
+            .mock("POST", "/v1/me/profile_image?_is_native=1")
+            .match_header(
+                "content-type",
+                mockito::Matcher::Regex("^multipart/form-data; boundary=".to_string()),
+            )
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex(r#"name="file"; filename="avatar.png""#.to_string()),
+                mockito::Matcher::Regex("Content-Type: image/png".to_string()),
+                mockito::Matcher::Regex("png-bytes".to_string()),
+            ]))
+            .with_status(200)
+            .with_body(
+                serde_json::json!({
+                    "response": {
+                        "object": "image",
+                        "id": "img_123",
+                        "public_url": "https://img.clerk.com/img_123"
+                    },
+                    "client": {
+                        "object": "client",
+                        "id": "client_123",
+                        "sign_in": null,
+                        "sign_up": null,
+                        "sessions": [],
+                        "last_active_session_id": null,
+                        "created_at": 1704067200,
+                        "updated_at": 1704067200
+                    }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+        let mut client = ClerkFapiClient::new(config).unwrap();
+
+        let updated_client = Arc::new(Mutex::new(None));
+        let updated_client_ref = updated_client.clone();
+        client.set_update_client_callback(move |c| {
+            let updated_client_ref = updated_client_ref.clone();
+            async move {
+                *updated_client_ref.lock().await = c.id;
+            }
+        });
+
+        let response = client
+            .update_profile_image(FileUpload::from_bytes(b"png-bytes".to_vec(), "avatar.png"))
+            .await
+            .unwrap();
+
+        image_mock.assert_async().await;
+        assert_eq!(response.response.id, Some("img_123".to_string()));
+        assert_eq!(*updated_client.lock().await, Some("client_123".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_patch_user_with_params_serializes_metadata() {
+        let mut server = mockito::Server::new_async().await;
+        let patch_mock = server
+            .mock("PATCH", "/v1/me?_is_native=1")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("first_name".to_string(), "Ada".to_string()),
+                mockito::Matcher::UrlEncoded(
+                    "unsafe_metadata".to_string(),
+                    r#"{"plan":"pro"}"#.to_string(),
+                ),
+            ]))
+            .with_status(200)
+            .with_body(
+                serde_json::json!({
+                    "response": ClientPeriodUser::default(),
+                    "client": ClientPeriodClient::default(),
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+        let client = ClerkFapiClient::new(config).unwrap();
+
+        client
+            .patch_user_with(
+                PatchUserParams::default()
+                    .first_name("Ada")
+                    .unsafe_metadata(serde_json::json!({"plan": "pro"})),
+            )
+            .await
+            .unwrap();
+        patch_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn test_positional_shim_sends_metadata_unchanged() {
+        let mut server = mockito::Server::new_async().await;
+        let patch_mock = server
+            .mock("PATCH", "/v1/me?_is_native=1")
+            .match_body(mockito::Matcher::UrlEncoded(
+                "unsafe_metadata".to_string(),
+                "not json".to_string(),
+            ))
+            .with_status(200)
+            .with_body(
+                serde_json::json!({
+                    "response": ClientPeriodUser::default(),
+                    "client": ClientPeriodClient::default(),
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+        let client = ClerkFapiClient::new(config).unwrap();
+
+        client
+            .patch_user(None, None, None, None, None, None, Some("not json"))
+            .await
+            .unwrap();
+        patch_mock.assert_async().await;
+    }
+
+    fn retrying_client(server_url: String) -> ClerkFapiClient {
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server_url),
+            None,
+        )
+        .unwrap()
+        .with_retry_policy(RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        });
+        ClerkFapiClient::new(config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_retries_idempotent_request_on_server_error() {
+        let mut server = mockito::Server::new_async().await;
+        let failing_mock = server
+            .mock("GET", "/v1/health?_is_native=1")
+            .with_status(502)
+            .expect(2)
+            .create_async()
+            .await;
+        let ok_mock = server
+            .mock("GET", "/v1/health?_is_native=1")
+            .with_status(200)
+            .with_body(r#"{"status":"pass"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = retrying_client(server.url());
+        let health = client.get_health().await.unwrap();
+
+        assert_eq!(health["status"], "pass");
+        failing_mock.assert_async().await;
+        ok_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_post_on_server_error() {
+        let mut server = mockito::Server::new_async().await;
+        let failing_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/tokens?_is_native=1")
+            .with_status(502)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = retrying_client(server.url());
+        assert!(client.create_session_token("sess_123", None).await.is_err());
+        failing_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limited_request_after_delay() {
+        let mut server = mockito::Server::new_async().await;
+        let limited_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/tokens?_is_native=1")
+            .with_status(429)
+            .with_header("retry-after", "0")
+            .expect(1)
+            .create_async()
+            .await;
+        let ok_mock = server
+            .mock("POST", "/v1/client/sessions/sess_123/tokens?_is_native=1")
+            .with_status(200)
+            .with_body(r#"{"object":"token","jwt":"test.jwt.token"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = retrying_client(server.url());
+        let token = client.create_session_token("sess_123", None).await.unwrap();
+
+        assert_eq!(token.jwt, Some("test.jwt.token".to_string()));
+        limited_mock.assert_async().await;
+        ok_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let mut server = mockito::Server::new_async().await;
+        let failing_mock = server
+            .mock("GET", "/v1/health?_is_native=1")
+            .with_status(503)
+            .expect(4)
+            .create_async()
+            .await;
+
+        let client = retrying_client(server.url());
+        assert!(client.get_health().await.is_err());
+        failing_mock.assert_async().await;
+    }
+
+    #[cfg(feature = "tracing")]
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<parking_lot::Mutex<Vec<u8>>>);
+
+    #[cfg(feature = "tracing")]
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn test_requests_are_traced_without_credentials() {
+        let mut server = mockito::Server::new_async().await;
+        let env_mock = server
+            .mock("GET", "/v1/environment?_is_native=1")
+            .with_status(401)
+            .with_header("Authorization", "Bearer secret-client-jwt")
+            .with_body(
+                serde_json::json!({
+                    "errors": [{
+                        "message": "Unauthorized",
+                        "long_message": "Invalid authorization",
+                        "code": "authorization_invalid",
+                        "clerk_trace_id": "trace_456"
+                    }]
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+        let client = ClerkFapiClient::new(config).unwrap();
+
+        // The error body is still available after the middleware read it
+        match client.get_environment().await {
+            Err(Error::ResponseError(content)) => {
+                assert_eq!(content.status, StatusCode::UNAUTHORIZED);
+                assert!(content.content.contains("authorization_invalid"));
+            }
+            other => panic!("expected a response error, got {:?}", other),
+        }
+        env_mock.assert_async().await;
+
+        let logs = String::from_utf8(logs.0.lock().clone()).unwrap();
+        assert!(logs.contains("endpoint=/v1/environment"));
+        assert!(logs.contains("status=401"));
+        assert!(logs.contains("clerk_trace_id=\"trace_456\""));
+        assert!(logs.contains("stored authorization header"));
+        assert!(!logs.contains("secret-client-jwt"));
+    }
 }
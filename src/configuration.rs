@@ -1,22 +1,33 @@
 use crate::apis::configuration::Configuration as ApiConfiguration;
+use async_trait::async_trait;
 use base64::{engine::general_purpose, Engine as _};
 use futures::future::BoxFuture;
 use parking_lot::RwLock;
 use pin_project_lite::pin_project;
 use reqwest::header::{HeaderMap, HeaderValue};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
 use serde_json::Value as JsonValue;
+use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
+use std::error;
 use std::fmt;
+use std::fs::{self, File, OpenOptions};
 use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::str;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const NAME: &str = env!("CARGO_PKG_NAME");
 const PUBLISHABLE_KEY_LIVE_PREFIX: &str = "pk_live_";
 const PUBLISHABLE_KEY_TEST_PREFIX: &str = "pk_test_";
+const DEFAULT_TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Clone)]
 struct PublishableKey {
@@ -106,6 +117,200 @@ pub trait Store: Send + Sync + std::fmt::Debug {
     fn delete(&self, key: &str) -> bool;
 }
 
+/// Error returned by an [`AsyncStore`] backend
+#[derive(Debug)]
+pub struct StoreError(Box<dyn error::Error + Send + Sync>);
+
+impl StoreError {
+    /// Wraps the error of a store backend
+    pub fn new(error: impl Into<Box<dyn error::Error + Send + Sync>>) -> Self {
+        Self(error.into())
+    }
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+/// Asynchronous, fallible variant of [`Store`] for backends that do I/O,
+/// such as databases or files
+///
+/// Existing [`Store`] implementations can be used through [`SyncStoreAdapter`].
+#[async_trait]
+pub trait AsyncStore: Send + Sync + std::fmt::Debug {
+    /// Inserts a key-value pair into the store.
+    async fn set(&self, key: &str, value: JsonValue) -> Result<(), StoreError>;
+
+    /// Returns the value for the given `key` or `None` if the key does not exist.
+    async fn get(&self, key: &str) -> Result<Option<JsonValue>, StoreError>;
+
+    /// Returns `true` if the given `key` exists in the store.
+    async fn has(&self, key: &str) -> Result<bool, StoreError>;
+
+    /// Removes a key-value pair from the store.
+    /// Returns true if a value was removed, false if the key didn't exist.
+    async fn delete(&self, key: &str) -> Result<bool, StoreError>;
+}
+
+/// Adapts a synchronous [`Store`] to the [`AsyncStore`] interface
+#[derive(Debug, Clone)]
+pub struct SyncStoreAdapter(Arc<dyn Store>);
+
+impl SyncStoreAdapter {
+    /// Wraps the store, its calls run inline on the async task and never fail
+    pub fn new(store: Arc<dyn Store>) -> Self {
+        Self(store)
+    }
+}
+
+#[async_trait]
+impl AsyncStore for SyncStoreAdapter {
+    async fn set(&self, key: &str, value: JsonValue) -> Result<(), StoreError> {
+        self.0.set(key, value);
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<JsonValue>, StoreError> {
+        Ok(self.0.get(key))
+    }
+
+    async fn has(&self, key: &str) -> Result<bool, StoreError> {
+        Ok(self.0.has(key))
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, StoreError> {
+        Ok(self.0.delete(key))
+    }
+}
+
+/// Controls how [`crate::clerk::Clerk`] refreshes a cached environment and
+/// client in the background after loading them from the store
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefreshPolicy {
+    /// Whether the background refresh runs at all
+    pub enabled: bool,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between retries
+    pub max_backoff: Duration,
+    /// Factor the delay is multiplied with after every failed attempt
+    pub multiplier: f64,
+    /// Fraction of the delay that is randomly added or removed, between 0 and 1
+    pub jitter: f64,
+    /// Maximum number of attempts, `None` retries until the refresh succeeds
+    pub max_attempts: Option<u32>,
+}
+
+impl RefreshPolicy {
+    /// A policy that never refreshes in the background
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the delay to wait after the given (1 based) failed attempt
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let base = self.initial_backoff.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        let capped = base.min(self.max_backoff.as_secs_f64());
+
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = 1.0 + jitter * (2.0 * random_unit() - 1.0);
+
+        Duration::from_secs_f64((capped * factor).max(0.0))
+    }
+}
+
+impl Default for RefreshPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            initial_backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(15 * 60),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: None,
+        }
+    }
+}
+
+/// Controls how listeners added with [`crate::clerk::Clerk::add_async_listener`]
+/// are run
+///
+/// Every listener runs in its own background task, so a slow listener never
+/// delays the client update that triggered it or the other listeners, and a
+/// panicking listener only loses the update it panicked on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListenerPolicy {
+    /// Updates are handled one at a time, in the order they happened
+    #[default]
+    Ordered,
+    /// Up to the given number of updates are handled at the same time,
+    /// later updates may finish before earlier ones
+    Concurrent(usize),
+}
+
+/// Controls how [`crate::clerk_fapi::ClerkFapiClient`] retries requests that
+/// failed with a transient error
+///
+/// Idempotent requests are retried on connection errors and `5xx` responses.
+/// Any request is retried on `429 Too Many Requests`, waiting for the
+/// `Retry-After` header when the response has one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the initial request
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every following one
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between retries
+    pub max_backoff: Duration,
+    /// Upper bound for a delay requested by a `Retry-After` header
+    pub max_retry_after: Duration,
+}
+
+impl RetryPolicy {
+    /// Returns the delay to wait before the given (1 based) retry
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(31);
+        self.initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Returns a random number in `[0, 1)`, good enough for spreading out retries
+fn random_unit() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
 #[derive(Clone, Default, Debug)]
 struct DefaultStore {
     inner: Arc<RwLock<HashMap<String, JsonValue>>>,
@@ -133,14 +338,394 @@ impl Store for DefaultStore {
     }
 }
 
+/// Store that persists values as a JSON object in a file, so a signed in
+/// client survives restarts
+///
+/// Writes go to a temporary file that is renamed over the original, so the
+/// file is never left half written. Every operation holds a lock on a
+/// `<path>.lock` sibling file, which makes it safe for several processes to
+/// share one credential file. On Unix both files are created with `0600`
+/// permissions.
+///
+/// The [`AsyncStore`] implementation runs the file I/O on the blocking thread
+/// pool and reports failures, the [`Store`] implementation ignores them.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    /// Creates a store backed by the file at `path`, the file and its parent
+    /// directories are created on the first write
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the JSON file the values are stored in
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn sibling_path(&self, suffix: &str) -> PathBuf {
+        let mut file_name = self.path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(suffix);
+        self.path.with_file_name(file_name)
+    }
+
+    fn open_private(options: &mut OpenOptions, path: &Path) -> io::Result<File> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(path)
+    }
+
+    /// Opens and locks the lock file, the lock is released when the returned
+    /// file is dropped
+    fn lock(&self, exclusive: bool) -> io::Result<File> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let lock_file = Self::open_private(
+            OpenOptions::new().read(true).write(true).create(true),
+            &self.sibling_path(".lock"),
+        )?;
+        if exclusive {
+            lock_file.lock()?;
+        } else {
+            lock_file.lock_shared()?;
+        }
+        Ok(lock_file)
+    }
+
+    fn read_values(&self) -> io::Result<serde_json::Map<String, JsonValue>> {
+        match fs::read(&self.path) {
+            Ok(bytes) if bytes.is_empty() => Ok(serde_json::Map::new()),
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(serde_json::Map::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write_values(&self, values: &serde_json::Map<String, JsonValue>) -> io::Result<()> {
+        let tmp_path = self.sibling_path(&format!(".{}.tmp", uuid::Uuid::new_v4()));
+        let result = (|| {
+            let mut file =
+                Self::open_private(OpenOptions::new().write(true).create_new(true), &tmp_path)?;
+            serde_json::to_writer(&mut file, values)?;
+            file.sync_all()?;
+            fs::rename(&tmp_path, &self.path)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result
+    }
+
+    fn read<T>(
+        &self,
+        f: impl FnOnce(&serde_json::Map<String, JsonValue>) -> T,
+    ) -> Result<T, StoreError> {
+        let _lock = self.lock(false).map_err(StoreError::new)?;
+        let values = self.read_values().map_err(StoreError::new)?;
+        Ok(f(&values))
+    }
+
+    fn update<T>(
+        &self,
+        f: impl FnOnce(&mut serde_json::Map<String, JsonValue>) -> T,
+    ) -> Result<T, StoreError> {
+        let _lock = self.lock(true).map_err(StoreError::new)?;
+        let mut values = self.read_values().map_err(StoreError::new)?;
+        let result = f(&mut values);
+        self.write_values(&values).map_err(StoreError::new)?;
+        Ok(result)
+    }
+
+    fn try_set(&self, key: &str, value: JsonValue) -> Result<(), StoreError> {
+        self.update(|values| {
+            values.insert(key.to_string(), value);
+        })
+    }
+
+    fn try_get(&self, key: &str) -> Result<Option<JsonValue>, StoreError> {
+        self.read(|values| values.get(key).cloned())
+    }
+
+    fn try_has(&self, key: &str) -> Result<bool, StoreError> {
+        self.read(|values| values.contains_key(key))
+    }
+
+    fn try_delete(&self, key: &str) -> Result<bool, StoreError> {
+        if !self.try_has(key)? {
+            return Ok(false);
+        }
+        self.update(|values| values.remove(key).is_some())
+    }
+
+    async fn blocking<T, F>(&self, f: F) -> Result<T, StoreError>
+    where
+        T: Send + 'static,
+        F: FnOnce(FileStore) -> Result<T, StoreError> + Send + 'static,
+    {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || f(store))
+            .await
+            .map_err(StoreError::new)?
+    }
+}
+
+impl Store for FileStore {
+    fn set(&self, key: &str, value: JsonValue) {
+        let _ = self.try_set(key, value);
+    }
+
+    fn get(&self, key: &str) -> Option<JsonValue> {
+        self.try_get(key).ok().flatten()
+    }
+
+    fn has(&self, key: &str) -> bool {
+        self.try_has(key).unwrap_or(false)
+    }
+
+    fn delete(&self, key: &str) -> bool {
+        self.try_delete(key).unwrap_or(false)
+    }
+}
+
+#[async_trait]
+impl AsyncStore for FileStore {
+    async fn set(&self, key: &str, value: JsonValue) -> Result<(), StoreError> {
+        let key = key.to_string();
+        self.blocking(move |store| store.try_set(&key, value)).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<JsonValue>, StoreError> {
+        let key = key.to_string();
+        self.blocking(move |store| store.try_get(&key)).await
+    }
+
+    async fn has(&self, key: &str) -> Result<bool, StoreError> {
+        let key = key.to_string();
+        self.blocking(move |store| store.try_has(&key)).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, StoreError> {
+        let key = key.to_string();
+        self.blocking(move |store| store.try_delete(&key)).await
+    }
+}
+
+const ENCRYPTED_VALUE_VERSION: u64 = 1;
+
+/// 256 bit key used by [`EncryptedStore`], identified by an id that is
+/// stored next to every value it encrypts
+pub struct EncryptionKey {
+    id: String,
+    key: LessSafeKey,
+}
+
+impl EncryptionKey {
+    /// Creates a key from 32 bytes of secret key material
+    pub fn new(id: impl Into<String>, bytes: [u8; 32]) -> Self {
+        Self::from_slice(id, &bytes).expect("32 bytes is a valid AES-256 key")
+    }
+
+    /// Creates a key from a slice, which must be exactly 32 bytes long
+    pub fn from_slice(id: impl Into<String>, bytes: &[u8]) -> Result<Self, StoreError> {
+        let key = UnboundKey::new(&AES_256_GCM, bytes)
+            .map_err(|_| StoreError::new("encryption key must be 32 bytes"))?;
+        Ok(Self {
+            id: id.into(),
+            key: LessSafeKey::new(key),
+        })
+    }
+
+    /// The id of the key
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptionKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Store wrapper that encrypts values with AES-256-GCM before handing them to
+/// the inner store
+///
+/// Values are bound to the key they are stored under, so they can't be moved
+/// between keys. To rotate keys pass the new key as the current one and the
+/// old keys with [`EncryptedStore::with_previous_key`]: values encrypted with
+/// a previous key are still readable and re-encrypted with the current key
+/// when read. Values that can't be decrypted are treated as missing by the
+/// [`Store`] implementation and reported as errors by the [`AsyncStore`] one.
+#[derive(Debug)]
+pub struct EncryptedStore<S> {
+    inner: S,
+    current_key: EncryptionKey,
+    previous_keys: Vec<EncryptionKey>,
+    rng: SystemRandom,
+}
+
+impl<S> EncryptedStore<S> {
+    /// Wraps `inner`, encrypting new values with `key`
+    pub fn new(inner: S, key: EncryptionKey) -> Self {
+        Self {
+            inner,
+            current_key: key,
+            previous_keys: Vec::new(),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// Adds a retired key that is only used to decrypt existing values
+    pub fn with_previous_key(mut self, key: EncryptionKey) -> Self {
+        self.previous_keys.push(key);
+        self
+    }
+
+    /// The wrapped store
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn encrypt(&self, key: &str, value: &JsonValue) -> Result<JsonValue, StoreError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| StoreError::new("failed to generate nonce"))?;
+
+        let mut in_out = serde_json::to_vec(value).map_err(StoreError::new)?;
+        self.current_key
+            .key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(key.as_bytes()),
+                &mut in_out,
+            )
+            .map_err(|_| StoreError::new("failed to encrypt value"))?;
+
+        Ok(serde_json::json!({
+            "v": ENCRYPTED_VALUE_VERSION,
+            "kid": self.current_key.id,
+            "nonce": general_purpose::STANDARD.encode(nonce),
+            "ciphertext": general_purpose::STANDARD.encode(in_out),
+        }))
+    }
+
+    /// Decrypts a stored value, also returning whether it was encrypted with
+    /// a previous key and should be re-encrypted
+    fn decrypt(&self, key: &str, stored: &JsonValue) -> Result<(JsonValue, bool), StoreError> {
+        let field = |name: &str| {
+            stored
+                .get(name)
+                .and_then(JsonValue::as_str)
+                .ok_or_else(|| StoreError::new(format!("encrypted value is missing `{}`", name)))
+        };
+        if stored.get("v").and_then(JsonValue::as_u64) != Some(ENCRYPTED_VALUE_VERSION) {
+            return Err(StoreError::new("value is not encrypted"));
+        }
+
+        let kid = field("kid")?;
+        let encryption_key = std::iter::once(&self.current_key)
+            .chain(&self.previous_keys)
+            .find(|k| k.id == kid)
+            .ok_or_else(|| StoreError::new(format!("unknown encryption key `{}`", kid)))?;
+
+        let nonce = general_purpose::STANDARD
+            .decode(field("nonce")?)
+            .map_err(StoreError::new)?;
+        let nonce = Nonce::try_assume_unique_for_key(&nonce)
+            .map_err(|_| StoreError::new("invalid nonce"))?;
+        let mut in_out = general_purpose::STANDARD
+            .decode(field("ciphertext")?)
+            .map_err(StoreError::new)?;
+
+        let plaintext = encryption_key
+            .key
+            .open_in_place(nonce, Aad::from(key.as_bytes()), &mut in_out)
+            .map_err(|_| StoreError::new("failed to decrypt value"))?;
+        let value = serde_json::from_slice(plaintext).map_err(StoreError::new)?;
+
+        Ok((value, encryption_key.id != self.current_key.id))
+    }
+}
+
+impl<S: Store> Store for EncryptedStore<S> {
+    fn set(&self, key: &str, value: JsonValue) {
+        if let Ok(encrypted) = self.encrypt(key, &value) {
+            self.inner.set(key, encrypted);
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<JsonValue> {
+        let stored = self.inner.get(key)?;
+        let (value, stale) = self.decrypt(key, &stored).ok()?;
+        if stale {
+            self.set(key, value.clone());
+        }
+        Some(value)
+    }
+
+    fn has(&self, key: &str) -> bool {
+        Store::get(self, key).is_some()
+    }
+
+    fn delete(&self, key: &str) -> bool {
+        self.inner.delete(key)
+    }
+}
+
+#[async_trait]
+impl<S: AsyncStore> AsyncStore for EncryptedStore<S> {
+    async fn set(&self, key: &str, value: JsonValue) -> Result<(), StoreError> {
+        let encrypted = self.encrypt(key, &value)?;
+        self.inner.set(key, encrypted).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<JsonValue>, StoreError> {
+        let Some(stored) = self.inner.get(key).await? else {
+            return Ok(None);
+        };
+        let (value, stale) = self.decrypt(key, &stored)?;
+        if stale {
+            AsyncStore::set(self, key, value.clone()).await?;
+        }
+        Ok(Some(value))
+    }
+
+    async fn has(&self, key: &str) -> Result<bool, StoreError> {
+        Ok(AsyncStore::get(self, key).await?.is_some())
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, StoreError> {
+        self.inner.delete(key).await
+    }
+}
+
+fn default_store() -> Arc<dyn AsyncStore> {
+    Arc::new(SyncStoreAdapter::new(Arc::new(DefaultStore::default())))
+}
+
 #[derive(Debug, Clone)]
 pub struct ClerkFapiConfiguration {
     pub(crate) base_url: String,
     pub(crate) instance_type: String,
     pub(crate) frontend_api: String,
     pub(crate) user_agent: String,
-    pub(crate) store: Arc<dyn Store>,
+    pub(crate) store: Arc<dyn AsyncStore>,
     pub(crate) store_prefix: String,
+    pub(crate) token_refresh_margin: Duration,
+    pub(crate) session_poll_interval: Option<Duration>,
+    pub(crate) refresh_policy: RefreshPolicy,
+    pub(crate) retry_policy: Option<RetryPolicy>,
+    pub(crate) listener_policy: ListenerPolicy,
 }
 
 impl ClerkFapiConfiguration {
@@ -160,11 +745,24 @@ impl ClerkFapiConfiguration {
         domain: Option<String>,
         store: Option<Arc<dyn Store>>,
         store_prefix: Option<String>,
+    ) -> Result<Self, String> {
+        let store =
+            store.map(|store| Arc::new(SyncStoreAdapter::new(store)) as Arc<dyn AsyncStore>);
+        Self::new_with_async_store(key, proxy_url, domain, store, store_prefix)
+    }
+
+    /// Creates a new ClerkFapiConfiguration with a custom asynchronous store and/or prefix
+    pub fn new_with_async_store(
+        key: String,
+        proxy_url: Option<String>,
+        domain: Option<String>,
+        store: Option<Arc<dyn AsyncStore>>,
+        store_prefix: Option<String>,
     ) -> Result<Self, String> {
         let parsed_key = parse_publishable_key(&key, domain.clone(), proxy_url.clone())?;
         let user_agent = format!("{}/{}", NAME, VERSION);
 
-        let store = store.unwrap_or_else(|| Arc::new(DefaultStore::default()));
+        let store = store.unwrap_or_else(default_store);
         let store_prefix = store_prefix.unwrap_or_else(|| "ClerkFapi:".to_string());
 
         Ok(Self {
@@ -180,9 +778,45 @@ impl ClerkFapiConfiguration {
             user_agent,
             store,
             store_prefix,
+            token_refresh_margin: DEFAULT_TOKEN_REFRESH_MARGIN,
+            session_poll_interval: None,
+            refresh_policy: RefreshPolicy::default(),
+            retry_policy: None,
+            listener_policy: ListenerPolicy::default(),
         })
     }
 
+    /// Sets how long before expiry cached session tokens are refreshed
+    pub fn with_token_refresh_margin(mut self, margin: Duration) -> Self {
+        self.token_refresh_margin = margin;
+        self
+    }
+
+    /// Enables the background session poller that keeps the active session
+    /// alive by touching it and refreshing its token every `interval`
+    pub fn with_session_poller(mut self, interval: Duration) -> Self {
+        self.session_poll_interval = Some(interval);
+        self
+    }
+
+    /// Sets the retry policy of the background environment and client refresh
+    pub fn with_refresh_policy(mut self, policy: RefreshPolicy) -> Self {
+        self.refresh_policy = policy;
+        self
+    }
+
+    /// Enables retrying of requests that failed with a transient error
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Sets how listeners added with [`crate::clerk::Clerk::add_async_listener`] are run
+    pub fn with_listener_policy(mut self, policy: ListenerPolicy) -> Self {
+        self.listener_policy = policy;
+        self
+    }
+
     /// Returns the base URL for API requests
     pub fn base_url(&self) -> &str {
         &self.base_url
@@ -204,7 +838,7 @@ impl ClerkFapiConfiguration {
     }
 
     /// Returns a reference to the store
-    pub fn store(&self) -> &dyn Store {
+    pub fn store(&self) -> &dyn AsyncStore {
         &*self.store
     }
 
@@ -213,33 +847,65 @@ impl ClerkFapiConfiguration {
         &self.store_prefix
     }
 
+    /// Returns how long before expiry cached session tokens are refreshed
+    pub fn token_refresh_margin(&self) -> Duration {
+        self.token_refresh_margin
+    }
+
+    /// Returns the session poller interval, `None` if the poller is disabled
+    pub fn session_poll_interval(&self) -> Option<Duration> {
+        self.session_poll_interval
+    }
+
+    /// Returns the retry policy of the background environment and client refresh
+    pub fn refresh_policy(&self) -> &RefreshPolicy {
+        &self.refresh_policy
+    }
+
+    /// Returns the policy async listeners are run with
+    pub fn listener_policy(&self) -> ListenerPolicy {
+        self.listener_policy
+    }
+
+    /// Returns the request retry policy, `None` if retries are disabled
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_ref()
+    }
+
     /// Helper method to get prefixed key
     fn get_prefixed_key(&self, key: impl AsRef<str>) -> String {
         format!("{}{}", self.store_prefix, key.as_ref())
     }
 
     /// Set a value in the store with the configured prefix
-    pub fn set_store_value(&self, key: impl AsRef<str>, value: impl Into<JsonValue>) {
+    pub async fn set_store_value(
+        &self,
+        key: impl AsRef<str>,
+        value: impl Into<JsonValue>,
+    ) -> Result<(), StoreError> {
         let prefixed_key = self.get_prefixed_key(key);
-        self.store.set(&prefixed_key, value.into());
+        self.store.set(&prefixed_key, value.into()).await
     }
 
     /// Get a value from the store using the configured prefix
-    pub fn get_store_value(&self, key: impl AsRef<str>) -> Option<JsonValue> {
+    pub async fn get_store_value(
+        &self,
+        key: impl AsRef<str>,
+    ) -> Result<Option<JsonValue>, StoreError> {
         let prefixed_key = self.get_prefixed_key(key);
-        self.store.get(&prefixed_key)
+        self.store.get(&prefixed_key).await
     }
 
     /// Check if a key exists in the store (using the configured prefix)
-    pub fn has_store_value(&self, key: impl AsRef<str>) -> bool {
+    pub async fn has_store_value(&self, key: impl AsRef<str>) -> Result<bool, StoreError> {
         let prefixed_key = self.get_prefixed_key(key);
-        self.store.has(&prefixed_key)
+        self.store.has(&prefixed_key).await
     }
 
     /// Delete a value from the store (using the configured prefix)
-    pub fn delete_store_value(&self, key: impl AsRef<str>) -> bool {
+    pub async fn delete_store_value(&self, key: impl AsRef<str>) -> Result<bool, StoreError> {
         let prefixed_key = self.get_prefixed_key(key);
-        self.store.delete(&prefixed_key)
+        self.store.delete(&prefixed_key).await
     }
 
     /// Returns whether this is a production instance
@@ -285,8 +951,13 @@ impl Default for ClerkFapiConfiguration {
             instance_type: String::new(),
             frontend_api: String::new(),
             user_agent: format!("{}/{}", NAME, VERSION),
-            store: Arc::new(DefaultStore::default()),
+            store: default_store(),
             store_prefix: "ClerkFapi:".to_string(),
+            token_refresh_margin: DEFAULT_TOKEN_REFRESH_MARGIN,
+            session_poll_interval: None,
+            refresh_policy: RefreshPolicy::default(),
+            retry_policy: None,
+            listener_policy: ListenerPolicy::default(),
         }
     }
 }
@@ -363,27 +1034,30 @@ mod tests {
         assert!(result.is_err());
     }
 
-    #[test]
-    fn test_default_store() {
+    #[tokio::test]
+    async fn test_default_store() {
         let config =
             ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
                 .unwrap();
 
         let store = config.store();
-        store.set("test_key", serde_json::json!("test_value"));
+        store
+            .set("test_key", serde_json::json!("test_value"))
+            .await
+            .unwrap();
 
-        assert!(store.has("test_key"));
+        assert!(store.has("test_key").await.unwrap());
         assert_eq!(
-            store.get("test_key").unwrap(),
+            store.get("test_key").await.unwrap().unwrap(),
             serde_json::json!("test_value")
         );
 
-        assert!(store.delete("test_key"));
-        assert!(!store.has("test_key"));
+        assert!(store.delete("test_key").await.unwrap());
+        assert!(!store.has("test_key").await.unwrap());
     }
 
-    #[test]
-    fn test_custom_store() {
+    #[tokio::test]
+    async fn test_custom_store() {
         #[derive(Debug)]
         struct MockStore(DefaultStore);
 
@@ -417,12 +1091,201 @@ mod tests {
 
         config
             .store()
-            .set("test_key", serde_json::json!("test_value"));
-        assert!(config.store().has("test_key"));
+            .set("test_key", serde_json::json!("test_value"))
+            .await
+            .unwrap();
+        assert!(config.store().has("test_key").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_custom_async_store() {
+        #[derive(Debug, Default)]
+        struct MockAsyncStore(tokio::sync::Mutex<HashMap<String, JsonValue>>);
+
+        #[async_trait]
+        impl AsyncStore for MockAsyncStore {
+            async fn set(&self, key: &str, value: JsonValue) -> Result<(), StoreError> {
+                self.0.lock().await.insert(key.to_string(), value);
+                Ok(())
+            }
+
+            async fn get(&self, key: &str) -> Result<Option<JsonValue>, StoreError> {
+                Ok(self.0.lock().await.get(key).cloned())
+            }
+
+            async fn has(&self, key: &str) -> Result<bool, StoreError> {
+                Ok(self.0.lock().await.contains_key(key))
+            }
+
+            async fn delete(&self, key: &str) -> Result<bool, StoreError> {
+                Ok(self.0.lock().await.remove(key).is_some())
+            }
+        }
+
+        let config = ClerkFapiConfiguration::new_with_async_store(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            None,
+            None,
+            Some(Arc::new(MockAsyncStore::default())),
+            None,
+        )
+        .unwrap();
+
+        config.set_store_value("client", "value").await.unwrap();
+        assert!(config.store().has("ClerkFapi:client").await.unwrap());
+        assert!(config.delete_store_value("client").await.unwrap());
+        assert_eq!(config.get_store_value("client").await.unwrap(), None);
+    }
+
+    fn temp_store_path() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("clerk-fapi-{}", uuid::Uuid::new_v4()))
+            .join("store.json")
+    }
+
+    #[tokio::test]
+    async fn test_file_store_persists_values() {
+        let path = temp_store_path();
+        let config = ClerkFapiConfiguration::new_with_async_store(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            None,
+            None,
+            Some(Arc::new(FileStore::new(&path))),
+            None,
+        )
+        .unwrap();
+
+        config
+            .set_store_value("client", serde_json::json!({"id": "client_123"}))
+            .await
+            .unwrap();
+
+        // A new store on the same file sees the value
+        let store = FileStore::new(&path);
+        assert_eq!(
+            Store::get(&store, "ClerkFapi:client"),
+            Some(serde_json::json!({"id": "client_123"}))
+        );
+        assert!(Store::delete(&store, "ClerkFapi:client"));
+        assert!(!config.has_store_value("client").await.unwrap());
+        assert!(!config.delete_store_value("client").await.unwrap());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_store_reports_corrupt_file() {
+        let path = temp_store_path();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "not json").unwrap();
+
+        let store = FileStore::new(&path);
+        assert!(AsyncStore::get(&store, "client").await.is_err());
+        assert!(AsyncStore::set(&store, "client", JsonValue::Null)
+            .await
+            .is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "not json");
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_file_store_concurrent_writers() {
+        let path = temp_store_path();
+
+        let handles = (0..8)
+            .map(|i| {
+                let store = FileStore::new(&path);
+                std::thread::spawn(move || {
+                    for j in 0..5 {
+                        Store::set(&store, &format!("key_{}_{}", i, j), serde_json::json!(j));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let values: serde_json::Map<String, JsonValue> =
+            serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(values.len(), 40);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_store_round_trip() {
+        let store = EncryptedStore::new(DefaultStore::default(), EncryptionKey::new("k1", [7; 32]));
+
+        Store::set(&store, "authorization", serde_json::json!("Bearer secret"));
+        assert_eq!(
+            Store::get(&store, "authorization"),
+            Some(serde_json::json!("Bearer secret"))
+        );
+
+        // The inner store only sees the ciphertext
+        let stored = store.inner().get("authorization").unwrap();
+        assert_eq!(stored["kid"], "k1");
+        assert!(!stored.to_string().contains("secret"));
+
+        // Values can't be moved to another key
+        store.inner().set("client", stored);
+        assert!(!Store::has(&store, "client"));
+
+        // Or read with a different key
+        let other = EncryptedStore::new(store.inner().clone(), EncryptionKey::new("k1", [8; 32]));
+        assert_eq!(Store::get(&other, "authorization"), None);
+
+        assert!(Store::delete(&store, "authorization"));
+        assert!(!Store::has(&store, "authorization"));
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_store_key_rotation() {
+        let inner = SyncStoreAdapter::new(Arc::new(DefaultStore::default()));
+        let old = EncryptedStore::new(inner.clone(), EncryptionKey::new("old", [1; 32]));
+        AsyncStore::set(&old, "authorization", serde_json::json!("token"))
+            .await
+            .unwrap();
+
+        let rotated = EncryptedStore::new(inner.clone(), EncryptionKey::new("new", [2; 32]))
+            .with_previous_key(EncryptionKey::new("old", [1; 32]));
+        assert_eq!(
+            AsyncStore::get(&rotated, "authorization").await.unwrap(),
+            Some(serde_json::json!("token"))
+        );
+
+        // Reading re-encrypted the value with the new key
+        let stored = AsyncStore::get(&inner, "authorization")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored["kid"], "new");
+
+        // Without the new key the value can no longer be read
+        let err = AsyncStore::get(&old, "authorization").await.unwrap_err();
+        assert_eq!(err.to_string(), "unknown encryption key `new`");
     }
 
     #[test]
-    fn test_default_store_prefix() {
+    fn test_encryption_key_length() {
+        assert!(EncryptionKey::from_slice("k1", &[0; 16]).is_err());
+        assert_eq!(
+            EncryptionKey::from_slice("k1", &[0; 32]).unwrap().id(),
+            "k1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_store_prefix() {
         let config =
             ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
                 .unwrap();
@@ -430,22 +1293,25 @@ mod tests {
         assert_eq!(config.store_prefix(), "ClerkFapi:");
 
         // Test store operations with prefix
-        config.set_store_value("test_key", "test_value");
-        assert!(config.has_store_value("test_key"));
+        config
+            .set_store_value("test_key", "test_value")
+            .await
+            .unwrap();
+        assert!(config.has_store_value("test_key").await.unwrap());
         assert_eq!(
-            config.get_store_value("test_key").unwrap(),
+            config.get_store_value("test_key").await.unwrap().unwrap(),
             serde_json::json!("test_value")
         );
 
         // Verify the actual key in store includes prefix
-        assert!(config.store().has("ClerkFapi:test_key"));
+        assert!(config.store().has("ClerkFapi:test_key").await.unwrap());
 
-        assert!(config.delete_store_value("test_key"));
-        assert!(!config.has_store_value("test_key"));
+        assert!(config.delete_store_value("test_key").await.unwrap());
+        assert!(!config.has_store_value("test_key").await.unwrap());
     }
 
-    #[test]
-    fn test_custom_store_prefix() {
+    #[tokio::test]
+    async fn test_custom_store_prefix() {
         let config = ClerkFapiConfiguration::new_with_store(
             "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
             None,
@@ -457,12 +1323,15 @@ mod tests {
 
         assert_eq!(config.store_prefix(), "CustomPrefix:");
 
-        config.set_store_value("test_key", "test_value");
-        assert!(config.store().has("CustomPrefix:test_key"));
+        config
+            .set_store_value("test_key", "test_value")
+            .await
+            .unwrap();
+        assert!(config.store().has("CustomPrefix:test_key").await.unwrap());
     }
 
-    #[test]
-    fn test_store_operations_with_prefix() {
+    #[tokio::test]
+    async fn test_store_operations_with_prefix() {
         let config = ClerkFapiConfiguration::new_with_store(
             "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
             None,
@@ -473,32 +1342,95 @@ mod tests {
         .unwrap();
 
         // Set and verify a value
-        config.set_store_value("key1", "value1");
-        assert!(config.has_store_value("key1"));
+        config.set_store_value("key1", "value1").await.unwrap();
+        assert!(config.has_store_value("key1").await.unwrap());
         assert_eq!(
-            config.get_store_value("key1").unwrap(),
+            config.get_store_value("key1").await.unwrap().unwrap(),
             serde_json::json!("value1")
         );
 
         // Delete and verify
-        assert!(config.delete_store_value("key1"));
-        assert!(!config.has_store_value("key1"));
+        assert!(config.delete_store_value("key1").await.unwrap());
+        assert!(!config.has_store_value("key1").await.unwrap());
 
         // Verify with direct store access
-        assert!(!config.store().has("Test:key1"));
+        assert!(!config.store().has("Test:key1").await.unwrap());
     }
 
-    #[test]
-    fn test_default_implementation() {
+    #[tokio::test]
+    async fn test_default_implementation() {
         let config = ClerkFapiConfiguration::default();
         assert_eq!(config.base_url(), "");
         assert_eq!(config.instance_type(), "");
         assert_eq!(config.frontend_api(), "");
         assert_eq!(config.user_agent(), format!("{}/{}", NAME, VERSION));
         assert_eq!(config.store_prefix(), "ClerkFapi:");
+        assert_eq!(config.token_refresh_margin(), DEFAULT_TOKEN_REFRESH_MARGIN);
+        assert_eq!(config.session_poll_interval(), None);
+        assert_eq!(config.refresh_policy(), &RefreshPolicy::default());
+        assert_eq!(config.retry_policy(), None);
 
         // Test that the default store works
-        config.set_store_value("test_key", "test_value");
-        assert!(config.has_store_value("test_key"));
+        config
+            .set_store_value("test_key", "test_value")
+            .await
+            .unwrap();
+        assert!(config.has_store_value("test_key").await.unwrap());
+    }
+
+    #[test]
+    fn test_refresh_policy_backoff() {
+        let policy = RefreshPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.0,
+            ..RefreshPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(8));
+        assert_eq!(policy.backoff(5), Duration::from_secs(10));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_refresh_policy_jitter_stays_in_bounds() {
+        let policy = RefreshPolicy {
+            initial_backoff: Duration::from_secs(10),
+            jitter: 0.5,
+            ..RefreshPolicy::default()
+        };
+
+        for _ in 0..100 {
+            let backoff = policy.backoff(1);
+            assert!(backoff >= Duration::from_secs(5));
+            assert!(backoff <= Duration::from_secs(15));
+        }
+    }
+
+    #[test]
+    fn test_refresh_policy_disabled() {
+        let config =
+            ClerkFapiConfiguration::new("pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(), None, None)
+                .unwrap()
+                .with_refresh_policy(RefreshPolicy::disabled());
+        assert!(!config.refresh_policy().enabled);
+    }
+
+    #[test]
+    fn test_retry_policy_backoff() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(500));
     }
 }
@@ -0,0 +1,316 @@
+//! Enterprise SSO sign ins for native apps
+//!
+//! Users whose email domain has an enterprise connection, SAML or OIDC, sign
+//! in with their identity provider instead of a password or a code.
+//! [`Clerk::enterprise_sso`] identifies the user, and when the sign in offers
+//! the enterprise SSO first factor starts it and listens on a loopback
+//! address for the redirect that ends it. Completing the sign in works the
+//! same as with [`crate::oauth`].
+//!
+//! ```no_run
+//! # use clerk_fapi_rs::clerk::{Clerk, Error};
+//! # async fn example(clerk: Clerk) -> Result<(), Error> {
+//! let pending = clerk.enterprise_sso("ada@acme.com").start().await?;
+//! println!("Continue in the browser: {}", pending.authorization_url());
+//!
+//! let outcome = pending.complete().await?;
+//! println!("signed in: {:?}", outcome.session_id());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A typed sign in can check [`NeedsFirstFactor::requires_enterprise_sso`]
+//! after identifying the user and continue with
+//! [`NeedsFirstFactor::enterprise_sso`].
+//!
+//! [`NeedsFirstFactor::requires_enterprise_sso`]: crate::sign_in::NeedsFirstFactor::requires_enterprise_sso
+//! [`NeedsFirstFactor::enterprise_sso`]: crate::sign_in::NeedsFirstFactor::enterprise_sso
+
+use crate::clerk::{Clerk, Error};
+use crate::loopback::Loopback;
+use crate::models::stubs_period_sign_in_factor::Strategy;
+use crate::models::ClientPeriodSignIn as SignIn;
+use crate::oauth::{PendingOAuth, DEFAULT_REDIRECT_TIMEOUT};
+use crate::params::{CreateSignInParams, PrepareSignInFactorOneParams};
+use std::time::Duration;
+
+const DEFAULT_CALLBACK_PATH: &str = "/sso-callback";
+
+/// Returns the enterprise SSO strategy the sign in can continue with, `None`
+/// when the email domain has no enterprise connection
+///
+/// Instances that predate enterprise connections offer the `saml` strategy
+/// instead of `enterprise_sso`.
+pub fn enterprise_sso_strategy(sign_in: &SignIn) -> Option<&'static str> {
+    let factors = sign_in
+        .supported_first_factors
+        .as_deref()
+        .unwrap_or_default();
+    let offers = |strategy| factors.iter().any(|factor| factor.strategy == strategy);
+
+    if offers(Strategy::EnterpriseSso) {
+        Some("enterprise_sso")
+    } else if offers(Strategy::Saml) {
+        Some("saml")
+    } else {
+        None
+    }
+}
+
+enum Source {
+    Identifier(String),
+    SignIn(SignIn),
+}
+
+/// Enterprise SSO sign in, created with [`Clerk::enterprise_sso`] or
+/// [`crate::sign_in::NeedsFirstFactor::enterprise_sso`]
+pub struct EnterpriseSsoFlow {
+    clerk: Clerk,
+    source: Source,
+    port: u16,
+    callback_path: String,
+    timeout: Duration,
+}
+
+impl EnterpriseSsoFlow {
+    pub(crate) fn new(clerk: Clerk, identifier: &str) -> Self {
+        Self::with_source(clerk, Source::Identifier(identifier.to_string()))
+    }
+
+    pub(crate) fn for_sign_in(clerk: Clerk, sign_in: SignIn) -> Self {
+        Self::with_source(clerk, Source::SignIn(sign_in))
+    }
+
+    fn with_source(clerk: Clerk, source: Source) -> Self {
+        Self {
+            clerk,
+            source,
+            port: 0,
+            callback_path: DEFAULT_CALLBACK_PATH.to_string(),
+            timeout: DEFAULT_REDIRECT_TIMEOUT,
+        }
+    }
+
+    /// Port of the loopback listener, by default a free one is picked
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Path of the redirect URL, `/sso-callback` by default
+    pub fn callback_path(mut self, path: impl Into<String>) -> Self {
+        self.callback_path = path.into();
+        self
+    }
+
+    /// How long to wait for the redirect, see [`DEFAULT_REDIRECT_TIMEOUT`]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Identifies the user if needed, starts listening for the redirect and
+    /// starts the enterprise SSO first factor
+    ///
+    /// Fails with [`Error::StrategyNotEnabled`] if the email domain has no
+    /// enterprise connection.
+    pub async fn start(self) -> Result<PendingOAuth, Error> {
+        let fapi = self.clerk.get_fapi_client();
+        let sign_in = match self.source {
+            Source::Identifier(identifier) => {
+                *fapi
+                    .create_sign_in_with(CreateSignInParams::identifier(identifier))
+                    .await?
+                    .response
+            }
+            Source::SignIn(sign_in) => sign_in,
+        };
+        let strategy = enterprise_sso_strategy(&sign_in)
+            .ok_or_else(|| Error::StrategyNotEnabled("enterprise_sso".to_string()))?;
+
+        let loopback = Loopback::bind(self.port, &self.callback_path).await?;
+        let response = fapi
+            .prepare_sign_in_factor_one_with(
+                &sign_in.id,
+                PrepareSignInFactorOneParams::oauth(strategy, loopback.url()),
+            )
+            .await?;
+        PendingOAuth::new(self.clerk, *response.response, loopback, self.timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::client_period_sign_in::Status;
+    use crate::models::client_sign_in_first_factor_verification::ClientSignInFirstFactorVerification as Verification;
+    use crate::models::stubs_period_verification_period_saml::{
+        Status as SamlStatus, Strategy as SamlStrategy,
+    };
+    use crate::models::{
+        ClientPeriodClient as Client, StubsPeriodSignInFactor as SignInFactor,
+        StubsPeriodVerificationPeriodSaml as SamlVerification,
+    };
+    use crate::oauth::OAuthOutcome;
+    use crate::sign_in::SignInState;
+    use crate::testing::fixtures::{
+        loaded_clerk, response_body, signed_in_client, ClientBuilder, EnvironmentBuilder,
+    };
+    use mockito::{Matcher, Server};
+
+    fn identified(strategies: &[Strategy]) -> SignIn {
+        SignIn {
+            id: "sia_123".to_string(),
+            status: Status::NeedsFirstFactor,
+            supported_first_factors: Some(
+                strategies.iter().map(|s| SignInFactor::new(*s)).collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn verifying(status: Status, verification: SamlStatus, server: &Server) -> SignIn {
+        let mut saml = SamlVerification::new(verification, SamlStrategy::EnterpriseSso);
+        saml.external_verification_redirect_url = Some(Some(format!("{}/idp/sso", server.url())));
+        SignIn {
+            first_factor_verification: Some(Box::new(
+                Verification::StubsPeriodVerificationPeriodSaml(Box::new(saml)),
+            )),
+            created_session_id: (status == Status::Complete).then(|| "sess_123".to_string()),
+            status,
+            ..identified(&[Strategy::EnterpriseSso])
+        }
+    }
+
+    #[test]
+    fn test_enterprise_sso_strategy() {
+        let sign_in = identified(&[Strategy::Saml, Strategy::EnterpriseSso]);
+        assert_eq!(enterprise_sso_strategy(&sign_in), Some("enterprise_sso"));
+        let sign_in = identified(&[Strategy::Password, Strategy::Saml]);
+        assert_eq!(enterprise_sso_strategy(&sign_in), Some("saml"));
+        let sign_in = identified(&[Strategy::Password, Strategy::EmailCode]);
+        assert_eq!(enterprise_sso_strategy(&sign_in), None);
+        assert_eq!(enterprise_sso_strategy(&SignIn::default()), None);
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_completes_after_redirect() {
+        let mut server = Server::new_async().await;
+        let clerk = loaded_clerk(
+            server.url(),
+            EnvironmentBuilder::new(),
+            ClientBuilder::new(),
+        )
+        .await;
+        let create = server
+            .mock("POST", "/v1/client/sign_ins?_is_native=1")
+            .match_body(Matcher::UrlEncoded(
+                "identifier".to_string(),
+                "ada@acme.com".to_string(),
+            ))
+            .with_body(response_body(
+                &identified(&[Strategy::EnterpriseSso]),
+                Client::default(),
+            ))
+            .create_async()
+            .await;
+        let prepare = server
+            .mock(
+                "POST",
+                "/v1/client/sign_ins/sia_123/prepare_first_factor?_is_native=1",
+            )
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("strategy".to_string(), "enterprise_sso".to_string()),
+                Matcher::Regex("redirect_url=http%3A%2F%2F127.0.0.1".to_string()),
+            ]))
+            .with_body(response_body(
+                &verifying(Status::NeedsFirstFactor, SamlStatus::Unverified, &server),
+                Client::default(),
+            ))
+            .create_async()
+            .await;
+
+        let pending = clerk.enterprise_sso("ada@acme.com").start().await.unwrap();
+        assert_eq!(
+            pending.authorization_url(),
+            format!("{}/idp/sso", server.url())
+        );
+        assert!(pending.redirect_url().ends_with("/sso-callback"));
+        create.assert_async().await;
+        prepare.assert_async().await;
+
+        server
+            .mock("GET", "/idp/sso")
+            .with_status(302)
+            .with_header(
+                "location",
+                &format!("{}?rotating_token_nonce=nonce_123", pending.redirect_url()),
+            )
+            .create_async()
+            .await;
+        let reload = server
+            .mock("GET", "/v1/client/sign_ins/sia_123")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("rotating_token_nonce".to_string(), "nonce_123".to_string()),
+                Matcher::UrlEncoded("_is_native".to_string(), "1".to_string()),
+            ]))
+            .with_body(response_body(
+                &verifying(Status::Complete, SamlStatus::Verified, &server),
+                signed_in_client(),
+            ))
+            .create_async()
+            .await;
+
+        let authorization_url = pending.authorization_url().to_string();
+        let browser = async {
+            let page = reqwest::get(authorization_url).await.unwrap();
+            assert!(page.status().is_success());
+        };
+        let (outcome, ()) = tokio::join!(pending.complete(), browser);
+
+        let outcome = outcome.unwrap();
+        assert!(matches!(
+            outcome,
+            OAuthOutcome::SignIn(SignInState::Complete(_))
+        ));
+        assert_eq!(
+            clerk.session().await.and_then(|s| s.id).as_deref(),
+            Some("sess_123")
+        );
+        reload.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_domain_without_connection_is_not_enabled() {
+        let mut server = Server::new_async().await;
+        let clerk = loaded_clerk(
+            server.url(),
+            EnvironmentBuilder::new(),
+            ClientBuilder::new(),
+        )
+        .await;
+        server
+            .mock("POST", "/v1/client/sign_ins?_is_native=1")
+            .with_body(response_body(
+                &identified(&[Strategy::Password, Strategy::EmailCode]),
+                Client::default(),
+            ))
+            .expect(2)
+            .create_async()
+            .await;
+
+        let error = clerk
+            .enterprise_sso("ada@example.com")
+            .start()
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::StrategyNotEnabled(s) if s == "enterprise_sso"));
+
+        let SignInState::NeedsFirstFactor(needs_factor) =
+            clerk.sign_in().identify("ada@example.com").await.unwrap()
+        else {
+            panic!("expected the sign in to need a first factor");
+        };
+        assert!(!needs_factor.requires_enterprise_sso());
+    }
+}
@@ -0,0 +1,334 @@
+//! Typed events emitted by [`crate::clerk::Clerk`] when its state changes
+//!
+//! Events are computed by comparing the state before and after an update, so
+//! an update that doesn't change anything emits nothing. They can be consumed
+//! either as a stream with [`crate::clerk::Clerk::subscribe`] or with a
+//! callback registered with [`crate::clerk::Clerk::add_event_listener`].
+//!
+//! ```no_run
+//! use clerk_fapi_rs::events::ClerkEvent;
+//!
+//! # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) {
+//! let mut events = clerk.subscribe();
+//! while let Ok(event) = events.recv().await {
+//!     match event {
+//!         ClerkEvent::SignedIn { session } => println!("signed in to {:?}", session.id),
+//!         ClerkEvent::SignedOut { .. } => println!("signed out"),
+//!         _ => {}
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::models::{
+    ClientPeriodEnvironment as Environment, ClientPeriodOrganization as Organization,
+    ClientPeriodSession as Session, ClientPeriodUser as User,
+};
+use parking_lot::RwLock;
+use std::sync::{Arc, Weak};
+use tokio::sync::broadcast;
+
+/// Number of events buffered for each subscriber before the slowest one lags
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// A change of the state of a [`crate::clerk::Clerk`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClerkEvent {
+    /// A session became active while there was none
+    SignedIn { session: Box<Session> },
+    /// The active session went away
+    SignedOut { session_id: Option<String> },
+    /// Another session was signed in on the client, see [`crate::clerk::Clerk::sessions`]
+    SessionAdded { session: Box<Session> },
+    /// A signed in session was signed out or ended
+    SessionRemoved { session_id: String },
+    /// Another session became the active one
+    SessionChanged {
+        previous_session_id: Option<String>,
+        session: Box<Session>,
+    },
+    /// The user of the active session was updated
+    UserUpdated { user: Box<User> },
+    /// The active organization, or its data, changed
+    OrganizationChanged {
+        organization: Option<Box<Organization>>,
+    },
+    /// A new environment was loaded
+    EnvironmentUpdated { environment: Box<Environment> },
+    /// A new session token was created, cache hits don't emit this
+    TokenRefreshed {
+        session_id: String,
+        organization_id: Option<String>,
+        template: Option<String>,
+    },
+}
+
+/// The parts of the state events are computed from
+#[derive(Default)]
+pub(crate) struct Snapshot {
+    pub(crate) sessions: Vec<Session>,
+    pub(crate) session: Option<Session>,
+    pub(crate) user: Option<User>,
+    pub(crate) organization: Option<Organization>,
+}
+
+impl Snapshot {
+    /// Returns the events that lead from `self` to `next`
+    pub(crate) fn diff(&self, next: &Snapshot) -> Vec<ClerkEvent> {
+        let mut events = Vec::new();
+        let previous_session_id = self.session.as_ref().and_then(|s| s.id.clone());
+        let session_id = next.session.as_ref().and_then(|s| s.id.clone());
+        let contains = |sessions: &[Session], id: &Option<String>| {
+            sessions.iter().any(|session| &session.id == id)
+        };
+
+        for session in &next.sessions {
+            if !contains(&self.sessions, &session.id) {
+                events.push(ClerkEvent::SessionAdded {
+                    session: Box::new(session.clone()),
+                });
+            }
+        }
+
+        match (&self.session, &next.session) {
+            (None, Some(session)) => events.push(ClerkEvent::SignedIn {
+                session: Box::new(session.clone()),
+            }),
+            (Some(_), None) => events.push(ClerkEvent::SignedOut {
+                session_id: previous_session_id,
+            }),
+            (Some(_), Some(session)) if previous_session_id != session_id => {
+                events.push(ClerkEvent::SessionChanged {
+                    previous_session_id,
+                    session: Box::new(session.clone()),
+                })
+            }
+            (Some(_), Some(_)) => {
+                // Same session, the user is only reported when it was updated
+                if let Some(user) = &next.user {
+                    if self.user.as_ref() != Some(user) {
+                        events.push(ClerkEvent::UserUpdated {
+                            user: Box::new(user.clone()),
+                        });
+                    }
+                }
+            }
+            (None, None) => {}
+        }
+
+        if self.organization != next.organization {
+            events.push(ClerkEvent::OrganizationChanged {
+                organization: next.organization.clone().map(Box::new),
+            });
+        }
+
+        for session in &self.sessions {
+            if let Some(id) = &session.id {
+                if !contains(&next.sessions, &session.id) {
+                    events.push(ClerkEvent::SessionRemoved {
+                        session_id: id.clone(),
+                    });
+                }
+            }
+        }
+        events
+    }
+}
+
+/// Handle to a listener registered with [`crate::clerk::Clerk::add_listener`]
+/// or [`crate::clerk::Clerk::add_event_listener`]
+///
+/// Dropping the handle does not remove the listener, use [`ListenerHandle::remove`].
+pub struct ListenerHandle {
+    remove: Box<dyn FnOnce() + Send + Sync>,
+}
+
+impl ListenerHandle {
+    /// Unregisters the listener, it is not called for later updates
+    pub fn remove(self) {
+        (self.remove)()
+    }
+}
+
+struct Entries<L: ?Sized> {
+    next_id: u64,
+    entries: Vec<(u64, Arc<L>)>,
+}
+
+/// Listeners that can be removed through a [`ListenerHandle`]
+pub(crate) struct Listeners<L: ?Sized> {
+    inner: RwLock<Entries<L>>,
+}
+
+impl<L: ?Sized> Default for Listeners<L> {
+    fn default() -> Self {
+        Self {
+            inner: RwLock::new(Entries {
+                next_id: 0,
+                entries: Vec::new(),
+            }),
+        }
+    }
+}
+
+impl<L: ?Sized + Send + Sync + 'static> Listeners<L> {
+    pub(crate) fn add(self: &Arc<Self>, listener: Arc<L>) -> ListenerHandle {
+        let mut inner = self.inner.write();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.entries.push((id, listener));
+
+        let weak: Weak<Self> = Arc::downgrade(self);
+        ListenerHandle {
+            remove: Box::new(move || {
+                if let Some(listeners) = weak.upgrade() {
+                    listeners
+                        .inner
+                        .write()
+                        .entries
+                        .retain(|(entry_id, _)| *entry_id != id);
+                }
+            }),
+        }
+    }
+
+    /// Returns the registered listeners, so they can be called without holding the lock
+    pub(crate) fn snapshot(&self) -> Vec<Arc<L>> {
+        self.inner
+            .read()
+            .entries
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect()
+    }
+}
+
+type EventListener = dyn Fn(&ClerkEvent) + Send + Sync;
+
+/// Broadcast channel and callbacks events are delivered to
+pub(crate) struct Events {
+    sender: broadcast::Sender<ClerkEvent>,
+    listeners: Arc<Listeners<EventListener>>,
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            listeners: Arc::default(),
+        }
+    }
+}
+
+impl Events {
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<ClerkEvent> {
+        self.sender.subscribe()
+    }
+
+    pub(crate) fn add_listener(&self, listener: Arc<EventListener>) -> ListenerHandle {
+        self.listeners.add(listener)
+    }
+
+    pub(crate) fn emit(&self, event: ClerkEvent) {
+        for listener in self.listeners.snapshot() {
+            listener(&event);
+        }
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, org: Option<&str>) -> Snapshot {
+        let session = Session {
+            id: Some(id.to_string()),
+            ..Default::default()
+        };
+        Snapshot {
+            sessions: vec![session.clone()],
+            session: Some(session),
+            user: Some(User {
+                id: Some("user_1".to_string()),
+                ..Default::default()
+            }),
+            organization: org.map(|id| Organization {
+                id: Some(id.to_string()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let signed_out = Snapshot::default();
+        let signed_in = session("sess_1", None);
+
+        assert!(signed_in.diff(&session("sess_1", None)).is_empty());
+        assert!(matches!(
+            signed_out.diff(&signed_in).as_slice(),
+            [ClerkEvent::SessionAdded { .. }, ClerkEvent::SignedIn { .. }]
+        ));
+        assert_eq!(
+            signed_in.diff(&signed_out),
+            vec![
+                ClerkEvent::SignedOut {
+                    session_id: Some("sess_1".to_string())
+                },
+                ClerkEvent::SessionRemoved {
+                    session_id: "sess_1".to_string()
+                }
+            ]
+        );
+        assert!(matches!(
+            signed_in.diff(&session("sess_2", Some("org_1"))).as_slice(),
+            [
+                ClerkEvent::SessionAdded { .. },
+                ClerkEvent::SessionChanged { previous_session_id: Some(id), .. },
+                ClerkEvent::OrganizationChanged { organization: Some(_) },
+                ClerkEvent::SessionRemoved { .. },
+            ] if id == "sess_1"
+        ));
+
+        // Another session signed in, the active one stays
+        let mut multi_session = session("sess_1", None);
+        multi_session
+            .sessions
+            .extend(session("sess_2", None).sessions);
+        assert!(matches!(
+            signed_in.diff(&multi_session).as_slice(),
+            [ClerkEvent::SessionAdded { session }] if session.id.as_deref() == Some("sess_2")
+        ));
+        assert_eq!(
+            multi_session.diff(&signed_in),
+            vec![ClerkEvent::SessionRemoved {
+                session_id: "sess_2".to_string()
+            }]
+        );
+
+        let mut renamed = session("sess_1", None);
+        renamed.user.as_mut().unwrap().first_name = Some(Some("Ada".to_string()));
+        assert!(matches!(
+            signed_in.diff(&renamed).as_slice(),
+            [ClerkEvent::UserUpdated { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_removed_listener_is_not_called() {
+        let events = Events::default();
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let calls_ref = calls.clone();
+        let handle = events.add_listener(Arc::new(move |_| {
+            calls_ref.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }));
+
+        events.emit(ClerkEvent::SignedOut { session_id: None });
+        handle.remove();
+        events.emit(ClerkEvent::SignedOut { session_id: None });
+
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,865 @@
+//! Verification of session tokens against the JWKS of the instance
+//!
+//! Backend services receiving tokens minted by [`crate::clerk::Clerk::get_token`]
+//! can verify them locally with a [`JwtVerifier`]. The keys are fetched from
+//! `/.well-known/jwks.json` on first use, cached, and fetched again when a
+//! token is signed with an unknown `kid`.
+//!
+//! Clients that only need to read their own tokens can use
+//! [`SessionClaims::decode`] or [`crate::clerk::Clerk::get_token_with_claims`]
+//! instead, which skip the verification.
+//!
+//! ```no_run
+//! use clerk_fapi_rs::configuration::ClerkFapiConfiguration;
+//! use clerk_fapi_rs::jwt::JwtVerifier;
+//!
+//! # async fn example(token: &str) -> Result<(), Box<dyn std::error::Error>> {
+//! let config = ClerkFapiConfiguration::new("pk_test_...".to_string(), None, None)?;
+//! let verifier = JwtVerifier::new(config)?
+//!     .with_authorized_parties(vec!["https://app.example.com".to_string()]);
+//!
+//! let claims = verifier.verify(token).await?;
+//! println!("user {} in organization {:?}", claims.sub, claims.org_id);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::clerk::Error as ClerkError;
+use crate::clerk_fapi::ClerkFapiClient;
+use crate::configuration::ClerkFapiConfiguration;
+use crate::models::JwksKeysInner;
+use base64::{engine::general_purpose, Engine as _};
+use ring::signature::{self, RsaPublicKeyComponents, UnparsedPublicKey};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+const DEFAULT_LEEWAY: Duration = Duration::from_secs(5);
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Errors returned when verifying a token
+#[derive(Debug)]
+pub enum Error {
+    /// The token is not a well formed JWT
+    Malformed(String),
+    /// The token is signed with an algorithm other than RS256 or ES256
+    UnsupportedAlgorithm(String),
+    /// No key in the JWKS matches the `kid` of the token
+    UnknownKey(Option<String>),
+    /// The signature does not match the token
+    InvalidSignature,
+    /// The token expired, `exp` is past the allowed leeway
+    Expired,
+    /// The token is not valid yet, `nbf` or `iat` is in the future
+    NotYetValid,
+    /// The `azp` claim is not one of the authorized parties
+    UnauthorizedParty(String),
+    /// Fetching the JWKS failed
+    Jwks(ClerkError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Malformed(reason) => write!(f, "malformed token: {}", reason),
+            Error::UnsupportedAlgorithm(alg) => write!(f, "unsupported algorithm {}", alg),
+            Error::UnknownKey(Some(kid)) => write!(f, "no key found for kid {}", kid),
+            Error::UnknownKey(None) => write!(f, "token has no kid"),
+            Error::InvalidSignature => write!(f, "invalid signature"),
+            Error::Expired => write!(f, "token has expired"),
+            Error::NotYetValid => write!(f, "token is not valid yet"),
+            Error::UnauthorizedParty(azp) => write!(f, "unauthorized party {}", azp),
+            Error::Jwks(e) => write!(f, "failed to fetch jwks: {}", e),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Jwks(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Claims of a session token
+///
+/// Both the v1 and the compact v2 claim shapes are read into the same fields.
+/// Claims that aren't standard Clerk claims, such as the ones added by a JWT
+/// template, are deserialized into `custom`, a JSON map by default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionClaims<T = JsonMap<String, JsonValue>> {
+    /// ID of the session, missing in tokens created from some JWT templates
+    pub sid: Option<String>,
+    /// ID of the user
+    pub sub: String,
+    /// ID of the active organization
+    pub org_id: Option<String>,
+    /// Slug of the active organization
+    pub org_slug: Option<String>,
+    /// Role of the user in the active organization, e.g. `org:admin`
+    pub org_role: Option<String>,
+    /// Permissions of the user in the active organization
+    pub org_permissions: Vec<String>,
+    /// Origin the token was issued for
+    pub azp: Option<String>,
+    /// Issuer, the Frontend API URL of the instance
+    pub iss: Option<String>,
+    /// Expiry as a unix timestamp
+    pub exp: i64,
+    /// Issue time as a unix timestamp
+    pub iat: Option<i64>,
+    /// Start of validity as a unix timestamp
+    pub nbf: Option<i64>,
+    /// Version of the claim shape, 1 or 2
+    pub version: u32,
+    /// The remaining, non standard claims
+    pub custom: T,
+}
+
+impl<T: DeserializeOwned> SessionClaims<T> {
+    /// Decodes the claims of a token without verifying it
+    ///
+    /// Use [`JwtVerifier`] when the token comes from an untrusted source.
+    pub fn decode(token: &str) -> Result<Self, Error> {
+        let mut parts = token.split('.');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(_), Some(payload), Some(_), None) => Self::from_payload(payload),
+            _ => Err(Error::Malformed("expected three segments".to_string())),
+        }
+    }
+
+    fn from_payload(payload: &str) -> Result<Self, Error> {
+        let mut claims: JsonValue = decode_json(payload)?;
+        let raw = RawClaims::deserialize(&claims).map_err(malformed)?;
+        if let Some(claims) = claims.as_object_mut() {
+            claims.retain(|name, _| !STANDARD_CLAIMS.contains(&name.as_str()));
+        }
+        let custom = T::deserialize(claims).map_err(malformed)?;
+
+        let (org_id, org_slug, org_role, org_permissions) = match raw.o {
+            Some(o) => {
+                let permissions = v2_permissions(raw.fea.as_deref(), &o);
+                let role = o.rol.map(|r| format!("org:{}", r));
+                (Some(o.id), o.slg, role, permissions)
+            }
+            None => (
+                raw.org_id,
+                raw.org_slug,
+                raw.org_role,
+                raw.org_permissions.unwrap_or_default(),
+            ),
+        };
+        Ok(SessionClaims {
+            sid: raw.sid,
+            sub: raw.sub,
+            org_id,
+            org_slug,
+            org_role,
+            org_permissions,
+            azp: raw.azp,
+            iss: raw.iss,
+            exp: raw.exp,
+            iat: raw.iat,
+            nbf: raw.nbf,
+            version: raw.v.unwrap_or(1),
+            custom,
+        })
+    }
+}
+
+/// A session token together with its decoded claims
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionToken<T = JsonMap<String, JsonValue>> {
+    /// The raw JWT
+    pub jwt: String,
+    /// The claims of the JWT, decoded without verification
+    pub claims: SessionClaims<T>,
+}
+
+/// Claims read into the fields of [`SessionClaims`] and left out of `custom`
+const STANDARD_CLAIMS: &[&str] = &[
+    "sid",
+    "sub",
+    "exp",
+    "iat",
+    "nbf",
+    "azp",
+    "iss",
+    "jti",
+    "v",
+    "fva",
+    "sts",
+    "pla",
+    "fea",
+    "o",
+    "org_id",
+    "org_slug",
+    "org_role",
+    "org_permissions",
+];
+
+/// Claims as they appear in the token, either in the v1 or the v2 shape
+#[derive(Deserialize)]
+struct RawClaims {
+    v: Option<u32>,
+    sid: Option<String>,
+    sub: String,
+    exp: i64,
+    iat: Option<i64>,
+    nbf: Option<i64>,
+    azp: Option<String>,
+    iss: Option<String>,
+    org_id: Option<String>,
+    org_slug: Option<String>,
+    org_role: Option<String>,
+    org_permissions: Option<Vec<String>>,
+    /// Features of the instance in v2 tokens, e.g. `o:reports,u:billing`
+    fea: Option<String>,
+    /// Organization claims of v2 tokens
+    o: Option<RawOrganizationClaims>,
+}
+
+#[derive(Deserialize)]
+struct RawOrganizationClaims {
+    id: String,
+    slg: Option<String>,
+    rol: Option<String>,
+    per: Option<String>,
+    fpm: Option<String>,
+}
+
+/// Expands the compact v2 permissions into `org:<feature>:<permission>`
+///
+/// `fpm` holds one bitmask per organization feature, bit `n` set means the
+/// user has the `n`th permission listed in `per` for that feature.
+fn v2_permissions(features: Option<&str>, o: &RawOrganizationClaims) -> Vec<String> {
+    let (Some(features), Some(per), Some(fpm)) = (features, &o.per, &o.fpm) else {
+        return Vec::new();
+    };
+    let permissions: Vec<&str> = per.split(',').filter(|p| !p.is_empty()).collect();
+    let org_features = features.split(',').filter_map(|f| {
+        let (scope, name) = f.split_once(':')?;
+        scope.contains('o').then_some(name)
+    });
+
+    let mut result = Vec::new();
+    for (feature, mask) in org_features.zip(fpm.split(',')) {
+        let Ok(mask) = mask.parse::<u64>() else {
+            continue;
+        };
+        for (i, permission) in permissions.iter().enumerate().take(64) {
+            if mask & (1 << i) != 0 {
+                result.push(format!("org:{}:{}", feature, permission));
+            }
+        }
+    }
+    result
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    kid: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Algorithm {
+    Rs256,
+    Es256,
+}
+
+impl Algorithm {
+    fn from_name(alg: &str) -> Option<Self> {
+        match alg {
+            "RS256" => Some(Algorithm::Rs256),
+            "ES256" => Some(Algorithm::Es256),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum VerifyingKey {
+    Rsa {
+        n: Vec<u8>,
+        e: Vec<u8>,
+    },
+    /// Uncompressed P-256 point, `0x04 || x || y`
+    Ec {
+        point: Vec<u8>,
+    },
+}
+
+impl VerifyingKey {
+    /// Returns `None` for keys that can't be used to verify RS256 or ES256
+    fn from_jwk(jwk: &JwksKeysInner) -> Option<Self> {
+        if jwk.r#use.as_deref().is_some_and(|u| u != "sig") {
+            return None;
+        }
+        match jwk.kty.as_deref()? {
+            "RSA" => Some(VerifyingKey::Rsa {
+                n: decode_segment(jwk.n.as_deref()?).ok()?,
+                e: decode_segment(jwk.e.as_deref()?).ok()?,
+            }),
+            "EC" if jwk.crv.as_deref() == Some("P-256") => {
+                let x = decode_segment(jwk.x.as_deref()?).ok()?;
+                let y = decode_segment(jwk.y.as_deref()?).ok()?;
+                if x.len() != 32 || y.len() != 32 {
+                    return None;
+                }
+                let mut point = Vec::with_capacity(65);
+                point.push(0x04);
+                point.extend_from_slice(&x);
+                point.extend_from_slice(&y);
+                Some(VerifyingKey::Ec { point })
+            }
+            _ => None,
+        }
+    }
+
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            VerifyingKey::Rsa { .. } => Algorithm::Rs256,
+            VerifyingKey::Ec { .. } => Algorithm::Es256,
+        }
+    }
+
+    fn verify(&self, message: &[u8], sig: &[u8]) -> Result<(), Error> {
+        let result = match self {
+            VerifyingKey::Rsa { n, e } => RsaPublicKeyComponents { n, e }.verify(
+                &signature::RSA_PKCS1_2048_8192_SHA256,
+                message,
+                sig,
+            ),
+            VerifyingKey::Ec { point } => {
+                UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_FIXED, point)
+                    .verify(message, sig)
+            }
+        };
+        result.map_err(|_| Error::InvalidSignature)
+    }
+}
+
+#[derive(Default)]
+struct KeySet {
+    keys: HashMap<String, VerifyingKey>,
+    fetched_at: Option<Instant>,
+    attempted_at: Option<Instant>,
+}
+
+impl KeySet {
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        self.fetched_at.is_some_and(|t| t.elapsed() < ttl)
+    }
+}
+
+/// Verifies session tokens against the JWKS of the instance
+///
+/// Cloning is cheap and clones share the key cache.
+#[derive(Clone)]
+pub struct JwtVerifier {
+    client: ClerkFapiClient,
+    keys: Arc<RwLock<KeySet>>,
+    leeway: Duration,
+    authorized_parties: Vec<String>,
+    cache_ttl: Duration,
+    min_refetch_interval: Duration,
+}
+
+impl JwtVerifier {
+    /// Creates a verifier for the instance of the publishable key
+    ///
+    /// Fails if the underlying HTTP client can't be created.
+    pub fn new(config: ClerkFapiConfiguration) -> Result<Self, reqwest::Error> {
+        Ok(Self::from_client(ClerkFapiClient::new(config)?))
+    }
+
+    /// Creates a verifier that fetches the JWKS with an existing client
+    pub fn from_client(client: ClerkFapiClient) -> Self {
+        Self {
+            client,
+            keys: Arc::new(RwLock::new(KeySet::default())),
+            leeway: DEFAULT_LEEWAY,
+            authorized_parties: Vec::new(),
+            cache_ttl: DEFAULT_CACHE_TTL,
+            min_refetch_interval: DEFAULT_MIN_REFETCH_INTERVAL,
+        }
+    }
+
+    /// Sets the clock skew allowed when checking `exp`, `nbf` and `iat`
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// Sets the origins accepted in the `azp` claim
+    ///
+    /// Tokens without `azp` are accepted. Empty by default, which accepts any origin.
+    pub fn with_authorized_parties(mut self, parties: Vec<String>) -> Self {
+        self.authorized_parties = parties;
+        self
+    }
+
+    /// Sets how long the fetched keys are used before fetching them again
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Sets the minimum time between fetches triggered by an unknown `kid`
+    pub fn with_min_refetch_interval(mut self, interval: Duration) -> Self {
+        self.min_refetch_interval = interval;
+        self
+    }
+
+    /// Verifies the signature and validity of the token and returns its claims
+    pub async fn verify(&self, token: &str) -> Result<SessionClaims, Error> {
+        self.verify_as(token).await
+    }
+
+    /// Same as [`JwtVerifier::verify`], deserializing the custom claims into `T`
+    pub async fn verify_as<T: DeserializeOwned>(
+        &self,
+        token: &str,
+    ) -> Result<SessionClaims<T>, Error> {
+        let mut parts = token.split('.');
+        let (Some(header), Some(payload), Some(sig), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(Error::Malformed("expected three segments".to_string()));
+        };
+
+        let message = &token[..header.len() + payload.len() + 1];
+        let header: Header = decode_json(header)?;
+        let algorithm = Algorithm::from_name(&header.alg)
+            .ok_or_else(|| Error::UnsupportedAlgorithm(header.alg.clone()))?;
+        let kid = header.kid.ok_or(Error::UnknownKey(None))?;
+
+        let key = self.key(&kid).await?;
+        if key.algorithm() != algorithm {
+            return Err(Error::UnsupportedAlgorithm(header.alg));
+        }
+        let sig = decode_segment(sig)?;
+        key.verify(message.as_bytes(), &sig)?;
+
+        let claims = SessionClaims::from_payload(payload)?;
+        self.validate(&claims, chrono::Utc::now().timestamp())?;
+        Ok(claims)
+    }
+
+    fn validate<T>(&self, claims: &SessionClaims<T>, now: i64) -> Result<(), Error> {
+        let leeway = self.leeway.as_secs() as i64;
+        if claims.exp + leeway <= now {
+            return Err(Error::Expired);
+        }
+        if claims.nbf.is_some_and(|nbf| nbf - leeway > now)
+            || claims.iat.is_some_and(|iat| iat - leeway > now)
+        {
+            return Err(Error::NotYetValid);
+        }
+        if let Some(azp) = &claims.azp {
+            if !self.authorized_parties.is_empty() && !self.authorized_parties.contains(azp) {
+                return Err(Error::UnauthorizedParty(azp.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the key for `kid`, fetching the JWKS if the cache is stale or
+    /// doesn't know the key
+    async fn key(&self, kid: &str) -> Result<VerifyingKey, Error> {
+        {
+            let keys = self.keys.read().await;
+            if keys.is_fresh(self.cache_ttl) {
+                if let Some(key) = keys.keys.get(kid) {
+                    return Ok(key.clone());
+                }
+            }
+        }
+
+        let mut keys = self.keys.write().await;
+        let needs_fetch = !keys.is_fresh(self.cache_ttl) || !keys.keys.contains_key(kid);
+        let attempted_recently = keys
+            .attempted_at
+            .is_some_and(|t| t.elapsed() < self.min_refetch_interval);
+        if needs_fetch && !attempted_recently {
+            keys.attempted_at = Some(Instant::now());
+            match self.client.get_jwks().await {
+                Ok(jwks) => {
+                    keys.keys = jwks
+                        .keys
+                        .iter()
+                        .flatten()
+                        .filter_map(|jwk| Some((jwk.kid.clone()?, VerifyingKey::from_jwk(jwk)?)))
+                        .collect();
+                    keys.fetched_at = Some(Instant::now());
+                }
+                // Keep verifying with the cached keys while the JWKS can't be
+                // refreshed, only fail if there is nothing to fall back to
+                Err(e) if keys.keys.is_empty() => return Err(Error::Jwks(e.into())),
+                Err(_) => {}
+            }
+        }
+        keys.keys
+            .get(kid)
+            .cloned()
+            .ok_or_else(|| Error::UnknownKey(Some(kid.to_string())))
+    }
+}
+
+fn decode_segment(segment: &str) -> Result<Vec<u8>, Error> {
+    general_purpose::URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|e| Error::Malformed(e.to_string()))
+}
+
+fn decode_json<T: DeserializeOwned>(segment: &str) -> Result<T, Error> {
+    serde_json::from_slice(&decode_segment(segment)?).map_err(malformed)
+}
+
+fn malformed(e: serde_json::Error) -> Error {
+    Error::Malformed(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, RsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+
+    /// 2048 bit RSA key in PKCS#1 DER, only used to sign test tokens
+    const RSA_TEST_KEY: &str = "\
+        MIIEowIBAAKCAQEApR27D2ccnBkqgsIto7TXEmhqxkZRXPxpM3OFuMog7jlgBRLSNqiFH3tuesxY\
+        x0sh2BbONhpuQkwapXVeefbJkGN6kfSWaAF8e5cXbRC84EUr9erhWZ8ctX9uuql5um/dbjDkkiVn\
+        pptX2tV4k91D4thKbXi8FN88rJS3M6ZPHXuL3wk78zTK/UXF0NlfpvZuHAWcn2vjKCP80pPId/Jd\
+        AV1yl1suLwHN9yaA36e5mzSopI6Z33vC204pE0WXLCM70FlDjfm2COUXR3Hdqnjbn9Ob8i+TgyVQ\
+        fP4mTQsmIOvTu7Ko0ceCzGfiao8+Ctz1zVjrkH7Qzc2R1oPkfEtmOQIDAQABAoIBAAiNXiuBkTtK\
+        mGeWbCu/CabD7sffS4aP8lj+Yk48dfEw2pbYrzeyidakG4hTvkNwUkvxfnPR8aUQcQ7h4edaT31h\
+        ka8upn2zmgGCxW1aQRYyrwC5TjvIoIGMHK2hapMsCSGUebpSIqjAAhBtHyY1fzcoMc6ywrcoCun7\
+        d/O+aPmjX7lx4P3zlY3THhDpv+9B+FERYxlHwHoXSEnqg80X45t4gCo3IN1gqpV0xKlCJDBIO/8T\
+        j5RlyaoywTnN6iuwBWbOJ8w9Qq9X8+aTne7+OXH1lJtcV9gF8I7qrHH/GPoTfb2iqSgp5mjQ2fqA\
+        PdeG/U92TguS6BGE+AtO4QQJz1sCgYEA2cZC6ya1vsXjaocR49dKpxBWgUxXOFtlsU/kda5veORP\
+        zSHKIEOvRBPQz98BkCI1zNR55oxememwrTMvoJ8RvXj6pW8LB0ZE73+hgRMS9gxwgwNQ9cOKKAp4\
+        FoNPZhgYV1lF8+EqGFkivyS5GxR+PUYdn2vRhB54lt4kM0gPTwMCgYEAwhlB0HQQrQKnyQ3WWiMp\
+        8W04KbdlOts9y4OuGbBiJTVKnCqxqnHmbrqfBlV4KYQgDvWO8DBIGs3pZVvyN72Vilh1dgHki37Q\
+        7mL7MoA7vmvBBe7CnxQM/hMHhhpjQFeGdk3ORfDMlUU1d3xrz8u8IssPSqTjUxAp1mkDeLk+gxMC\
+        gYAsq4lgeNFA75CFcm1OAgRTHnvQC9u8OKs0YsHnDb/Z2WMxHaQ7WAbkfPgRG5pTeWD1wgO7McHZ\
+        6mCONninhtl5tw4azxtBA73yLojvU/1IsSaHCTAbKV/2dKdLXuXzeC/OAwbhTqd21RnWgW7iyiJW\
+        6UH8SH5yjlmIAMsGJdrWBwKBgA+HK7+8eVUOAFRSWhKt+34ICdU0idGylngAmd4OQVvOrqBdvgbv\
+        w8qZ/XzpO4q+uj3YIU5P3oTeckyclX0mGp+Nwh/luN38EETyADyjC6SJRFDwL/vAA+YTqadt1wDf\
+        v68ZPGZWBFfwyH09o/ZkyzFRY3DKTjFn+qDfl9qBn2GnAoGBAKVssKnwOVHBJc3mVLU91+GXI/eA\
+        wiy6gklwNBoAnNbytbjvo2mdEvJWS3PJ1cgA4KaQ377eJfVSuUKjP0JxrXpQQfv/rM/v1FqDaQ7T\
+        Je2Wp2ENrHGGHteERet14qMW4/WnrzydSGsdLcEICQnNZsiFU3eK4ok69tc6Ntg4KcVh";
+
+    fn encode(bytes: &[u8]) -> String {
+        general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    fn now() -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+
+    fn session_claims() -> serde_json::Value {
+        serde_json::json!({
+            "sid": "sess_123",
+            "sub": "user_123",
+            "azp": "https://app.example.com",
+            "iss": "https://clerk.example.com",
+            "exp": now() + 60,
+            "iat": now(),
+            "nbf": now(),
+            "org_id": "org_123",
+            "org_role": "org:admin",
+            "org_permissions": ["org:sys_memberships:manage"],
+        })
+    }
+
+    struct EcKey {
+        kid: String,
+        key_pair: EcdsaKeyPair,
+    }
+
+    impl EcKey {
+        fn generate(kid: &str) -> Self {
+            let rng = SystemRandom::new();
+            let pkcs8 =
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+            let key_pair =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+                    .unwrap();
+            Self {
+                kid: kid.to_string(),
+                key_pair,
+            }
+        }
+
+        fn jwk(&self) -> serde_json::Value {
+            let point = self.key_pair.public_key().as_ref();
+            serde_json::json!({
+                "use": "sig",
+                "kty": "EC",
+                "kid": self.kid,
+                "alg": "ES256",
+                "crv": "P-256",
+                "x": encode(&point[1..33]),
+                "y": encode(&point[33..65]),
+            })
+        }
+
+        fn sign(&self, claims: &serde_json::Value) -> String {
+            let header = serde_json::json!({"alg": "ES256", "kid": self.kid, "typ": "JWT"});
+            let message = format!(
+                "{}.{}",
+                encode(header.to_string().as_bytes()),
+                encode(claims.to_string().as_bytes())
+            );
+            let sig = self
+                .key_pair
+                .sign(&SystemRandom::new(), message.as_bytes())
+                .unwrap();
+            format!("{}.{}", message, encode(sig.as_ref()))
+        }
+    }
+
+    async fn jwks_mock(
+        server: &mut mockito::Server,
+        keys: Vec<serde_json::Value>,
+    ) -> mockito::Mock {
+        server
+            .mock("GET", "/.well-known/jwks.json?_is_native=1")
+            .with_status(200)
+            .with_body(serde_json::json!({ "keys": keys }).to_string())
+            .create_async()
+            .await
+    }
+
+    fn verifier(server: &mockito::Server) -> JwtVerifier {
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some(server.url()),
+            None,
+        )
+        .unwrap();
+        JwtVerifier::new(config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verifies_es256_token() {
+        let mut server = mockito::Server::new_async().await;
+        let key = EcKey::generate("ins_1");
+        let mock = server
+            .mock("GET", "/.well-known/jwks.json?_is_native=1")
+            .with_status(200)
+            .with_body(serde_json::json!({ "keys": [key.jwk()] }).to_string())
+            .expect(1)
+            .create_async()
+            .await;
+
+        let verifier =
+            verifier(&server).with_authorized_parties(vec!["https://app.example.com".to_string()]);
+        let claims = verifier.verify(&key.sign(&session_claims())).await.unwrap();
+
+        assert_eq!(claims.sid.as_deref(), Some("sess_123"));
+        assert_eq!(claims.sub, "user_123");
+        assert_eq!(claims.org_id.as_deref(), Some("org_123"));
+        assert_eq!(claims.org_role.as_deref(), Some("org:admin"));
+        assert_eq!(claims.org_permissions, vec!["org:sys_memberships:manage"]);
+
+        // The second verification uses the cached keys
+        verifier.verify(&key.sign(&session_claims())).await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_verifies_rs256_token() {
+        let mut server = mockito::Server::new_async().await;
+        let der = general_purpose::STANDARD.decode(RSA_TEST_KEY).unwrap();
+        let key_pair = RsaKeyPair::from_der(&der).unwrap();
+        let public = RsaPublicKeyComponents::<Vec<u8>>::from(key_pair.public());
+        let jwk = serde_json::json!({
+            "use": "sig",
+            "kty": "RSA",
+            "kid": "ins_rsa",
+            "alg": "RS256",
+            "n": encode(&public.n),
+            "e": encode(&public.e),
+        });
+        jwks_mock(&mut server, vec![jwk]).await;
+
+        let header = serde_json::json!({"alg": "RS256", "kid": "ins_rsa", "typ": "JWT"});
+        let message = format!(
+            "{}.{}",
+            encode(header.to_string().as_bytes()),
+            encode(session_claims().to_string().as_bytes())
+        );
+        let mut sig = vec![0; key_pair.public().modulus_len()];
+        key_pair
+            .sign(
+                &signature::RSA_PKCS1_SHA256,
+                &SystemRandom::new(),
+                message.as_bytes(),
+                &mut sig,
+            )
+            .unwrap();
+        let token = format!("{}.{}", message, encode(&sig));
+
+        let claims = verifier(&server).verify(&token).await.unwrap();
+        assert_eq!(claims.sid.as_deref(), Some("sess_123"));
+
+        let tampered = format!("{}.{}", message.replace('.', ".e30"), encode(&sig));
+        assert!(matches!(
+            verifier(&server).verify(&tampered).await,
+            Err(Error::InvalidSignature)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_refetches_jwks_on_unknown_kid() {
+        let mut server = mockito::Server::new_async().await;
+        let old_key = EcKey::generate("ins_old");
+        let new_key = EcKey::generate("ins_new");
+        let old_mock = jwks_mock(&mut server, vec![old_key.jwk()]).await;
+
+        let verifier = verifier(&server).with_min_refetch_interval(Duration::ZERO);
+        verifier
+            .verify(&old_key.sign(&session_claims()))
+            .await
+            .unwrap();
+        old_mock.remove_async().await;
+
+        let new_mock = jwks_mock(&mut server, vec![new_key.jwk()]).await;
+        verifier
+            .verify(&new_key.sign(&session_claims()))
+            .await
+            .unwrap();
+        new_mock.assert_async().await;
+
+        // The rotated out key is no longer accepted
+        assert!(matches!(
+            verifier.verify(&old_key.sign(&session_claims())).await,
+            Err(Error::UnknownKey(Some(kid))) if kid == "ins_old"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_tokens() {
+        let mut server = mockito::Server::new_async().await;
+        let key = EcKey::generate("ins_1");
+        let other_key = EcKey::generate("ins_1");
+        jwks_mock(&mut server, vec![key.jwk()]).await;
+        let verifier = verifier(&server)
+            .with_leeway(Duration::from_secs(5))
+            .with_authorized_parties(vec!["https://other.example.com".to_string()]);
+
+        let mut claims = session_claims();
+        claims["azp"] = serde_json::json!(null);
+
+        assert!(matches!(
+            verifier.verify(&other_key.sign(&claims)).await,
+            Err(Error::InvalidSignature)
+        ));
+
+        let mut expired = claims.clone();
+        expired["exp"] = serde_json::json!(now() - 10);
+        assert!(matches!(
+            verifier.verify(&key.sign(&expired)).await,
+            Err(Error::Expired)
+        ));
+
+        let mut within_leeway = claims.clone();
+        within_leeway["exp"] = serde_json::json!(now() - 2);
+        assert!(verifier.verify(&key.sign(&within_leeway)).await.is_ok());
+
+        let mut future = claims.clone();
+        future["nbf"] = serde_json::json!(now() + 60);
+        assert!(matches!(
+            verifier.verify(&key.sign(&future)).await,
+            Err(Error::NotYetValid)
+        ));
+
+        assert!(matches!(
+            verifier.verify(&key.sign(&session_claims())).await,
+            Err(Error::UnauthorizedParty(azp)) if azp == "https://app.example.com"
+        ));
+
+        assert!(matches!(
+            verifier.verify("not-a-token").await,
+            Err(Error::Malformed(_))
+        ));
+    }
+
+    fn unsigned_token(claims: &serde_json::Value) -> String {
+        format!("e30.{}.sig", encode(claims.to_string().as_bytes()))
+    }
+
+    #[test]
+    fn test_expands_v2_organization_claims() {
+        let token = unsigned_token(&serde_json::json!({
+            "v": 2,
+            "sid": "sess_123",
+            "sub": "user_123",
+            "exp": 1,
+            "fea": "o:reports,u:billing,o:members",
+            "o": {
+                "id": "org_123",
+                "slg": "acme",
+                "rol": "admin",
+                "per": "read,manage",
+                "fpm": "1,3",
+            },
+        }));
+        let claims = SessionClaims::<JsonMap<String, JsonValue>>::decode(&token).unwrap();
+
+        assert_eq!(claims.version, 2);
+        assert_eq!(claims.org_id.as_deref(), Some("org_123"));
+        assert_eq!(claims.org_slug.as_deref(), Some("acme"));
+        assert_eq!(claims.org_role.as_deref(), Some("org:admin"));
+        assert_eq!(
+            claims.org_permissions,
+            vec!["org:reports:read", "org:members:read", "org:members:manage"]
+        );
+        assert!(claims.custom.is_empty());
+    }
+
+    #[test]
+    fn test_decodes_custom_claims() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct TemplateClaims {
+            email: String,
+            plan: Option<String>,
+        }
+
+        let mut payload = session_claims();
+        payload["org_slug"] = serde_json::json!("acme");
+        payload["email"] = serde_json::json!("user@example.com");
+        let token = unsigned_token(&payload);
+
+        let claims = SessionClaims::<TemplateClaims>::decode(&token).unwrap();
+        assert_eq!(claims.version, 1);
+        assert_eq!(claims.org_slug.as_deref(), Some("acme"));
+        assert_eq!(
+            claims.custom,
+            TemplateClaims {
+                email: "user@example.com".to_string(),
+                plan: None,
+            }
+        );
+
+        let claims = SessionClaims::<JsonMap<String, JsonValue>>::decode(&token).unwrap();
+        assert_eq!(claims.custom.keys().collect::<Vec<_>>(), vec!["email"]);
+
+        assert!(matches!(
+            SessionClaims::<TemplateClaims>::decode(&unsigned_token(&session_claims())),
+            Err(Error::Malformed(_))
+        ));
+    }
+}
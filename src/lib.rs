@@ -12,4 +12,18 @@ pub mod apis;
 pub mod clerk;
 pub mod clerk_fapi;
 pub mod configuration;
+pub mod enterprise_sso;
+pub mod events;
+pub mod jwt;
 pub mod models;
+pub mod oauth;
+pub mod params;
+pub mod passkey;
+pub mod saml_metadata;
+pub mod sign_in;
+pub mod sign_up;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+mod loopback;
+mod token_cache;
@@ -0,0 +1,126 @@
+//! Loopback HTTP listener receiving the browser redirect that ends OAuth and
+//! SSO flows in native apps
+
+use crate::clerk::Error;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+/// Requests with a larger head are not redirects, they are dropped
+const MAX_REQUEST_HEAD_BYTES: usize = 16 * 1024;
+
+/// How long a connection may take to send its request, browsers open
+/// speculative connections that never send anything
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+const CLOSE_PAGE: &str = "<!DOCTYPE html><html><body>\
+    <p>You can close this window and return to the app.</p>\
+    </body></html>";
+
+pub(crate) struct Loopback {
+    listener: TcpListener,
+    path: String,
+    url: String,
+}
+
+impl Loopback {
+    /// Listens on `127.0.0.1:port`, a port of 0 picks a free one
+    pub(crate) async fn bind(port: u16, path: &str) -> Result<Self, Error> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|e| Error::Transport(Box::new(e)))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| Error::Transport(Box::new(e)))?
+            .port();
+        let path = format!("/{}", path.trim_start_matches('/'));
+        Ok(Self {
+            url: format!("http://127.0.0.1:{}{}", port, path),
+            listener,
+            path,
+        })
+    }
+
+    /// The URL the flow should redirect to
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Waits for a request to the redirect URL and returns its query parameters
+    pub(crate) async fn wait(self, timeout: Duration) -> Result<HashMap<String, String>, Error> {
+        tokio::time::timeout(timeout, self.accept_redirect())
+            .await
+            .map_err(|_| Error::RedirectTimeout)?
+    }
+
+    async fn accept_redirect(self) -> Result<HashMap<String, String>, Error> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        loop {
+            tokio::select! {
+                accepted = self.listener.accept() => {
+                    let (stream, _) = accepted.map_err(|e| Error::Transport(Box::new(e)))?;
+                    let sender = sender.clone();
+                    let path = self.path.clone();
+                    tokio::spawn(async move {
+                        if let Some(params) = handle_connection(stream, &path).await {
+                            let _ = sender.send(params).await;
+                        }
+                    });
+                }
+                Some(params) = receiver.recv() => return Ok(params),
+            }
+        }
+    }
+}
+
+/// Answers a single request, returns the query parameters if it was the redirect
+async fn handle_connection(mut stream: TcpStream, path: &str) -> Option<HashMap<String, String>> {
+    let target = tokio::time::timeout(REQUEST_TIMEOUT, read_request_target(&mut stream))
+        .await
+        .ok()??;
+    let url = url::Url::parse(&format!("http://127.0.0.1{}", target)).ok()?;
+
+    if url.path() != path {
+        respond(&mut stream, "404 Not Found", "Not found").await;
+        return None;
+    }
+    respond(&mut stream, "200 OK", CLOSE_PAGE).await;
+    Some(url.query_pairs().into_owned().collect())
+}
+
+/// Reads the request head and returns the target of a `GET` request
+async fn read_request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD_BYTES {
+            return None;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        head.extend_from_slice(&chunk[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next()?.split(' ');
+    match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some(target)) if target.starts_with('/') => Some(target.to_string()),
+        _ => None,
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    // The browser going away doesn't matter, the redirect was received
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
@@ -15,7 +15,6 @@ use serde::{Deserialize, Serialize};
 #[serde(untagged)]
 pub enum ClientSignInFirstFactorVerification {
     StubsPeriodVerificationPeriodPassword(Box<models::StubsPeriodVerificationPeriodPassword>),
-    StubsPeriodVerificationPeriodOauth(Box<models::StubsPeriodVerificationPeriodOauth>),
     StubsPeriodVerificationPeriodOtp(Box<models::StubsPeriodVerificationPeriodOtp>),
     StubsPeriodVerificationPeriodLink(Box<models::StubsPeriodVerificationPeriodLink>),
     StubsPeriodVerificationPeriodCode(Box<models::StubsPeriodVerificationPeriodCode>),
@@ -28,6 +27,7 @@ pub enum ClientSignInFirstFactorVerification {
     StubsPeriodVerificationPeriodGoogleOneTap(
         Box<models::StubsPeriodVerificationPeriodGoogleOneTap>,
     ),
+    StubsPeriodVerificationPeriodOauth(Box<models::StubsPeriodVerificationPeriodOauth>),
 }
 
 impl Default for ClientSignInFirstFactorVerification {
@@ -14,17 +14,17 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ClientSignUpVerificationsExternalAccount {
-    StubsPeriodVerificationPeriodOauth(Box<models::StubsPeriodVerificationPeriodOauth>),
     StubsPeriodVerificationPeriodSaml(Box<models::StubsPeriodVerificationPeriodSaml>),
     StubsPeriodVerificationPeriodTicket(Box<models::StubsPeriodVerificationPeriodTicket>),
     StubsPeriodVerificationPeriodGoogleOneTap(
         Box<models::StubsPeriodVerificationPeriodGoogleOneTap>,
     ),
+    StubsPeriodVerificationPeriodOauth(Box<models::StubsPeriodVerificationPeriodOauth>),
 }
 
 impl Default for ClientSignUpVerificationsExternalAccount {
     fn default() -> Self {
-        Self::StubsPeriodVerificationPeriodOauth(Default::default())
+        Self::StubsPeriodVerificationPeriodSaml(Default::default())
     }
 }
 ///
@@ -27,6 +27,8 @@ pub struct JwksKeysInner {
     pub e: Option<String>,
     #[serde(rename = "x", skip_serializing_if = "Option::is_none")]
     pub x: Option<String>,
+    #[serde(rename = "y", skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
     #[serde(rename = "crv", skip_serializing_if = "Option::is_none")]
     pub crv: Option<String>,
 }
@@ -41,6 +43,7 @@ impl JwksKeysInner {
             n: None,
             e: None,
             x: None,
+            y: None,
             crv: None,
         }
     }
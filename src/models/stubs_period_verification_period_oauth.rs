@@ -15,8 +15,9 @@ use serde::{Deserialize, Serialize};
 pub struct StubsPeriodVerificationPeriodOauth {
     #[serde(rename = "status")]
     pub status: Status,
+    /// The OAuth provider, e.g. `oauth_google` or `oauth_custom_<name>`
     #[serde(rename = "strategy")]
-    pub strategy: Strategy,
+    pub strategy: String,
     #[serde(
         rename = "external_verification_redirect_url",
         default,
@@ -45,7 +46,7 @@ pub struct StubsPeriodVerificationPeriodOauth {
 impl StubsPeriodVerificationPeriodOauth {
     pub fn new(
         status: Status,
-        strategy: Strategy,
+        strategy: String,
         expire_at: i64,
     ) -> StubsPeriodVerificationPeriodOauth {
         StubsPeriodVerificationPeriodOauth {
@@ -78,21 +79,3 @@ impl Default for Status {
         Self::Unverified
     }
 }
-///
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
-pub enum Strategy {
-    #[serde(rename = "oauth_apple")]
-    Apple,
-    #[serde(rename = "oauth_google")]
-    Google,
-    #[serde(rename = "oauth_mock")]
-    Mock,
-    #[serde(rename = "oauth_custom_mock")]
-    CustomMock,
-}
-
-impl Default for Strategy {
-    fn default() -> Strategy {
-        Self::Apple
-    }
-}
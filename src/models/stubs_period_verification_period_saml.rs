@@ -79,6 +79,8 @@ impl Default for Status {
 pub enum Strategy {
     #[serde(rename = "saml")]
     Saml,
+    #[serde(rename = "enterprise_sso")]
+    EnterpriseSso,
 }
 
 impl Default for Strategy {
@@ -0,0 +1,538 @@
+//! OAuth sign ins with social providers for native apps
+//!
+//! [`Clerk::oauth`] starts a sign in with one of the [`social_strategies`] of
+//! the instance and listens on a loopback address for the redirect that ends
+//! it. The app only has to open [`PendingOAuth::authorization_url`] in a
+//! browser. An account that doesn't exist yet is transferred to a sign up.
+//!
+//! Clerk only redirects native apps to allowlisted URLs, so production apps
+//! usually need a fixed [`OAuthFlow::port`].
+//!
+//! Enterprise SSO sign ins, see [`crate::enterprise_sso`], continue with the
+//! same [`PendingOAuth`].
+//!
+//! ```no_run
+//! # use clerk_fapi_rs::clerk::{Clerk, Error};
+//! # async fn example(clerk: Clerk) -> Result<(), Error> {
+//! let pending = clerk.oauth("oauth_google").start().await?;
+//! println!("Continue in the browser: {}", pending.authorization_url());
+//!
+//! let outcome = pending.complete().await?;
+//! match outcome.session_id() {
+//!     Some(session_id) => println!("signed in: {}", session_id),
+//!     None => println!("more steps needed: {:?}", outcome),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::clerk::{Clerk, Error};
+use crate::loopback::Loopback;
+use crate::models::client_period_sign_in::Status;
+use crate::models::client_sign_in_first_factor_verification::ClientSignInFirstFactorVerification as Verification;
+use crate::models::stubs_period_verification_period_oauth::Status as VerificationStatus;
+use crate::models::stubs_period_verification_period_saml::Status as SamlStatus;
+use crate::models::{ClientPeriodEnvironment as Environment, ClientPeriodSignIn as SignIn};
+use crate::params::{CreateSignInParams, CreateSignUpParams};
+use crate::sign_in::SignInState;
+use crate::sign_up::SignUpState;
+use serde_json::Value as JsonValue;
+use std::fmt;
+use std::time::Duration;
+
+/// How long [`PendingOAuth::complete`] waits for the redirect by default
+pub const DEFAULT_REDIRECT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+const DEFAULT_CALLBACK_PATH: &str = "/oauth-callback";
+
+/// Returns the social strategies users can sign in with, e.g. `oauth_google`
+pub fn social_strategies(environment: &Environment) -> Vec<String> {
+    let socials = environment
+        .user_settings
+        .as_ref()
+        .and_then(|settings| serde_json::to_value(&settings.social).ok());
+    let Some(JsonValue::Object(socials)) = socials else {
+        return Vec::new();
+    };
+
+    socials
+        .values()
+        .filter(|social| social["enabled"] == true && social["authenticatable"] == true)
+        .filter_map(|social| social["strategy"].as_str().map(str::to_string))
+        .collect()
+}
+
+/// OAuth sign in, created with [`Clerk::oauth`]
+pub struct OAuthFlow {
+    clerk: Clerk,
+    strategy: String,
+    port: u16,
+    callback_path: String,
+    timeout: Duration,
+}
+
+impl OAuthFlow {
+    pub(crate) fn new(clerk: Clerk, strategy: &str) -> Self {
+        Self {
+            clerk,
+            strategy: strategy.to_string(),
+            port: 0,
+            callback_path: DEFAULT_CALLBACK_PATH.to_string(),
+            timeout: DEFAULT_REDIRECT_TIMEOUT,
+        }
+    }
+
+    /// Port of the loopback listener, by default a free one is picked
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Path of the redirect URL, `/oauth-callback` by default
+    pub fn callback_path(mut self, path: impl Into<String>) -> Self {
+        self.callback_path = path.into();
+        self
+    }
+
+    /// How long to wait for the redirect, see [`DEFAULT_REDIRECT_TIMEOUT`]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Starts listening for the redirect and creates the sign in
+    ///
+    /// Fails with [`Error::StrategyNotEnabled`] before making a request if
+    /// the strategy isn't one of the [`social_strategies`], and with
+    /// [`Error::NotLoaded`] if the environment hasn't been loaded.
+    pub async fn start(self) -> Result<PendingOAuth, Error> {
+        let environment = self.clerk.environment().await.ok_or(Error::NotLoaded)?;
+        if !social_strategies(&environment).contains(&self.strategy) {
+            return Err(Error::StrategyNotEnabled(self.strategy));
+        }
+
+        let loopback = Loopback::bind(self.port, &self.callback_path).await?;
+        let response = self
+            .clerk
+            .get_fapi_client()
+            .create_sign_in_with(CreateSignInParams::oauth(&self.strategy, loopback.url()))
+            .await?;
+        PendingOAuth::new(self.clerk, *response.response, loopback, self.timeout)
+    }
+}
+
+/// The sign in waits for the user to authorize the app in the browser
+pub struct PendingOAuth {
+    redirect: PendingRedirect,
+    authorization_url: String,
+}
+
+impl PendingOAuth {
+    /// Continues a sign in whose external verification was started with the
+    /// loopback URL as the redirect URL
+    pub(crate) fn new(
+        clerk: Clerk,
+        sign_in: SignIn,
+        loopback: Loopback,
+        timeout: Duration,
+    ) -> Result<Self, Error> {
+        let authorization_url = external_verification(&sign_in)
+            .and_then(|verification| verification.redirect_url)
+            .ok_or_else(|| {
+                Error::ExternalVerificationFailed("no authorization URL returned".to_string())
+            })?;
+
+        Ok(PendingOAuth {
+            redirect: PendingRedirect {
+                clerk,
+                sign_in,
+                loopback,
+                timeout,
+            },
+            authorization_url,
+        })
+    }
+
+    /// The URL of the provider to open in the browser
+    pub fn authorization_url(&self) -> &str {
+        &self.authorization_url
+    }
+
+    /// The loopback URL the browser is redirected to at the end
+    pub fn redirect_url(&self) -> &str {
+        self.redirect.loopback.url()
+    }
+
+    /// The sign in as returned when the external verification was started
+    pub fn sign_in(&self) -> &SignIn {
+        &self.redirect.sign_in
+    }
+
+    /// Waits for the redirect and completes the sign in, or the sign up when
+    /// the account doesn't exist yet
+    ///
+    /// Fails with [`Error::RedirectTimeout`] if the redirect doesn't arrive in
+    /// time and with [`Error::ExternalVerificationFailed`] if the provider
+    /// didn't verify the account.
+    pub async fn complete(self) -> Result<OAuthOutcome, Error> {
+        self.redirect.complete().await
+    }
+}
+
+impl fmt::Debug for PendingOAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingOAuth")
+            .field("sign_in_id", &self.redirect.sign_in.id)
+            .field("redirect_url", &self.redirect_url())
+            .finish()
+    }
+}
+
+/// A sign in that continues once the browser is redirected to the loopback listener
+pub(crate) struct PendingRedirect {
+    pub(crate) clerk: Clerk,
+    pub(crate) sign_in: SignIn,
+    pub(crate) loopback: Loopback,
+    pub(crate) timeout: Duration,
+}
+
+impl PendingRedirect {
+    pub(crate) async fn complete(self) -> Result<OAuthOutcome, Error> {
+        let params = self.loopback.wait(self.timeout).await?;
+
+        // The nonce in native redirects rotates the client token to the signed in client
+        let fapi = self.clerk.get_fapi_client();
+        let response = match params.get("rotating_token_nonce") {
+            Some(nonce) => fapi.get_sign_in_with_nonce(&self.sign_in.id, nonce).await?,
+            None => fapi.get_sign_in(&self.sign_in.id).await?,
+        };
+        let sign_in = *response.response;
+
+        match external_verification(&sign_in) {
+            Some(verification) if verification.status == VerificationStatus::Transferable => {
+                let response = self
+                    .clerk
+                    .get_fapi_client()
+                    .create_sign_ups_with(CreateSignUpParams::transfer())
+                    .await?;
+                let state = SignUpState::from_sign_up(self.clerk, *response.response).await?;
+                return Ok(OAuthOutcome::SignUp(state));
+            }
+            Some(verification)
+                if sign_in.status != Status::Complete
+                    && matches!(
+                        verification.status,
+                        VerificationStatus::Failed | VerificationStatus::Expired
+                    ) =>
+            {
+                let reason = verification
+                    .error
+                    .unwrap_or_else(|| format!("verification {:?}", verification.status));
+                return Err(Error::ExternalVerificationFailed(reason));
+            }
+            _ => {}
+        }
+        let state = SignInState::from_sign_in(self.clerk, sign_in).await?;
+        Ok(OAuthOutcome::SignIn(state))
+    }
+}
+
+/// The redirect based first factor verification of a sign in
+pub(crate) struct ExternalVerification {
+    pub(crate) status: VerificationStatus,
+    pub(crate) redirect_url: Option<String>,
+    pub(crate) error: Option<String>,
+}
+
+pub(crate) fn external_verification(sign_in: &SignIn) -> Option<ExternalVerification> {
+    match sign_in.first_factor_verification.as_deref()? {
+        Verification::StubsPeriodVerificationPeriodOauth(verification) => {
+            Some(ExternalVerification {
+                status: verification.status,
+                redirect_url: verification
+                    .external_verification_redirect_url
+                    .clone()
+                    .flatten(),
+                error: verification
+                    .error
+                    .clone()
+                    .flatten()
+                    .map(|error| error.long_message),
+            })
+        }
+        Verification::StubsPeriodVerificationPeriodSaml(verification) => {
+            Some(ExternalVerification {
+                status: match verification.status {
+                    SamlStatus::Unverified => VerificationStatus::Unverified,
+                    SamlStatus::Verified => VerificationStatus::Verified,
+                    SamlStatus::Failed => VerificationStatus::Failed,
+                    SamlStatus::Expired => VerificationStatus::Expired,
+                    SamlStatus::Transferable => VerificationStatus::Transferable,
+                },
+                redirect_url: verification
+                    .external_verification_redirect_url
+                    .clone()
+                    .flatten(),
+                error: verification
+                    .error
+                    .as_ref()
+                    .map(|error| error.long_message.clone()),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The result of an OAuth or enterprise SSO sign in after the redirect
+// Both states are large, it's returned once per flow
+#[allow(clippy::large_enum_variant)]
+pub enum OAuthOutcome {
+    /// The account exists, the sign in is complete or needs more steps such
+    /// as a second factor
+    SignIn(SignInState),
+    /// The account didn't exist and the sign in was transferred to a sign up,
+    /// which may need more fields such as a username
+    SignUp(SignUpState),
+}
+
+impl OAuthOutcome {
+    /// The ID of the session that was activated, `None` if more steps are needed
+    pub fn session_id(&self) -> Option<&str> {
+        match self {
+            OAuthOutcome::SignIn(SignInState::Complete(complete)) => Some(complete.session_id()),
+            OAuthOutcome::SignIn(_) => None,
+            OAuthOutcome::SignUp(SignUpState::Complete(complete)) => Some(complete.session_id()),
+            OAuthOutcome::SignUp(_) => None,
+        }
+    }
+}
+
+impl fmt::Debug for OAuthOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OAuthOutcome::SignIn(state) => f.debug_tuple("SignIn").field(state).finish(),
+            OAuthOutcome::SignUp(state) => f.debug_tuple("SignUp").field(state).finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::client_period_sign_up::Status as SignUpStatus;
+    use crate::models::{
+        ClientPeriodClient as Client, ClientPeriodSignUp as SignUp,
+        StubsPeriodVerificationPeriodOauth as OAuthVerification,
+        UserSettingsPeriodSocial as Social,
+    };
+    use crate::testing::fixtures::{
+        loaded_clerk, response_body, signed_in_client, ClientBuilder, EnvironmentBuilder,
+    };
+    use mockito::{Matcher, Server};
+
+    fn environment() -> Environment {
+        let mut environment = EnvironmentBuilder::new().build();
+        let social = &mut environment.user_settings.as_mut().unwrap().social;
+        social.oauth_github = Some(Box::new(Social::new(
+            true,
+            false,
+            true,
+            "oauth_github".to_string(),
+        )));
+        // Enabled only for connecting accounts, not for signing in
+        social.oauth_google = Some(Box::new(Social::new(
+            true,
+            false,
+            false,
+            "oauth_google".to_string(),
+        )));
+        environment
+    }
+
+    fn sign_in(status: Status, verification: VerificationStatus, server: &Server) -> SignIn {
+        let mut oauth =
+            OAuthVerification::new(verification, "oauth_github".to_string(), 1_704_067_200_000);
+        oauth.external_verification_redirect_url =
+            Some(Some(format!("{}/provider/authorize", server.url())));
+        SignIn {
+            id: "sia_123".to_string(),
+            status,
+            first_factor_verification: Some(Box::new(
+                Verification::StubsPeriodVerificationPeriodOauth(Box::new(oauth)),
+            )),
+            created_session_id: (status == Status::Complete).then(|| "sess_123".to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Starts the flow and follows the authorization URL like a browser,
+    /// the provider redirects back with the nonce
+    async fn authorize(server: &mut Server, clerk: &Clerk) -> Result<OAuthOutcome, Error> {
+        let create_mock = server
+            .mock("POST", "/v1/client/sign_ins?_is_native=1")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("strategy".to_string(), "oauth_github".to_string()),
+                Matcher::Regex("redirect_url=http%3A%2F%2F127.0.0.1".to_string()),
+            ]))
+            .with_body(response_body(
+                &sign_in(
+                    Status::NeedsFirstFactor,
+                    VerificationStatus::Unverified,
+                    server,
+                ),
+                Client::default(),
+            ))
+            .create_async()
+            .await;
+
+        let pending = clerk.oauth("oauth_github").start().await.unwrap();
+        assert_eq!(
+            pending.authorization_url(),
+            format!("{}/provider/authorize", server.url())
+        );
+        assert!(pending.redirect_url().ends_with("/oauth-callback"));
+        server
+            .mock("GET", "/provider/authorize")
+            .with_status(302)
+            .with_header(
+                "location",
+                &format!("{}?rotating_token_nonce=nonce_123", pending.redirect_url()),
+            )
+            .create_async()
+            .await;
+
+        let authorization_url = pending.authorization_url().to_string();
+        let browser = async {
+            let page = reqwest::get(authorization_url).await.unwrap();
+            assert!(page.status().is_success());
+        };
+        let (outcome, ()) = tokio::join!(pending.complete(), browser);
+        create_mock.assert_async().await;
+        outcome
+    }
+
+    fn reload_mock(server: &mut Server, sign_in: &SignIn, client: Client) -> mockito::Mock {
+        server
+            .mock("GET", "/v1/client/sign_ins/sia_123")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("rotating_token_nonce".to_string(), "nonce_123".to_string()),
+                Matcher::UrlEncoded("_is_native".to_string(), "1".to_string()),
+            ]))
+            .with_body(response_body(sign_in, client))
+    }
+
+    #[test]
+    fn test_social_strategies() {
+        assert_eq!(social_strategies(&environment()), vec!["oauth_github"]);
+        assert!(social_strategies(&Environment::default()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_completes_after_redirect() {
+        let mut server = Server::new_async().await;
+        let clerk = loaded_clerk(server.url(), environment(), ClientBuilder::new()).await;
+        let complete = sign_in(Status::Complete, VerificationStatus::Verified, &server);
+        let reload = reload_mock(&mut server, &complete, signed_in_client())
+            .create_async()
+            .await;
+
+        let outcome = authorize(&mut server, &clerk).await.unwrap();
+
+        assert!(matches!(outcome, OAuthOutcome::SignIn(_)));
+        assert_eq!(outcome.session_id(), Some("sess_123"));
+        assert_eq!(
+            clerk.session().await.and_then(|s| s.id).as_deref(),
+            Some("sess_123")
+        );
+        reload.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_new_account_is_transferred_to_sign_up() {
+        let mut server = Server::new_async().await;
+        let clerk = loaded_clerk(server.url(), environment(), ClientBuilder::new()).await;
+        let transferable = sign_in(
+            Status::NeedsFirstFactor,
+            VerificationStatus::Transferable,
+            &server,
+        );
+        let reload = reload_mock(&mut server, &transferable, Client::default())
+            .create_async()
+            .await;
+        let sign_up = SignUp {
+            id: Some("sua_123".to_string()),
+            status: Some(SignUpStatus::Complete),
+            created_session_id: Some(Some("sess_123".to_string())),
+            ..Default::default()
+        };
+        let transfer = server
+            .mock("POST", "/v1/client/sign_ups?_is_native=1")
+            .match_body(Matcher::UrlEncoded(
+                "transfer".to_string(),
+                "true".to_string(),
+            ))
+            .with_body(response_body(&sign_up, signed_in_client()))
+            .create_async()
+            .await;
+
+        let outcome = authorize(&mut server, &clerk).await.unwrap();
+
+        assert!(matches!(outcome, OAuthOutcome::SignUp(_)));
+        assert_eq!(outcome.session_id(), Some("sess_123"));
+        reload.assert_async().await;
+        transfer.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_failed_verification_is_an_error() {
+        let mut server = Server::new_async().await;
+        let clerk = loaded_clerk(server.url(), environment(), ClientBuilder::new()).await;
+        let failed = sign_in(
+            Status::NeedsFirstFactor,
+            VerificationStatus::Failed,
+            &server,
+        );
+        reload_mock(&mut server, &failed, Client::default())
+            .create_async()
+            .await;
+
+        let error = authorize(&mut server, &clerk).await.unwrap_err();
+        assert!(matches!(error, Error::ExternalVerificationFailed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_start_checks_strategy_and_redirect_times_out() {
+        let mut server = Server::new_async().await;
+        let clerk = loaded_clerk(server.url(), environment(), ClientBuilder::new()).await;
+
+        let error = clerk.oauth("oauth_google").start().await.unwrap_err();
+        assert!(matches!(error, Error::StrategyNotEnabled(s) if s == "oauth_google"));
+
+        server
+            .mock("POST", "/v1/client/sign_ins?_is_native=1")
+            .with_body(response_body(
+                &sign_in(
+                    Status::NeedsFirstFactor,
+                    VerificationStatus::Unverified,
+                    &server,
+                ),
+                Client::default(),
+            ))
+            .create_async()
+            .await;
+        let pending = clerk
+            .oauth("oauth_github")
+            .timeout(Duration::from_millis(500))
+            .start()
+            .await
+            .unwrap();
+
+        // Other requests to the listener don't end the wait
+        let favicon = pending
+            .redirect_url()
+            .replace("/oauth-callback", "/favicon.ico");
+        let (outcome, page) = tokio::join!(pending.complete(), reqwest::get(favicon));
+        assert_eq!(page.unwrap().status(), reqwest::StatusCode::NOT_FOUND);
+        assert!(matches!(outcome, Err(Error::RedirectTimeout)));
+    }
+}
@@ -0,0 +1,526 @@
+//! Parameter structs for the [`crate::clerk_fapi::ClerkFapiClient`] endpoints
+//! that take many optional arguments
+//!
+//! Every struct implements `Default` and has a setter per field, plus
+//! constructors for the common strategies:
+//!
+//! ```
+//! use clerk_fapi_rs::params::CreateSignInParams;
+//!
+//! let params = CreateSignInParams::email_code("user@example.com");
+//! assert_eq!(params.strategy.as_deref(), Some("email_code"));
+//! ```
+
+use serde_json::Value as JsonValue;
+
+macro_rules! setters {
+    ($($field:ident),* $(,)?) => {$(
+        #[doc = concat!("Sets `", stringify!($field), "`")]
+        pub fn $field(mut self, $field: impl Into<String>) -> Self {
+            self.$field = Some($field.into());
+            self
+        }
+    )*};
+}
+
+/// Parameters of [`crate::clerk_fapi::ClerkFapiClient::create_sign_in_with`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CreateSignInParams {
+    pub strategy: Option<String>,
+    pub identifier: Option<String>,
+    pub password: Option<String>,
+    pub ticket: Option<String>,
+    pub redirect_url: Option<String>,
+    pub action_complete_redirect_url: Option<String>,
+    pub transfer: Option<bool>,
+    pub code: Option<String>,
+    pub token: Option<String>,
+}
+
+impl CreateSignInParams {
+    /// Starts a sign in with the given identifier, leaving the strategy to a later step
+    pub fn identifier(identifier: impl Into<String>) -> Self {
+        Self {
+            identifier: Some(identifier.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Signs in with an identifier and password in a single request
+    pub fn password(identifier: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            strategy: Some("password".to_string()),
+            identifier: Some(identifier.into()),
+            password: Some(password.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Starts a sign in that sends a one time code to the email address
+    pub fn email_code(email_address: impl Into<String>) -> Self {
+        Self {
+            strategy: Some("email_code".to_string()),
+            identifier: Some(email_address.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Starts a sign in that sends a one time code to the phone number
+    pub fn phone_code(phone_number: impl Into<String>) -> Self {
+        Self {
+            strategy: Some("phone_code".to_string()),
+            identifier: Some(phone_number.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Signs in with a sign in token or invitation ticket
+    pub fn ticket(ticket: impl Into<String>) -> Self {
+        Self {
+            strategy: Some("ticket".to_string()),
+            ticket: Some(ticket.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Starts an OAuth or SSO sign in that redirects to `redirect_url` when done
+    pub fn oauth(strategy: impl Into<String>, redirect_url: impl Into<String>) -> Self {
+        Self {
+            strategy: Some(strategy.into()),
+            redirect_url: Some(redirect_url.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Starts a sign in with a discoverable passkey, the user is identified
+    /// by the passkey they pick
+    pub fn passkey() -> Self {
+        Self {
+            strategy: Some("passkey".to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Turns a sign up for an existing account into a sign in
+    pub fn transfer() -> Self {
+        Self {
+            transfer: Some(true),
+            ..Default::default()
+        }
+    }
+
+    setters!(
+        strategy,
+        redirect_url,
+        action_complete_redirect_url,
+        code,
+        token,
+    );
+}
+
+/// Parameters of [`crate::clerk_fapi::ClerkFapiClient::prepare_sign_in_factor_one_with`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PrepareSignInFactorOneParams {
+    pub strategy: Option<String>,
+    pub email_address_id: Option<String>,
+    pub phone_number_id: Option<String>,
+    pub web3_wallet_id: Option<String>,
+    pub passkey_id: Option<String>,
+    pub redirect_url: Option<String>,
+    pub action_complete_redirect_url: Option<String>,
+}
+
+impl PrepareSignInFactorOneParams {
+    fn with_strategy(strategy: &str) -> Self {
+        Self {
+            strategy: Some(strategy.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Sends a one time code to the email address
+    pub fn email_code(email_address_id: impl Into<String>) -> Self {
+        Self::with_strategy("email_code").email_address_id(email_address_id)
+    }
+
+    /// Sends a one time code to the phone number
+    pub fn phone_code(phone_number_id: impl Into<String>) -> Self {
+        Self::with_strategy("phone_code").phone_number_id(phone_number_id)
+    }
+
+    /// Sends a magic link to the email address that redirects to `redirect_url`
+    pub fn email_link(
+        email_address_id: impl Into<String>,
+        redirect_url: impl Into<String>,
+    ) -> Self {
+        Self::with_strategy("email_link")
+            .email_address_id(email_address_id)
+            .redirect_url(redirect_url)
+    }
+
+    /// Sends a code to reset the password to the email address
+    pub fn reset_password_email_code(email_address_id: impl Into<String>) -> Self {
+        Self::with_strategy("reset_password_email_code").email_address_id(email_address_id)
+    }
+
+    /// Sends a code to reset the password to the phone number
+    pub fn reset_password_phone_code(phone_number_id: impl Into<String>) -> Self {
+        Self::with_strategy("reset_password_phone_code").phone_number_id(phone_number_id)
+    }
+
+    /// Requests a nonce to sign with the web3 wallet
+    pub fn web3_signature(strategy: impl Into<String>, web3_wallet_id: impl Into<String>) -> Self {
+        Self::default()
+            .strategy(strategy)
+            .web3_wallet_id(web3_wallet_id)
+    }
+
+    /// Requests a passkey challenge
+    pub fn passkey() -> Self {
+        Self::with_strategy("passkey")
+    }
+
+    /// Starts an OAuth or SSO flow that redirects to `redirect_url` when done
+    pub fn oauth(strategy: impl Into<String>, redirect_url: impl Into<String>) -> Self {
+        Self::default()
+            .strategy(strategy)
+            .redirect_url(redirect_url)
+    }
+
+    setters!(
+        strategy,
+        email_address_id,
+        phone_number_id,
+        web3_wallet_id,
+        passkey_id,
+        redirect_url,
+        action_complete_redirect_url,
+    );
+}
+
+/// Parameters of [`crate::clerk_fapi::ClerkFapiClient::attempt_sign_in_factor_one_with`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AttemptSignInFactorOneParams {
+    pub strategy: Option<String>,
+    pub code: Option<String>,
+    pub password: Option<String>,
+    pub signature: Option<String>,
+    pub redirect_url: Option<String>,
+    pub action_complete_redirect_url: Option<String>,
+    pub ticket: Option<String>,
+    pub public_key_credential: Option<String>,
+}
+
+impl AttemptSignInFactorOneParams {
+    /// Verifies a one time code sent with the given strategy
+    pub fn code(strategy: impl Into<String>, code: impl Into<String>) -> Self {
+        Self {
+            strategy: Some(strategy.into()),
+            code: Some(code.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Verifies a one time code sent to an email address
+    pub fn email_code(code: impl Into<String>) -> Self {
+        Self::code("email_code", code)
+    }
+
+    /// Verifies a one time code sent to a phone number
+    pub fn phone_code(code: impl Into<String>) -> Self {
+        Self::code("phone_code", code)
+    }
+
+    /// Verifies the password of the account
+    pub fn password(password: impl Into<String>) -> Self {
+        Self {
+            strategy: Some("password".to_string()),
+            password: Some(password.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Verifies a web3 signature
+    pub fn signature(strategy: impl Into<String>, signature: impl Into<String>) -> Self {
+        Self {
+            strategy: Some(strategy.into()),
+            signature: Some(signature.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Verifies a sign in token or invitation ticket
+    pub fn ticket(ticket: impl Into<String>) -> Self {
+        Self {
+            strategy: Some("ticket".to_string()),
+            ticket: Some(ticket.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Verifies the JSON encoded `PublicKeyCredential` of a passkey assertion
+    pub fn passkey(public_key_credential: impl Into<String>) -> Self {
+        Self {
+            strategy: Some("passkey".to_string()),
+            public_key_credential: Some(public_key_credential.into()),
+            ..Default::default()
+        }
+    }
+
+    setters!(strategy, redirect_url, action_complete_redirect_url,);
+}
+
+/// Parameters of [`crate::clerk_fapi::ClerkFapiClient::attempt_passkey_verification_with`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AttemptPasskeyVerificationParams {
+    pub strategy: Option<String>,
+    pub public_key_credential: Option<String>,
+}
+
+impl AttemptPasskeyVerificationParams {
+    /// Verifies the JSON encoded `PublicKeyCredential` the authenticator
+    /// created for a new passkey
+    pub fn passkey(public_key_credential: impl Into<String>) -> Self {
+        Self {
+            strategy: Some("passkey".to_string()),
+            public_key_credential: Some(public_key_credential.into()),
+        }
+    }
+
+    setters!(strategy);
+}
+
+/// Parameters of [`crate::clerk_fapi::ClerkFapiClient::create_sign_ups_with`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CreateSignUpParams {
+    pub transfer: Option<bool>,
+    pub password: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub username: Option<String>,
+    pub email_address: Option<String>,
+    pub phone_number: Option<String>,
+    pub email_address_or_phone_number: Option<String>,
+    pub unsafe_metadata: Option<JsonValue>,
+    pub strategy: Option<String>,
+    pub action_complete_redirect_url: Option<String>,
+    pub redirect_url: Option<String>,
+    pub ticket: Option<String>,
+    pub web3_wallet: Option<String>,
+    pub captcha_token: Option<String>,
+    pub captcha_error: Option<String>,
+    pub code: Option<String>,
+    pub token: Option<String>,
+}
+
+impl CreateSignUpParams {
+    /// Signs up with an invitation ticket
+    pub fn ticket(ticket: impl Into<String>) -> Self {
+        Self {
+            strategy: Some("ticket".to_string()),
+            ticket: Some(ticket.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Starts an OAuth or SSO sign up that redirects to `redirect_url` when done
+    pub fn oauth(strategy: impl Into<String>, redirect_url: impl Into<String>) -> Self {
+        Self::default()
+            .strategy(strategy)
+            .redirect_url(redirect_url)
+    }
+
+    /// Turns a sign in for a missing account into a sign up
+    pub fn transfer() -> Self {
+        Self {
+            transfer: Some(true),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the custom JSON object stored on the user once the sign up completes
+    pub fn unsafe_metadata(mut self, unsafe_metadata: JsonValue) -> Self {
+        self.unsafe_metadata = Some(unsafe_metadata);
+        self
+    }
+
+    setters!(
+        password,
+        first_name,
+        last_name,
+        username,
+        email_address,
+        phone_number,
+        email_address_or_phone_number,
+        strategy,
+        action_complete_redirect_url,
+        redirect_url,
+        web3_wallet,
+        captcha_token,
+        captcha_error,
+        code,
+        token,
+    );
+}
+
+/// Parameters of [`crate::clerk_fapi::ClerkFapiClient::update_sign_ups_with`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UpdateSignUpParams {
+    pub password: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub username: Option<String>,
+    pub email_address: Option<String>,
+    pub phone_number: Option<String>,
+    pub email_address_or_phone_number: Option<String>,
+    pub unsafe_metadata: Option<JsonValue>,
+    pub strategy: Option<String>,
+    pub redirect_url: Option<String>,
+    pub action_complete_redirect_url: Option<String>,
+    pub ticket: Option<String>,
+    pub web3_wallet: Option<String>,
+    pub code: Option<String>,
+    pub token: Option<String>,
+}
+
+impl UpdateSignUpParams {
+    /// Sets the custom JSON object stored on the user once the sign up completes
+    pub fn unsafe_metadata(mut self, unsafe_metadata: JsonValue) -> Self {
+        self.unsafe_metadata = Some(unsafe_metadata);
+        self
+    }
+
+    setters!(
+        password,
+        first_name,
+        last_name,
+        username,
+        email_address,
+        phone_number,
+        email_address_or_phone_number,
+        strategy,
+        redirect_url,
+        action_complete_redirect_url,
+        ticket,
+        web3_wallet,
+        code,
+        token,
+    );
+}
+
+/// Parameters of [`crate::clerk_fapi::ClerkFapiClient::patch_user_with`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PatchUserParams {
+    pub username: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub primary_email_address_id: Option<String>,
+    pub primary_phone_number_id: Option<String>,
+    pub primary_web3_wallet_id: Option<String>,
+    pub unsafe_metadata: Option<JsonValue>,
+}
+
+impl PatchUserParams {
+    /// Sets the custom JSON object stored on the user
+    pub fn unsafe_metadata(mut self, unsafe_metadata: JsonValue) -> Self {
+        self.unsafe_metadata = Some(unsafe_metadata);
+        self
+    }
+
+    setters!(
+        username,
+        first_name,
+        last_name,
+        primary_email_address_id,
+        primary_phone_number_id,
+        primary_web3_wallet_id,
+    );
+}
+
+/// Parameters of [`crate::clerk_fapi::ClerkFapiClient::post_o_auth_accounts_with`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PostOAuthAccountsParams {
+    pub strategy: Option<String>,
+    pub redirect_url: Option<String>,
+    pub action_complete_redirect_url: Option<String>,
+    pub code: Option<String>,
+    pub token: Option<String>,
+}
+
+impl PostOAuthAccountsParams {
+    /// Connects an OAuth account, redirecting to `redirect_url` when done
+    pub fn oauth(strategy: impl Into<String>, redirect_url: impl Into<String>) -> Self {
+        Self::default()
+            .strategy(strategy)
+            .redirect_url(redirect_url)
+    }
+
+    setters!(
+        strategy,
+        redirect_url,
+        action_complete_redirect_url,
+        code,
+        token,
+    );
+}
+
+/// Parameters of [`crate::clerk_fapi::ClerkFapiClient::create_organization_invitations_with`]
+/// and [`crate::clerk_fapi::ClerkFapiClient::create_organization_membership_with`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrganizationMemberParams {
+    pub user_id: Option<String>,
+    pub role: Option<String>,
+    pub email_address: Option<String>,
+    /// Role sent in the request body, the API accepts the role in either field
+    pub role2: Option<String>,
+}
+
+impl OrganizationMemberParams {
+    /// Targets the user with the given id
+    pub fn user(user_id: impl Into<String>, role: impl Into<String>) -> Self {
+        Self::default().user_id(user_id).role(role)
+    }
+
+    /// Targets the given email address
+    pub fn email(email_address: impl Into<String>, role: impl Into<String>) -> Self {
+        Self::default().email_address(email_address).role(role)
+    }
+
+    setters!(user_id, role, email_address, role2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constructors_set_strategy() {
+        let params = CreateSignInParams::password("user@example.com", "secret");
+        assert_eq!(params.strategy.as_deref(), Some("password"));
+        assert_eq!(params.identifier.as_deref(), Some("user@example.com"));
+        assert_eq!(params.password.as_deref(), Some("secret"));
+
+        let params = PrepareSignInFactorOneParams::email_link("idn_123", "https://example.com");
+        assert_eq!(params.strategy.as_deref(), Some("email_link"));
+        assert_eq!(params.email_address_id.as_deref(), Some("idn_123"));
+        assert_eq!(params.redirect_url.as_deref(), Some("https://example.com"));
+
+        let params = AttemptSignInFactorOneParams::email_code("424242");
+        assert_eq!(
+            params,
+            AttemptSignInFactorOneParams {
+                strategy: Some("email_code".to_string()),
+                code: Some("424242".to_string()),
+                ..Default::default()
+            }
+        );
+
+        let params = AttemptSignInFactorOneParams::passkey("{}");
+        assert_eq!(params.strategy.as_deref(), Some("passkey"));
+        assert_eq!(params.public_key_credential.as_deref(), Some("{}"));
+
+        let params = AttemptPasskeyVerificationParams::passkey("{}");
+        assert_eq!(params.strategy.as_deref(), Some("passkey"));
+        assert_eq!(params.public_key_credential.as_deref(), Some("{}"));
+    }
+}
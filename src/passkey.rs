@@ -0,0 +1,613 @@
+//! Passkey (WebAuthn) registration and sign in
+//!
+//! The Frontend API prepares a WebAuthn ceremony by returning its options as
+//! JSON in the `nonce` of the passkey verification. An [`Authenticator`]
+//! turns the options into a credential, which is sent back to verify the
+//! passkey or the sign in. Browsers use `navigator.credentials`, native apps
+//! implement [`Authenticator`] with the platform APIs or a security key
+//! library. The `testing` feature has a software authenticator.
+//!
+//! ```no_run
+//! # use clerk_fapi_rs::clerk::{Clerk, Error};
+//! # use clerk_fapi_rs::passkey::Authenticator;
+//! # use clerk_fapi_rs::sign_in::SignInState;
+//! # async fn example(clerk: Clerk, authenticator: &dyn Authenticator) -> Result<(), Error> {
+//! // Registers a passkey for the signed in user
+//! let passkey = clerk.create_passkey(authenticator).await?;
+//! println!("created passkey {}", passkey.id);
+//!
+//! // Later, signs in without an identifier, the user picks the passkey
+//! if let SignInState::Complete(complete) = clerk.sign_in().passkey(authenticator).await? {
+//!     println!("signed in: {}", complete.session_id());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::clerk::{Clerk, Error};
+use crate::models::client_passkey_verification::ClientPasskeyVerification;
+use crate::models::client_sign_in_first_factor_verification::ClientSignInFirstFactorVerification as Verification;
+use crate::models::{ClientPeriodPasskey as Passkey, ClientPeriodSignIn as SignIn};
+use crate::params::AttemptPasskeyVerificationParams;
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Deserializer};
+use std::error;
+use std::fmt;
+
+/// Creates and uses passkeys, the part of WebAuthn that browsers expose as
+/// `navigator.credentials`
+#[async_trait]
+pub trait Authenticator: Send + Sync {
+    /// Creates a new credential for the relying party and user of the options
+    async fn create(
+        &self,
+        options: &CreationOptions,
+    ) -> Result<RegistrationCredential, AuthenticatorError>;
+
+    /// Signs the challenge of the options with an existing credential
+    async fn get(
+        &self,
+        options: &RequestOptions,
+    ) -> Result<AssertionCredential, AuthenticatorError>;
+}
+
+/// The authenticator failed, or the user cancelled the ceremony
+#[derive(Debug)]
+pub struct AuthenticatorError(Box<dyn error::Error + Send + Sync>);
+
+impl AuthenticatorError {
+    /// Wraps the error of an authenticator
+    pub fn new(error: impl Into<Box<dyn error::Error + Send + Sync>>) -> Self {
+        Self(error.into())
+    }
+}
+
+impl fmt::Display for AuthenticatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl error::Error for AuthenticatorError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+/// `PublicKeyCredentialCreationOptions` of a new passkey
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationOptions {
+    /// The challenge the authenticator signs with the new credential
+    #[serde(deserialize_with = "base64url")]
+    pub challenge: Vec<u8>,
+    /// The relying party the credential is scoped to
+    pub rp: RelyingParty,
+    /// The account the credential is created for
+    pub user: UserEntity,
+    /// The accepted key types, COSE algorithm identifiers such as `-7` (ES256)
+    #[serde(default)]
+    pub pub_key_cred_params: Vec<CredentialParameters>,
+    /// Milliseconds the ceremony may take
+    pub timeout: Option<u64>,
+    /// Credentials the user already has, the authenticator shouldn't create
+    /// another one if it holds one of them
+    #[serde(default)]
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+    /// Requirements on the authenticator that creates the credential
+    pub authenticator_selection: Option<AuthenticatorSelection>,
+    /// The attestation conveyance preference, e.g. `none` or `direct`
+    pub attestation: Option<String>,
+}
+
+/// `PublicKeyCredentialRequestOptions` of a passkey sign in
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestOptions {
+    /// The challenge the authenticator signs with an existing credential
+    #[serde(deserialize_with = "base64url")]
+    pub challenge: Vec<u8>,
+    /// Milliseconds the ceremony may take
+    pub timeout: Option<u64>,
+    /// The relying party, the domain of the origin when not set
+    pub rp_id: Option<String>,
+    /// The credentials that can be used, any credential of the relying party
+    /// when empty
+    #[serde(default)]
+    pub allow_credentials: Vec<CredentialDescriptor>,
+    /// Whether the user has to be verified, `required`, `preferred` or `discouraged`
+    pub user_verification: Option<String>,
+}
+
+/// `PublicKeyCredentialRpEntity`, the relying party of a new credential
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct RelyingParty {
+    /// The domain of the relying party, the domain of the origin when not set
+    pub id: Option<String>,
+    /// The name of the relying party shown to the user
+    pub name: String,
+}
+
+/// `PublicKeyCredentialUserEntity`, the account a new credential is created for
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEntity {
+    /// The user handle, returned in assertions of discoverable credentials
+    #[serde(deserialize_with = "base64url")]
+    pub id: Vec<u8>,
+    /// The identifier of the account, e.g. the email address
+    pub name: String,
+    /// The name of the user shown by the authenticator
+    pub display_name: String,
+}
+
+/// `PublicKeyCredentialParameters`, a key type the relying party accepts
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct CredentialParameters {
+    /// The credential type, always `public-key`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The COSE algorithm identifier
+    pub alg: i64,
+}
+
+/// `PublicKeyCredentialDescriptor`, an existing credential of the user
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct CredentialDescriptor {
+    /// The credential type, always `public-key`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The credential ID
+    #[serde(deserialize_with = "base64url")]
+    pub id: Vec<u8>,
+    /// How the authenticator holding the credential can be reached, e.g. `internal` or `usb`
+    #[serde(default)]
+    pub transports: Vec<String>,
+}
+
+/// `AuthenticatorSelectionCriteria` of a new credential
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    /// `platform` or `cross-platform`, any authenticator when not set
+    pub authenticator_attachment: Option<String>,
+    /// Whether the credential has to be discoverable, `required`, `preferred` or `discouraged`
+    pub resident_key: Option<String>,
+    /// Legacy form of `resident_key`, true when it is `required`
+    pub require_resident_key: Option<bool>,
+    /// Whether the user has to be verified, `required`, `preferred` or `discouraged`
+    pub user_verification: Option<String>,
+}
+
+/// The response of [`Authenticator::create`], a `PublicKeyCredential` with an
+/// attestation
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegistrationCredential {
+    /// The ID of the new credential
+    pub id: Vec<u8>,
+    /// The `clientDataJSON` the authenticator signed
+    pub client_data_json: Vec<u8>,
+    /// The CBOR encoded attestation object with the public key
+    pub attestation_object: Vec<u8>,
+    /// How the authenticator can be reached, e.g. `internal` or `usb`
+    pub transports: Vec<String>,
+    /// `platform` or `cross-platform`
+    pub authenticator_attachment: Option<String>,
+}
+
+impl RegistrationCredential {
+    /// The credential as the JSON the Frontend API expects
+    pub fn to_json(&self) -> String {
+        let id = encode(&self.id);
+        serde_json::json!({
+            "id": id,
+            "rawId": id,
+            "type": "public-key",
+            "authenticatorAttachment": self.authenticator_attachment,
+            "response": {
+                "clientDataJSON": encode(&self.client_data_json),
+                "attestationObject": encode(&self.attestation_object),
+                "transports": self.transports,
+            },
+        })
+        .to_string()
+    }
+}
+
+/// The response of [`Authenticator::get`], a `PublicKeyCredential` with an
+/// assertion
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssertionCredential {
+    /// The ID of the credential that signed the challenge
+    pub id: Vec<u8>,
+    /// The `clientDataJSON` the authenticator signed
+    pub client_data_json: Vec<u8>,
+    /// The authenticator data the authenticator signed
+    pub authenticator_data: Vec<u8>,
+    /// The signature over the authenticator data and the hash of the client data
+    pub signature: Vec<u8>,
+    /// The user handle of a discoverable credential, see [`UserEntity::id`]
+    pub user_handle: Option<Vec<u8>>,
+    /// `platform` or `cross-platform`
+    pub authenticator_attachment: Option<String>,
+}
+
+impl AssertionCredential {
+    /// The credential as the JSON the Frontend API expects
+    pub fn to_json(&self) -> String {
+        let id = encode(&self.id);
+        serde_json::json!({
+            "id": id,
+            "rawId": id,
+            "type": "public-key",
+            "authenticatorAttachment": self.authenticator_attachment,
+            "response": {
+                "clientDataJSON": encode(&self.client_data_json),
+                "authenticatorData": encode(&self.authenticator_data),
+                "signature": encode(&self.signature),
+                "userHandle": self.user_handle.as_deref().map(encode),
+            },
+        })
+        .to_string()
+    }
+}
+
+impl CreationOptions {
+    /// Parses the options of a passkey that was just created and not verified yet
+    pub fn from_passkey(passkey: &Passkey) -> Result<Self, Error> {
+        let nonce = match passkey.verification.as_deref() {
+            Some(ClientPasskeyVerification::StubsPeriodVerificationPeriodPasskey(v)) => {
+                v.nonce.as_deref()
+            }
+            None => None,
+        };
+        parse_nonce(nonce)
+    }
+}
+
+impl RequestOptions {
+    /// Parses the options of a sign in that was prepared with the passkey strategy
+    pub fn from_sign_in(sign_in: &SignIn) -> Result<Self, Error> {
+        let nonce = match sign_in.first_factor_verification.as_deref() {
+            Some(Verification::StubsPeriodVerificationPeriodPasskey(v)) => v.nonce.as_deref(),
+            _ => None,
+        };
+        parse_nonce(nonce)
+    }
+}
+
+fn parse_nonce<T: serde::de::DeserializeOwned>(nonce: Option<&str>) -> Result<T, Error> {
+    let nonce = nonce.ok_or(Error::MissingPasskeyOptions)?;
+    Ok(serde_json::from_str(nonce)?)
+}
+
+/// Creates a passkey for the user of the active session and verifies it with
+/// the credential of the authenticator
+pub(crate) async fn create(
+    clerk: &Clerk,
+    authenticator: &dyn Authenticator,
+) -> Result<Passkey, Error> {
+    let session_id = clerk
+        .session()
+        .await
+        .and_then(|session| session.id)
+        .ok_or(Error::NoActiveSession)?;
+    let fapi = clerk.get_fapi_client();
+
+    let passkey = *fapi.post_passkey(Some(&session_id)).await?.response;
+    let options = CreationOptions::from_passkey(&passkey)?;
+    let credential = authenticator.create(&options).await?;
+    let response = fapi
+        .attempt_passkey_verification_with(
+            &passkey.id,
+            AttemptPasskeyVerificationParams::passkey(credential.to_json()),
+        )
+        .await?;
+    Ok(*response.response)
+}
+
+/// Signs the challenge of a sign in prepared with the passkey strategy,
+/// returns the credential as JSON
+pub(crate) async fn assert(
+    sign_in: &SignIn,
+    authenticator: &dyn Authenticator,
+) -> Result<String, Error> {
+    let options = RequestOptions::from_sign_in(sign_in)?;
+    Ok(authenticator.get(&options).await?.to_json())
+}
+
+fn encode(bytes: &[u8]) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// WebAuthn encodes binary values as unpadded base64url, padding is accepted
+fn base64url<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    general_purpose::URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::client_period_sign_in::Status;
+    use crate::models::stubs_period_sign_in_factor::Strategy;
+    use crate::models::{
+        ClientPeriodClient as Client, StubsPeriodSignInFactor as SignInFactor,
+        StubsPeriodVerificationPeriodPasskey as PasskeyVerification,
+    };
+    use crate::sign_in::SignInState;
+    use crate::testing::fixtures::{
+        loaded_clerk, response_body, signed_in_client, ClientBuilder, EnvironmentBuilder,
+    };
+    use crate::testing::SoftwareAuthenticator;
+    use mockito::{Matcher, Server};
+
+    const ORIGIN: &str = "https://example.com";
+
+    fn creation_nonce() -> String {
+        serde_json::json!({
+            "challenge": encode(b"registration challenge"),
+            "rp": { "id": "example.com", "name": "Example" },
+            "user": { "id": encode(b"user_123"), "name": "ada@example.com", "displayName": "Ada" },
+            "pubKeyCredParams": [{ "type": "public-key", "alg": -7 }, { "type": "public-key", "alg": -257 }],
+            "timeout": 60000,
+            "excludeCredentials": [],
+            "authenticatorSelection": {
+                "authenticatorAttachment": "platform",
+                "residentKey": "required",
+                "requireResidentKey": true,
+                "userVerification": "required",
+            },
+            "attestation": "none",
+        })
+        .to_string()
+    }
+
+    fn request_nonce() -> String {
+        serde_json::json!({
+            "challenge": encode(b"sign in challenge"),
+            "timeout": 60000,
+            "rpId": "example.com",
+            "allowCredentials": [],
+            "userVerification": "required",
+        })
+        .to_string()
+    }
+
+    fn passkey(nonce: Option<String>) -> Passkey {
+        let verification = PasskeyVerification {
+            nonce,
+            ..Default::default()
+        };
+        Passkey {
+            id: "pk_123".to_string(),
+            name: "Passkey".to_string(),
+            verification: Some(Box::new(
+                ClientPasskeyVerification::StubsPeriodVerificationPeriodPasskey(Box::new(
+                    verification,
+                )),
+            )),
+            ..Default::default()
+        }
+    }
+
+    fn prepared_sign_in(status: Status) -> SignIn {
+        let verification = PasskeyVerification {
+            nonce: Some(request_nonce()),
+            expire_at: 1_704_067_200_000,
+            ..Default::default()
+        };
+        SignIn {
+            id: "sia_123".to_string(),
+            status,
+            supported_first_factors: Some(vec![SignInFactor::new(Strategy::Passkey)]),
+            first_factor_verification: Some(Box::new(
+                Verification::StubsPeriodVerificationPeriodPasskey(Box::new(verification)),
+            )),
+            created_session_id: (status == Status::Complete).then(|| "sess_123".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_options_are_parsed_from_nonce() {
+        let options = CreationOptions::from_passkey(&passkey(Some(creation_nonce()))).unwrap();
+        assert_eq!(options.challenge, b"registration challenge");
+        assert_eq!(options.rp.id.as_deref(), Some("example.com"));
+        assert_eq!(options.user.id, b"user_123");
+        assert_eq!(options.pub_key_cred_params[0].alg, -7);
+        assert_eq!(
+            options
+                .authenticator_selection
+                .and_then(|s| s.user_verification)
+                .as_deref(),
+            Some("required")
+        );
+
+        let options =
+            RequestOptions::from_sign_in(&prepared_sign_in(Status::NeedsFirstFactor)).unwrap();
+        assert_eq!(options.challenge, b"sign in challenge");
+        assert_eq!(options.rp_id.as_deref(), Some("example.com"));
+        assert!(options.allow_credentials.is_empty());
+
+        let error = CreationOptions::from_passkey(&passkey(None)).unwrap_err();
+        assert!(matches!(error, Error::MissingPasskeyOptions));
+    }
+
+    #[tokio::test]
+    async fn test_create_passkey() {
+        let mut server = Server::new_async().await;
+        let clerk = loaded_clerk(server.url(), EnvironmentBuilder::new(), signed_in_client()).await;
+        let create = server
+            .mock("POST", "/v1/me/passkeys")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("_clerk_session_id".to_string(), "sess_123".to_string()),
+                Matcher::UrlEncoded("_is_native".to_string(), "1".to_string()),
+            ]))
+            .with_body(response_body(
+                &passkey(Some(creation_nonce())),
+                signed_in_client(),
+            ))
+            .create_async()
+            .await;
+        let verify = server
+            .mock(
+                "POST",
+                "/v1/me/passkeys/pk_123/attempt_verification?_is_native=1",
+            )
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("strategy".to_string(), "passkey".to_string()),
+                Matcher::Regex("public_key_credential=%7B".to_string()),
+                Matcher::Regex("attestationObject".to_string()),
+            ]))
+            .with_body(response_body(&passkey(None), signed_in_client()))
+            .create_async()
+            .await;
+
+        let authenticator = SoftwareAuthenticator::new(ORIGIN);
+        let created = clerk.create_passkey(&authenticator).await.unwrap();
+
+        assert_eq!(created.id, "pk_123");
+        assert_eq!(authenticator.credential_ids().len(), 1);
+        create.assert_async().await;
+        verify.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_create_passkey_needs_session() {
+        let server = Server::new_async().await;
+        let clerk = loaded_clerk(
+            server.url(),
+            EnvironmentBuilder::new(),
+            ClientBuilder::new(),
+        )
+        .await;
+
+        let error = clerk
+            .create_passkey(&SoftwareAuthenticator::new(ORIGIN))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::NoActiveSession));
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_with_passkey_first_factor() {
+        let mut server = Server::new_async().await;
+        let clerk = loaded_clerk(
+            server.url(),
+            EnvironmentBuilder::new(),
+            ClientBuilder::new(),
+        )
+        .await;
+        let authenticator = SoftwareAuthenticator::new(ORIGIN);
+        let registration = CreationOptions::from_passkey(&passkey(Some(creation_nonce()))).unwrap();
+        authenticator.create(&registration).await.unwrap();
+
+        let identified = SignIn {
+            first_factor_verification: None,
+            ..prepared_sign_in(Status::NeedsFirstFactor)
+        };
+        server
+            .mock("POST", "/v1/client/sign_ins?_is_native=1")
+            .with_body(response_body(&identified, Client::default()))
+            .create_async()
+            .await;
+        let prepare = server
+            .mock(
+                "POST",
+                "/v1/client/sign_ins/sia_123/prepare_first_factor?_is_native=1",
+            )
+            .match_body(Matcher::UrlEncoded(
+                "strategy".to_string(),
+                "passkey".to_string(),
+            ))
+            .with_body(response_body(
+                &prepared_sign_in(Status::NeedsFirstFactor),
+                Client::default(),
+            ))
+            .create_async()
+            .await;
+        let attempt = server
+            .mock(
+                "POST",
+                "/v1/client/sign_ins/sia_123/attempt_first_factor?_is_native=1",
+            )
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("strategy".to_string(), "passkey".to_string()),
+                Matcher::Regex("authenticatorData".to_string()),
+            ]))
+            .with_body(response_body(
+                &prepared_sign_in(Status::Complete),
+                signed_in_client(),
+            ))
+            .create_async()
+            .await;
+
+        let SignInState::NeedsFirstFactor(needs_factor) =
+            clerk.sign_in().identify("ada@example.com").await.unwrap()
+        else {
+            panic!("expected the sign in to need a first factor");
+        };
+        let state = needs_factor.attempt_passkey(&authenticator).await.unwrap();
+
+        assert!(matches!(state, SignInState::Complete(_)));
+        assert_eq!(
+            clerk.session().await.and_then(|s| s.id).as_deref(),
+            Some("sess_123")
+        );
+        prepare.assert_async().await;
+        attempt.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_with_discoverable_passkey() {
+        let mut server = Server::new_async().await;
+        let clerk = loaded_clerk(
+            server.url(),
+            EnvironmentBuilder::new(),
+            ClientBuilder::new(),
+        )
+        .await;
+        let authenticator = SoftwareAuthenticator::new(ORIGIN);
+        let registration = CreationOptions::from_passkey(&passkey(Some(creation_nonce()))).unwrap();
+        authenticator.create(&registration).await.unwrap();
+
+        let create = server
+            .mock("POST", "/v1/client/sign_ins?_is_native=1")
+            .match_body(Matcher::UrlEncoded(
+                "strategy".to_string(),
+                "passkey".to_string(),
+            ))
+            .with_body(response_body(
+                &prepared_sign_in(Status::NeedsFirstFactor),
+                Client::default(),
+            ))
+            .create_async()
+            .await;
+        let attempt = server
+            .mock(
+                "POST",
+                "/v1/client/sign_ins/sia_123/attempt_first_factor?_is_native=1",
+            )
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("strategy".to_string(), "passkey".to_string()),
+                // The user handle identifies the user of a discoverable credential
+                Matcher::Regex(format!("userHandle%22%3A%22{}", encode(b"user_123"))),
+            ]))
+            .with_body(response_body(
+                &prepared_sign_in(Status::Complete),
+                signed_in_client(),
+            ))
+            .create_async()
+            .await;
+
+        let state = clerk.sign_in().passkey(&authenticator).await.unwrap();
+
+        assert!(matches!(state, SignInState::Complete(_)));
+        create.assert_async().await;
+        attempt.assert_async().await;
+    }
+}
@@ -0,0 +1,203 @@
+//! Typed Service Provider metadata, parsed from the XML document returned by
+//! [`crate::clerk_fapi::ClerkFapiClient::saml_metadata`]
+//!
+//! [`crate::clerk::Clerk::saml_metadata`] fetches and parses it in one call.
+
+use roxmltree::{Document, Node};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const METADATA_NS: &str = "urn:oasis:names:tc:SAML:2.0:metadata";
+const XMLDSIG_NS: &str = "http://www.w3.org/2000/09/xmldsig#";
+
+/// The SAML metadata of the Service Provider side of a SAML connection, as
+/// configured in the Identity Provider
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SamlMetadata {
+    /// The entity ID of the Service Provider
+    pub entity_id: String,
+    /// The endpoints the Identity Provider posts SAML Responses to
+    pub assertion_consumer_services: Vec<AssertionConsumerService>,
+    /// The accepted name ID formats, e.g.
+    /// `urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress`
+    pub name_id_formats: Vec<String>,
+    /// Base64 encoded DER X.509 certificates the Service Provider signs with
+    pub signing_certificates: Vec<String>,
+    /// Whether the Service Provider signs its authentication requests
+    pub authn_requests_signed: Option<bool>,
+    /// Whether the Service Provider requires the Identity Provider to sign assertions
+    pub want_assertions_signed: Option<bool>,
+}
+
+/// An `AssertionConsumerService` endpoint of the Service Provider
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AssertionConsumerService {
+    /// The SAML binding, e.g. `urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST`
+    pub binding: String,
+    /// The URL the Identity Provider posts SAML Responses to
+    pub location: String,
+    /// The index the Identity Provider can refer to the endpoint with
+    pub index: Option<u32>,
+    /// Whether this is the default endpoint when there are several
+    pub is_default: Option<bool>,
+}
+
+impl SamlMetadata {
+    /// Parses an `EntityDescriptor` with an `SPSSODescriptor`
+    pub fn from_xml(xml: &str) -> Result<Self, ParseSamlMetadataError> {
+        let document = Document::parse(xml).map_err(|e| ParseSamlMetadataError(e.to_string()))?;
+        let entity = document.root_element();
+        if !entity.has_tag_name((METADATA_NS, "EntityDescriptor")) {
+            return Err(ParseSamlMetadataError(format!(
+                "expected an EntityDescriptor, found {}",
+                entity.tag_name().name()
+            )));
+        }
+        let entity_id = entity
+            .attribute("entityID")
+            .ok_or_else(|| ParseSamlMetadataError("missing entityID".to_string()))?;
+        let descriptor = children(entity, METADATA_NS, "SPSSODescriptor")
+            .next()
+            .ok_or_else(|| ParseSamlMetadataError("missing SPSSODescriptor".to_string()))?;
+
+        let assertion_consumer_services =
+            children(descriptor, METADATA_NS, "AssertionConsumerService")
+                .map(|service| {
+                    Ok(AssertionConsumerService {
+                        binding: required_attribute(service, "Binding")?,
+                        location: required_attribute(service, "Location")?,
+                        index: service.attribute("index").and_then(|i| i.parse().ok()),
+                        is_default: bool_attribute(service, "isDefault"),
+                    })
+                })
+                .collect::<Result<_, ParseSamlMetadataError>>()?;
+
+        let name_id_formats = children(descriptor, METADATA_NS, "NameIDFormat")
+            .filter_map(|format| format.text())
+            .map(|format| format.trim().to_string())
+            .collect();
+
+        // A key descriptor without `use` is used both for signing and encryption
+        let signing_certificates = children(descriptor, METADATA_NS, "KeyDescriptor")
+            .filter(|key| matches!(key.attribute("use"), None | Some("signing")))
+            .flat_map(|key| key.descendants())
+            .filter(|node| node.has_tag_name((XMLDSIG_NS, "X509Certificate")))
+            .filter_map(|certificate| certificate.text())
+            .map(|certificate| certificate.split_whitespace().collect())
+            .collect();
+
+        Ok(SamlMetadata {
+            entity_id: entity_id.to_string(),
+            assertion_consumer_services,
+            name_id_formats,
+            signing_certificates,
+            authn_requests_signed: bool_attribute(descriptor, "AuthnRequestsSigned"),
+            want_assertions_signed: bool_attribute(descriptor, "WantAssertionsSigned"),
+        })
+    }
+}
+
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    namespace: &'a str,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.has_tag_name((namespace, name)))
+}
+
+fn required_attribute(node: Node, name: &str) -> Result<String, ParseSamlMetadataError> {
+    node.attribute(name).map(str::to_string).ok_or_else(|| {
+        ParseSamlMetadataError(format!("missing {} of {}", name, node.tag_name().name()))
+    })
+}
+
+fn bool_attribute(node: Node, name: &str) -> Option<bool> {
+    match node.attribute(name)? {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// The SAML metadata wasn't a valid Service Provider `EntityDescriptor`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseSamlMetadataError(String);
+
+impl fmt::Display for ParseSamlMetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid SAML metadata: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseSamlMetadataError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<md:EntityDescriptor xmlns:md="urn:oasis:names:tc:SAML:2.0:metadata" entityID="https://clerk.example.com/v1/saml/metadata/samlc_123">
+  <md:SPSSODescriptor AuthnRequestsSigned="false" WantAssertionsSigned="true" protocolSupportEnumeration="urn:oasis:names:tc:SAML:2.0:protocol">
+    <md:KeyDescriptor use="signing">
+      <ds:KeyInfo xmlns:ds="http://www.w3.org/2000/09/xmldsig#">
+        <ds:X509Data>
+          <ds:X509Certificate>
+            MIIBszCCAVmgAwIBAgIU
+            c2lnbmluZw==
+          </ds:X509Certificate>
+        </ds:X509Data>
+      </ds:KeyInfo>
+    </md:KeyDescriptor>
+    <md:KeyDescriptor use="encryption">
+      <ds:KeyInfo xmlns:ds="http://www.w3.org/2000/09/xmldsig#">
+        <ds:X509Data><ds:X509Certificate>ZW5jcnlwdGlvbg==</ds:X509Certificate></ds:X509Data>
+      </ds:KeyInfo>
+    </md:KeyDescriptor>
+    <md:NameIDFormat>urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress</md:NameIDFormat>
+    <md:AssertionConsumerService Binding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST" Location="https://clerk.example.com/v1/saml/acs/samlc_123" index="1" isDefault="true"/>
+  </md:SPSSODescriptor>
+</md:EntityDescriptor>"#;
+
+    #[test]
+    fn test_from_xml() {
+        let metadata = SamlMetadata::from_xml(METADATA).unwrap();
+
+        assert_eq!(
+            metadata.entity_id,
+            "https://clerk.example.com/v1/saml/metadata/samlc_123"
+        );
+        assert_eq!(
+            metadata.assertion_consumer_services,
+            vec![AssertionConsumerService {
+                binding: "urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST".to_string(),
+                location: "https://clerk.example.com/v1/saml/acs/samlc_123".to_string(),
+                index: Some(1),
+                is_default: Some(true),
+            }]
+        );
+        assert_eq!(
+            metadata.name_id_formats,
+            vec!["urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress"]
+        );
+        assert_eq!(
+            metadata.signing_certificates,
+            vec!["MIIBszCCAVmgAwIBAgIUc2lnbmluZw=="]
+        );
+        assert_eq!(metadata.authn_requests_signed, Some(false));
+        assert_eq!(metadata.want_assertions_signed, Some(true));
+    }
+
+    #[test]
+    fn test_from_xml_errors() {
+        assert!(SamlMetadata::from_xml("not xml").is_err());
+        let error = SamlMetadata::from_xml(
+            r#"<EntityDescriptor xmlns="urn:oasis:names:tc:SAML:2.0:metadata" entityID="sp"/>"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid SAML metadata: missing SPSSODescriptor"
+        );
+    }
+}
@@ -0,0 +1,875 @@
+//! Typed sign in flow
+//!
+//! [`Clerk::sign_in`] starts a [`SignInFlow`]. Every step returns a
+//! [`SignInState`] that only offers the calls valid for the current status of
+//! the sign in, and a completed sign in activates its session on the
+//! [`Clerk`] instance.
+//!
+//! ```no_run
+//! # use clerk_fapi_rs::clerk::{Clerk, Error};
+//! # use clerk_fapi_rs::sign_in::{FirstFactor, SignInState};
+//! # async fn example(clerk: Clerk) -> Result<(), Error> {
+//! let state = clerk.sign_in().identify("user@example.com").await?;
+//! let SignInState::NeedsFirstFactor(needs_factor) = state else {
+//!     unimplemented!()
+//! };
+//! let code = needs_factor
+//!     .factors()
+//!     .into_iter()
+//!     .find_map(|factor| match factor {
+//!         FirstFactor::Code(code) => Some(code),
+//!         _ => None,
+//!     })
+//!     .expect("email or phone code is enabled");
+//!
+//! let pending = needs_factor.prepare_code(&code).await?;
+//! match pending.attempt("123456").await? {
+//!     SignInState::Complete(complete) => println!("signed in: {}", complete.session_id()),
+//!     other => println!("more steps needed: {:?}", other.status()),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::clerk::{Clerk, Error};
+use crate::enterprise_sso::{enterprise_sso_strategy, EnterpriseSsoFlow};
+use crate::models::client_period_sign_in::Status;
+use crate::models::stubs_period_sign_in_factor::Strategy;
+use crate::models::{ClientPeriodSignIn as SignIn, StubsPeriodSignInFactor as SignInFactor};
+use crate::params::{
+    AttemptSignInFactorOneParams, CreateSignInParams, PrepareSignInFactorOneParams,
+};
+use crate::passkey::{self, Authenticator};
+use std::fmt;
+
+/// A one time code sent to an email address or phone number
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeFactor {
+    /// Code sent to the email address to sign in
+    EmailCode {
+        email_address_id: String,
+        safe_identifier: Option<String>,
+    },
+    /// Code sent to the phone number to sign in
+    PhoneCode {
+        phone_number_id: String,
+        safe_identifier: Option<String>,
+    },
+    /// Code sent to the email address to reset a forgotten password
+    ResetPasswordEmailCode {
+        email_address_id: String,
+        safe_identifier: Option<String>,
+    },
+    /// Code sent to the phone number to reset a forgotten password
+    ResetPasswordPhoneCode {
+        phone_number_id: String,
+        safe_identifier: Option<String>,
+    },
+}
+
+impl CodeFactor {
+    fn strategy(&self) -> &'static str {
+        match self {
+            CodeFactor::EmailCode { .. } => "email_code",
+            CodeFactor::PhoneCode { .. } => "phone_code",
+            CodeFactor::ResetPasswordEmailCode { .. } => "reset_password_email_code",
+            CodeFactor::ResetPasswordPhoneCode { .. } => "reset_password_phone_code",
+        }
+    }
+
+    /// The masked email address or phone number the code is sent to
+    pub fn safe_identifier(&self) -> Option<&str> {
+        match self {
+            CodeFactor::EmailCode {
+                safe_identifier, ..
+            }
+            | CodeFactor::PhoneCode {
+                safe_identifier, ..
+            }
+            | CodeFactor::ResetPasswordEmailCode {
+                safe_identifier, ..
+            }
+            | CodeFactor::ResetPasswordPhoneCode {
+                safe_identifier, ..
+            } => safe_identifier.as_deref(),
+        }
+    }
+}
+
+/// A magic link sent to an email address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailLinkFactor {
+    pub email_address_id: String,
+    /// The masked email address the link is sent to
+    pub safe_identifier: Option<String>,
+}
+
+/// A one time code sent to a phone number to verify the second step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhoneCodeFactor {
+    pub phone_number_id: String,
+    /// The masked phone number the code is sent to
+    pub safe_identifier: Option<String>,
+}
+
+/// A factor that can be used to verify the first step of a sign in
+#[derive(Debug, Clone, PartialEq)]
+pub enum FirstFactor {
+    /// The password of the account
+    Password,
+    /// A one time code, see [`NeedsFirstFactor::prepare_code`]
+    Code(CodeFactor),
+    /// A magic link sent to the email address, see [`NeedsFirstFactor::prepare_email_link`]
+    EmailLink(EmailLinkFactor),
+    /// A passkey registered for the account, see [`NeedsFirstFactor::attempt_passkey`]
+    Passkey,
+    /// The enterprise connection of the email domain, see
+    /// [`NeedsFirstFactor::enterprise_sso`]
+    EnterpriseSso,
+    /// A factor this flow has no typed support for, such as OAuth or web3
+    /// signatures
+    Other(SignInFactor),
+}
+
+impl FirstFactor {
+    fn from_factor(factor: &SignInFactor) -> Self {
+        let email = || factor.email_address_id.clone();
+        let phone = || factor.phone_number_id.clone();
+        let safe_identifier = factor.safe_identifier.clone();
+
+        let typed = match factor.strategy {
+            Strategy::Password => Some(FirstFactor::Password),
+            Strategy::Passkey => Some(FirstFactor::Passkey),
+            Strategy::EnterpriseSso | Strategy::Saml => Some(FirstFactor::EnterpriseSso),
+            Strategy::EmailCode => email().map(|email_address_id| {
+                FirstFactor::Code(CodeFactor::EmailCode {
+                    email_address_id,
+                    safe_identifier,
+                })
+            }),
+            Strategy::PhoneCode => phone().map(|phone_number_id| {
+                FirstFactor::Code(CodeFactor::PhoneCode {
+                    phone_number_id,
+                    safe_identifier,
+                })
+            }),
+            Strategy::ResetPasswordEmailCode => email().map(|email_address_id| {
+                FirstFactor::Code(CodeFactor::ResetPasswordEmailCode {
+                    email_address_id,
+                    safe_identifier,
+                })
+            }),
+            Strategy::ResetPasswordPhoneCode => phone().map(|phone_number_id| {
+                FirstFactor::Code(CodeFactor::ResetPasswordPhoneCode {
+                    phone_number_id,
+                    safe_identifier,
+                })
+            }),
+            Strategy::EmailLink => email().map(|email_address_id| {
+                FirstFactor::EmailLink(EmailLinkFactor {
+                    email_address_id,
+                    safe_identifier,
+                })
+            }),
+            _ => None,
+        };
+
+        typed.unwrap_or_else(|| FirstFactor::Other(factor.clone()))
+    }
+}
+
+/// A factor that can be used to verify the second step of a sign in
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecondFactor {
+    /// A one time code sent to the phone number, see [`NeedsSecondFactor::prepare_phone_code`]
+    PhoneCode(PhoneCodeFactor),
+    /// A code from an authenticator app, see [`NeedsSecondFactor::attempt_totp`]
+    Totp,
+    /// One of the backup codes of the account, see [`NeedsSecondFactor::attempt_backup_code`]
+    BackupCode,
+    /// A factor this flow has no typed support for
+    Other(SignInFactor),
+}
+
+impl SecondFactor {
+    fn from_factor(factor: &SignInFactor) -> Self {
+        match (factor.strategy, &factor.phone_number_id) {
+            (Strategy::PhoneCode, Some(phone_number_id)) => {
+                SecondFactor::PhoneCode(PhoneCodeFactor {
+                    phone_number_id: phone_number_id.clone(),
+                    safe_identifier: factor.safe_identifier.clone(),
+                })
+            }
+            (Strategy::Totp, _) => SecondFactor::Totp,
+            (Strategy::BackupCode, _) => SecondFactor::BackupCode,
+            _ => SecondFactor::Other(factor.clone()),
+        }
+    }
+}
+
+/// Entry point of a sign in, created with [`Clerk::sign_in`]
+#[derive(Clone)]
+pub struct SignInFlow {
+    clerk: Clerk,
+}
+
+impl SignInFlow {
+    pub(crate) fn new(clerk: Clerk) -> Self {
+        Self { clerk }
+    }
+
+    /// Starts a sign in without an identifier
+    pub async fn start(self) -> Result<SignInState, Error> {
+        let response = self
+            .clerk
+            .get_fapi_client()
+            .create_sign_in_with(CreateSignInParams::default())
+            .await?;
+        SignInState::from_sign_in(self.clerk, *response.response).await
+    }
+
+    /// Starts a sign in for the given email address, phone number or username
+    pub async fn identify(self, identifier: &str) -> Result<SignInState, Error> {
+        create_with_identifier(self.clerk, identifier).await
+    }
+
+    /// Signs in with an identifier and password in a single request
+    pub async fn password(self, identifier: &str, password: &str) -> Result<SignInState, Error> {
+        let response = self
+            .clerk
+            .get_fapi_client()
+            .create_sign_in_with(CreateSignInParams::password(identifier, password))
+            .await?;
+        SignInState::from_sign_in(self.clerk, *response.response).await
+    }
+
+    /// Signs in with a discoverable passkey, the user is identified by the
+    /// passkey they pick in the authenticator
+    pub async fn passkey(self, authenticator: &dyn Authenticator) -> Result<SignInState, Error> {
+        let response = self
+            .clerk
+            .get_fapi_client()
+            .create_sign_in_with(CreateSignInParams::passkey())
+            .await?;
+        attempt_passkey(self.clerk, &response.response, authenticator).await
+    }
+
+    /// Signs in with a sign in token or invitation ticket
+    pub async fn ticket(self, ticket: &str) -> Result<SignInState, Error> {
+        let response = self
+            .clerk
+            .get_fapi_client()
+            .create_sign_in_with(CreateSignInParams::ticket(ticket))
+            .await?;
+        SignInState::from_sign_in(self.clerk, *response.response).await
+    }
+
+    /// Continues an existing sign in, for example after an email link was
+    /// opened in another process
+    pub async fn resume(self, sign_in_id: &str) -> Result<SignInState, Error> {
+        let response = self.clerk.get_fapi_client().get_sign_in(sign_in_id).await?;
+        SignInState::from_sign_in(self.clerk, *response.response).await
+    }
+}
+
+async fn attempt_passkey(
+    clerk: Clerk,
+    sign_in: &SignIn,
+    authenticator: &dyn Authenticator,
+) -> Result<SignInState, Error> {
+    let credential = passkey::assert(sign_in, authenticator).await?;
+    let response = clerk
+        .get_fapi_client()
+        .attempt_sign_in_factor_one_with(
+            &sign_in.id,
+            AttemptSignInFactorOneParams::passkey(credential),
+        )
+        .await?;
+    SignInState::from_sign_in(clerk, *response.response).await
+}
+
+async fn create_with_identifier(clerk: Clerk, identifier: &str) -> Result<SignInState, Error> {
+    let response = clerk
+        .get_fapi_client()
+        .create_sign_in_with(CreateSignInParams::identifier(identifier))
+        .await?;
+    SignInState::from_sign_in(clerk, *response.response).await
+}
+
+/// The state of a sign in after the last request
+pub enum SignInState {
+    /// The user still has to say who they are, e.g. after [`SignInFlow::start`]
+    NeedsIdentifier(NeedsIdentifier),
+    /// The account is known and one of its first factors has to be verified
+    NeedsFirstFactor(NeedsFirstFactor),
+    /// The first factor was verified and the account requires a second one
+    NeedsSecondFactor(NeedsSecondFactor),
+    /// A reset password code was verified and a new password has to be set
+    NeedsNewPassword(NeedsNewPassword),
+    /// The sign in created a session, which is now the active session
+    Complete(Complete),
+}
+
+impl SignInState {
+    /// Moves the sign in to the state matching its status, activating the
+    /// created session once it is complete
+    pub(crate) async fn from_sign_in(clerk: Clerk, sign_in: SignIn) -> Result<Self, Error> {
+        let step = SignInStep { clerk, sign_in };
+        Ok(match step.sign_in.status {
+            Status::NeedsIdentifier => SignInState::NeedsIdentifier(NeedsIdentifier(step)),
+            Status::NeedsFirstFactor => SignInState::NeedsFirstFactor(NeedsFirstFactor(step)),
+            Status::NeedsSecondFactor => SignInState::NeedsSecondFactor(NeedsSecondFactor(step)),
+            Status::NeedsNewPassword => SignInState::NeedsNewPassword(NeedsNewPassword(step)),
+            Status::Complete => {
+                let session_id = step.sign_in.created_session_id.clone().ok_or_else(|| {
+                    Error::UnexpectedResponse("complete sign in has no created session".to_string())
+                })?;
+                step.clerk.activate_session(session_id).await?;
+                SignInState::Complete(Complete(step))
+            }
+            Status::Abandoned => return Err(Error::SignInAbandoned(step.sign_in.id)),
+        })
+    }
+
+    /// The sign in as returned by the last request
+    pub fn sign_in(&self) -> &SignIn {
+        match self {
+            SignInState::NeedsIdentifier(s) => s.sign_in(),
+            SignInState::NeedsFirstFactor(s) => s.sign_in(),
+            SignInState::NeedsSecondFactor(s) => s.sign_in(),
+            SignInState::NeedsNewPassword(s) => s.sign_in(),
+            SignInState::Complete(s) => s.sign_in(),
+        }
+    }
+
+    /// The status of the sign in
+    pub fn status(&self) -> Status {
+        self.sign_in().status
+    }
+}
+
+impl fmt::Debug for SignInState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignInState")
+            .field("id", &self.sign_in().id)
+            .field("status", &self.status())
+            .finish()
+    }
+}
+
+/// Shared data of every state
+#[derive(Clone)]
+struct SignInStep {
+    clerk: Clerk,
+    sign_in: SignIn,
+}
+
+impl SignInStep {
+    async fn reload(self) -> Result<SignInState, Error> {
+        SignInFlow::new(self.clerk).resume(&self.sign_in.id).await
+    }
+}
+
+macro_rules! sign_in_state {
+    ($($state:ident),*) => {$(
+        impl $state {
+            /// The sign in as returned by the last request
+            pub fn sign_in(&self) -> &SignIn {
+                &self.0.sign_in
+            }
+
+            /// Fetches the latest state of the sign in
+            pub async fn reload(self) -> Result<SignInState, Error> {
+                self.0.reload().await
+            }
+        }
+    )*};
+}
+
+sign_in_state!(
+    NeedsIdentifier,
+    NeedsFirstFactor,
+    PendingFirstFactor,
+    NeedsSecondFactor,
+    PendingSecondFactor,
+    NeedsNewPassword,
+    Complete
+);
+
+/// The sign in was started without an identifier
+pub struct NeedsIdentifier(SignInStep);
+
+impl NeedsIdentifier {
+    /// Continues with the given email address, phone number or username
+    pub async fn identify(self, identifier: &str) -> Result<SignInState, Error> {
+        create_with_identifier(self.0.clerk, identifier).await
+    }
+}
+
+/// The user was identified and has to verify a first factor
+pub struct NeedsFirstFactor(SignInStep);
+
+impl NeedsFirstFactor {
+    /// The first factors the user can verify with
+    pub fn factors(&self) -> Vec<FirstFactor> {
+        self.0
+            .sign_in
+            .supported_first_factors
+            .iter()
+            .flatten()
+            .map(FirstFactor::from_factor)
+            .collect()
+    }
+
+    /// Whether the email domain has an enterprise connection the user has
+    /// to sign in with
+    pub fn requires_enterprise_sso(&self) -> bool {
+        enterprise_sso_strategy(&self.0.sign_in).is_some()
+    }
+
+    /// Continues with the enterprise connection of the email domain, see
+    /// [`crate::enterprise_sso`]
+    pub fn enterprise_sso(self) -> EnterpriseSsoFlow {
+        EnterpriseSsoFlow::for_sign_in(self.0.clerk, self.0.sign_in)
+    }
+
+    /// Verifies the password of the account
+    pub async fn attempt_password(self, password: &str) -> Result<SignInState, Error> {
+        let response = self
+            .0
+            .clerk
+            .get_fapi_client()
+            .attempt_sign_in_factor_one_with(
+                &self.0.sign_in.id,
+                AttemptSignInFactorOneParams::password(password),
+            )
+            .await?;
+        SignInState::from_sign_in(self.0.clerk, *response.response).await
+    }
+
+    /// Verifies a passkey of the account, see [`crate::passkey`]
+    pub async fn attempt_passkey(
+        self,
+        authenticator: &dyn Authenticator,
+    ) -> Result<SignInState, Error> {
+        let response = self
+            .0
+            .clerk
+            .get_fapi_client()
+            .prepare_sign_in_factor_one_with(
+                &self.0.sign_in.id,
+                PrepareSignInFactorOneParams::passkey(),
+            )
+            .await?;
+        attempt_passkey(self.0.clerk, &response.response, authenticator).await
+    }
+
+    /// Sends a one time code, which is then verified with [`PendingFirstFactor::attempt`]
+    pub async fn prepare_code(self, factor: &CodeFactor) -> Result<PendingFirstFactor, Error> {
+        let params = match factor {
+            CodeFactor::EmailCode {
+                email_address_id, ..
+            } => PrepareSignInFactorOneParams::email_code(email_address_id),
+            CodeFactor::PhoneCode {
+                phone_number_id, ..
+            } => PrepareSignInFactorOneParams::phone_code(phone_number_id),
+            CodeFactor::ResetPasswordEmailCode {
+                email_address_id, ..
+            } => PrepareSignInFactorOneParams::reset_password_email_code(email_address_id),
+            CodeFactor::ResetPasswordPhoneCode {
+                phone_number_id, ..
+            } => PrepareSignInFactorOneParams::reset_password_phone_code(phone_number_id),
+        };
+
+        let response = self
+            .0
+            .clerk
+            .get_fapi_client()
+            .prepare_sign_in_factor_one_with(&self.0.sign_in.id, params)
+            .await?;
+
+        let step = SignInStep {
+            clerk: self.0.clerk,
+            sign_in: *response.response,
+        };
+        Ok(PendingFirstFactor(step, factor.strategy()))
+    }
+
+    /// Sends a magic link to the email address
+    ///
+    /// The sign in completes once the link is opened, use
+    /// [`NeedsFirstFactor::reload`] to pick up the new state.
+    pub async fn prepare_email_link(
+        self,
+        factor: &EmailLinkFactor,
+        redirect_url: &str,
+    ) -> Result<NeedsFirstFactor, Error> {
+        let response = self
+            .0
+            .clerk
+            .get_fapi_client()
+            .prepare_sign_in_factor_one_with(
+                &self.0.sign_in.id,
+                PrepareSignInFactorOneParams::email_link(&factor.email_address_id, redirect_url),
+            )
+            .await?;
+
+        Ok(NeedsFirstFactor(SignInStep {
+            clerk: self.0.clerk,
+            sign_in: *response.response,
+        }))
+    }
+}
+
+/// A one time code was sent for the first factor and has to be entered
+pub struct PendingFirstFactor(SignInStep, &'static str);
+
+impl PendingFirstFactor {
+    /// Verifies the code that was sent
+    pub async fn attempt(self, code: &str) -> Result<SignInState, Error> {
+        let response = self
+            .0
+            .clerk
+            .get_fapi_client()
+            .attempt_sign_in_factor_one_with(
+                &self.0.sign_in.id,
+                AttemptSignInFactorOneParams::code(self.1, code),
+            )
+            .await?;
+        SignInState::from_sign_in(self.0.clerk, *response.response).await
+    }
+}
+
+/// The first factor was verified and a second factor is required
+pub struct NeedsSecondFactor(SignInStep);
+
+impl NeedsSecondFactor {
+    /// The second factors the user can verify with
+    pub fn factors(&self) -> Vec<SecondFactor> {
+        self.0
+            .sign_in
+            .supported_second_factors
+            .iter()
+            .flatten()
+            .map(SecondFactor::from_factor)
+            .collect()
+    }
+
+    /// Sends a one time code to the phone number, which is then verified with
+    /// [`PendingSecondFactor::attempt`]
+    pub async fn prepare_phone_code(
+        self,
+        factor: &PhoneCodeFactor,
+    ) -> Result<PendingSecondFactor, Error> {
+        let response = self
+            .0
+            .clerk
+            .get_fapi_client()
+            .prepare_sign_in_factor_two(
+                &self.0.sign_in.id,
+                Some("phone_code"),
+                Some(&factor.phone_number_id),
+            )
+            .await?;
+        let step = SignInStep {
+            clerk: self.0.clerk,
+            sign_in: *response.response,
+        };
+        Ok(PendingSecondFactor(step, "phone_code"))
+    }
+
+    /// Verifies a code from an authenticator app
+    pub async fn attempt_totp(self, code: &str) -> Result<SignInState, Error> {
+        PendingSecondFactor(self.0, "totp").attempt(code).await
+    }
+
+    /// Verifies one of the backup codes of the account
+    pub async fn attempt_backup_code(self, code: &str) -> Result<SignInState, Error> {
+        PendingSecondFactor(self.0, "backup_code")
+            .attempt(code)
+            .await
+    }
+}
+
+/// A one time code was sent for the second factor and has to be entered
+pub struct PendingSecondFactor(SignInStep, &'static str);
+
+impl PendingSecondFactor {
+    /// Verifies the code that was sent
+    pub async fn attempt(self, code: &str) -> Result<SignInState, Error> {
+        let response = self
+            .0
+            .clerk
+            .get_fapi_client()
+            .attempt_sign_in_factor_two(&self.0.sign_in.id, Some(self.1), Some(code))
+            .await?;
+        SignInState::from_sign_in(self.0.clerk, *response.response).await
+    }
+}
+
+/// A reset password code was verified and a new password has to be set
+pub struct NeedsNewPassword(SignInStep);
+
+impl NeedsNewPassword {
+    /// Sets the new password of the account
+    pub async fn reset_password(
+        self,
+        password: &str,
+        sign_out_of_other_sessions: bool,
+    ) -> Result<SignInState, Error> {
+        let response = self
+            .0
+            .clerk
+            .get_fapi_client()
+            .reset_password(
+                &self.0.sign_in.id,
+                Some(password),
+                Some(sign_out_of_other_sessions),
+            )
+            .await?;
+        SignInState::from_sign_in(self.0.clerk, *response.response).await
+    }
+}
+
+/// The sign in is complete and its session is active on the [`Clerk`] instance
+pub struct Complete(SignInStep);
+
+impl Complete {
+    /// The id of the session created by the sign in
+    pub fn session_id(&self) -> &str {
+        self.0
+            .sign_in
+            .created_session_id
+            .as_deref()
+            .unwrap_or_default()
+    }
+
+    /// The [`Clerk`] instance the session was activated on
+    pub fn clerk(&self) -> &Clerk {
+        &self.0.clerk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::ClerkFapiConfiguration;
+    use crate::models::ClientPeriodClient as Client;
+    use crate::testing::fixtures::{
+        loaded_clerk, response_body, signed_in_client, ClientBuilder, EnvironmentBuilder,
+    };
+    use mockito::{Matcher, Server};
+
+    fn sign_in(status: Status) -> SignIn {
+        SignIn {
+            id: "sia_123".to_string(),
+            status,
+            ..Default::default()
+        }
+    }
+
+    fn factor(strategy: Strategy) -> SignInFactor {
+        SignInFactor::new(strategy)
+    }
+
+    #[test]
+    fn test_factors_are_typed() {
+        let mut email_code = factor(Strategy::EmailCode);
+        email_code.email_address_id = Some("idn_123".to_string());
+        email_code.safe_identifier = Some("u***@example.com".to_string());
+
+        let mut step = sign_in(Status::NeedsFirstFactor);
+        step.supported_first_factors = Some(vec![
+            factor(Strategy::Password),
+            email_code,
+            // A code factor without an email address can't be prepared
+            factor(Strategy::PhoneCode),
+            factor(Strategy::OauthGoogle),
+            factor(Strategy::Saml),
+        ]);
+
+        let factors = NeedsFirstFactor(SignInStep {
+            clerk: Clerk::new(ClerkFapiConfiguration::default()).unwrap(),
+            sign_in: step,
+        })
+        .factors();
+
+        assert_eq!(factors[0], FirstFactor::Password);
+        match &factors[1] {
+            FirstFactor::Code(code) => {
+                assert_eq!(code.strategy(), "email_code");
+                assert_eq!(code.safe_identifier(), Some("u***@example.com"));
+            }
+            other => panic!("expected an email code factor, got {:?}", other),
+        }
+        assert!(matches!(&factors[2], FirstFactor::Other(f) if f.strategy == Strategy::PhoneCode));
+        assert!(matches!(&factors[3], FirstFactor::Other(_)));
+        assert_eq!(factors[4], FirstFactor::EnterpriseSso);
+    }
+
+    #[tokio::test]
+    async fn test_email_code_sign_in_activates_session() {
+        let mut server = Server::new_async().await;
+
+        let mut needs_first_factor = sign_in(Status::NeedsFirstFactor);
+        let mut email_code = factor(Strategy::EmailCode);
+        email_code.email_address_id = Some("idn_123".to_string());
+        needs_first_factor.supported_first_factors = Some(vec![email_code]);
+
+        let create_mock = server
+            .mock("POST", "/v1/client/sign_ins?_is_native=1")
+            .match_body(Matcher::UrlEncoded(
+                "identifier".to_string(),
+                "user@example.com".to_string(),
+            ))
+            .with_body(response_body(&needs_first_factor, Client::default()))
+            .create_async()
+            .await;
+        let prepare_mock = server
+            .mock(
+                "POST",
+                "/v1/client/sign_ins/sia_123/prepare_first_factor?_is_native=1",
+            )
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("strategy".to_string(), "email_code".to_string()),
+                Matcher::UrlEncoded("email_address_id".to_string(), "idn_123".to_string()),
+            ]))
+            .with_body(response_body(&needs_first_factor, Client::default()))
+            .create_async()
+            .await;
+
+        let mut complete = sign_in(Status::Complete);
+        complete.created_session_id = Some("sess_123".to_string());
+        let attempt_mock = server
+            .mock(
+                "POST",
+                "/v1/client/sign_ins/sia_123/attempt_first_factor?_is_native=1",
+            )
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("strategy".to_string(), "email_code".to_string()),
+                Matcher::UrlEncoded("code".to_string(), "424242".to_string()),
+            ]))
+            .with_body(response_body(&complete, signed_in_client()))
+            .create_async()
+            .await;
+
+        let clerk = loaded_clerk(
+            server.url(),
+            EnvironmentBuilder::new(),
+            ClientBuilder::new(),
+        )
+        .await;
+        let state = clerk.sign_in().identify("user@example.com").await.unwrap();
+        let SignInState::NeedsFirstFactor(needs_factor) = state else {
+            panic!("expected the first factor to be needed, got {:?}", state);
+        };
+        let Some(FirstFactor::Code(code)) = needs_factor.factors().into_iter().next() else {
+            panic!("expected a code factor");
+        };
+
+        let pending = needs_factor.prepare_code(&code).await.unwrap();
+        let SignInState::Complete(complete) = pending.attempt("424242").await.unwrap() else {
+            panic!("expected the sign in to complete");
+        };
+
+        assert_eq!(complete.session_id(), "sess_123");
+        assert_eq!(
+            clerk.session().await.and_then(|s| s.id),
+            Some("sess_123".to_string())
+        );
+        create_mock.assert_async().await;
+        prepare_mock.assert_async().await;
+        attempt_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_password_sign_in_with_totp() {
+        let mut server = Server::new_async().await;
+
+        let mut needs_second_factor = sign_in(Status::NeedsSecondFactor);
+        needs_second_factor.supported_second_factors =
+            Some(vec![factor(Strategy::Totp), factor(Strategy::BackupCode)]);
+        let create_mock = server
+            .mock("POST", "/v1/client/sign_ins?_is_native=1")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("strategy".to_string(), "password".to_string()),
+                Matcher::UrlEncoded("password".to_string(), "hunter2".to_string()),
+            ]))
+            .with_body(response_body(&needs_second_factor, Client::default()))
+            .create_async()
+            .await;
+
+        let mut complete = sign_in(Status::Complete);
+        complete.created_session_id = Some("sess_123".to_string());
+        let attempt_mock = server
+            .mock(
+                "POST",
+                "/v1/client/sign_ins/sia_123/attempt_second_factor?_is_native=1",
+            )
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("strategy".to_string(), "totp".to_string()),
+                Matcher::UrlEncoded("code".to_string(), "123456".to_string()),
+            ]))
+            .with_body(response_body(&complete, signed_in_client()))
+            .create_async()
+            .await;
+
+        let clerk = loaded_clerk(
+            server.url(),
+            EnvironmentBuilder::new(),
+            ClientBuilder::new(),
+        )
+        .await;
+        let state = clerk
+            .sign_in()
+            .password("user@example.com", "hunter2")
+            .await
+            .unwrap();
+        let SignInState::NeedsSecondFactor(needs_factor) = state else {
+            panic!("expected the second factor to be needed, got {:?}", state);
+        };
+        assert_eq!(
+            needs_factor.factors(),
+            vec![SecondFactor::Totp, SecondFactor::BackupCode]
+        );
+
+        let state = needs_factor.attempt_totp("123456").await.unwrap();
+        assert_eq!(state.status(), Status::Complete);
+        assert!(clerk.user().await.is_some());
+        create_mock.assert_async().await;
+        attempt_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_complete_sign_in_without_session_is_unexpected() {
+        let clerk = Clerk::new(ClerkFapiConfiguration::default()).unwrap();
+        let result = SignInState::from_sign_in(clerk, sign_in(Status::Complete)).await;
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedResponse(reason)) if reason == "complete sign in has no created session"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_sign_in_is_an_error() {
+        let mut server = Server::new_async().await;
+        let get_mock = server
+            .mock("GET", "/v1/client/sign_ins/sia_123?_is_native=1")
+            .with_body(response_body(
+                &sign_in(Status::Abandoned),
+                Client::default(),
+            ))
+            .create_async()
+            .await;
+
+        let clerk = loaded_clerk(
+            server.url(),
+            EnvironmentBuilder::new(),
+            ClientBuilder::new(),
+        )
+        .await;
+        let result = clerk.sign_in().resume("sia_123").await;
+        assert!(matches!(result, Err(Error::SignInAbandoned(id)) if id == "sia_123"));
+        get_mock.assert_async().await;
+    }
+}
@@ -0,0 +1,835 @@
+//! Typed sign up flow
+//!
+//! [`Clerk::sign_up`] returns a [`SignUpFlow`] builder. The fields are
+//! validated against the user settings of the loaded environment before the
+//! sign up is created, and every step returns a [`SignUpState`] that reports
+//! the missing and unverified fields as [`SignUpField`]s. A completed sign up
+//! activates its session on the [`Clerk`] instance.
+//!
+//! ```no_run
+//! # use clerk_fapi_rs::clerk::{Clerk, Error};
+//! # use clerk_fapi_rs::sign_up::{SignUpField, SignUpState};
+//! # async fn example(clerk: Clerk) -> Result<(), Error> {
+//! let state = clerk
+//!     .sign_up()
+//!     .email_address("user@example.com")
+//!     .password("correct horse battery staple")
+//!     .create()
+//!     .await?;
+//!
+//! if let SignUpState::MissingRequirements(missing) = state {
+//!     if missing.unverified_fields().contains(&SignUpField::EmailAddress) {
+//!         let pending = missing.prepare_email_code().await?;
+//!         pending.attempt("123456").await?;
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::clerk::{Clerk, Error};
+use crate::models::client_period_sign_up::Status;
+use crate::models::{
+    ClientPeriodSignUp as SignUp, ClientPeriodUserSettings as UserSettings,
+    UserSettingsPeriodAttribute as Attribute,
+};
+use crate::params::{CreateSignUpParams, UpdateSignUpParams};
+use serde_json::Value as JsonValue;
+use std::fmt;
+
+/// A field of a sign up as reported in its required, missing and unverified fields
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SignUpField {
+    EmailAddress,
+    PhoneNumber,
+    Username,
+    Password,
+    FirstName,
+    LastName,
+    Web3Wallet,
+    LegalAccepted,
+    /// A field this flow has no typed support for
+    Other(String),
+}
+
+impl SignUpField {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "email_address" => SignUpField::EmailAddress,
+            "phone_number" => SignUpField::PhoneNumber,
+            "username" => SignUpField::Username,
+            "password" => SignUpField::Password,
+            "first_name" => SignUpField::FirstName,
+            "last_name" => SignUpField::LastName,
+            "web3_wallet" => SignUpField::Web3Wallet,
+            "legal_accepted" => SignUpField::LegalAccepted,
+            other => SignUpField::Other(other.to_string()),
+        }
+    }
+
+    /// The name of the field in the Frontend API
+    pub fn as_str(&self) -> &str {
+        match self {
+            SignUpField::EmailAddress => "email_address",
+            SignUpField::PhoneNumber => "phone_number",
+            SignUpField::Username => "username",
+            SignUpField::Password => "password",
+            SignUpField::FirstName => "first_name",
+            SignUpField::LastName => "last_name",
+            SignUpField::Web3Wallet => "web3_wallet",
+            SignUpField::LegalAccepted => "legal_accepted",
+            SignUpField::Other(name) => name,
+        }
+    }
+
+    fn parse_all(names: &Option<Vec<String>>) -> Vec<SignUpField> {
+        names
+            .iter()
+            .flatten()
+            .map(|name| Self::from_name(name))
+            .collect()
+    }
+}
+
+impl fmt::Display for SignUpField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A kind of character the password settings can require
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordCharacter {
+    Number,
+    Uppercase,
+    Lowercase,
+    Special,
+}
+
+/// Why a sign up field was rejected by [`SignUpFields::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldError {
+    /// The field is not enabled for the instance
+    Disabled(SignUpField),
+    /// The value is shorter than the configured minimum
+    TooShort {
+        field: SignUpField,
+        min_length: usize,
+    },
+    /// The value is longer than the configured maximum
+    TooLong {
+        field: SignUpField,
+        max_length: usize,
+    },
+    /// The password lacks a kind of character the settings require
+    PasswordRequires(PasswordCharacter),
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldError::Disabled(field) => write!(f, "{} is not enabled", field),
+            FieldError::TooShort { field, min_length } => {
+                write!(f, "{} must be at least {} characters", field, min_length)
+            }
+            FieldError::TooLong { field, max_length } => {
+                write!(f, "{} must be at most {} characters", field, max_length)
+            }
+            FieldError::PasswordRequires(character) => {
+                write!(f, "password must contain a {:?} character", character)
+            }
+        }
+    }
+}
+
+/// Values sent when creating or updating a sign up
+#[derive(Clone, Default)]
+pub struct SignUpFields {
+    email_address: Option<String>,
+    phone_number: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    unsafe_metadata: Option<JsonValue>,
+}
+
+impl SignUpFields {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn email_address(mut self, email_address: impl Into<String>) -> Self {
+        self.email_address = Some(email_address.into());
+        self
+    }
+
+    pub fn phone_number(mut self, phone_number: impl Into<String>) -> Self {
+        self.phone_number = Some(phone_number.into());
+        self
+    }
+
+    pub fn username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    pub fn first_name(mut self, first_name: impl Into<String>) -> Self {
+        self.first_name = Some(first_name.into());
+        self
+    }
+
+    pub fn last_name(mut self, last_name: impl Into<String>) -> Self {
+        self.last_name = Some(last_name.into());
+        self
+    }
+
+    /// Custom JSON object stored on the user once the sign up completes
+    pub fn unsafe_metadata(mut self, unsafe_metadata: JsonValue) -> Self {
+        self.unsafe_metadata = Some(unsafe_metadata);
+        self
+    }
+
+    /// Checks the fields against the user settings of the instance
+    pub fn validate(&self, settings: &UserSettings) -> Result<(), Vec<FieldError>> {
+        let attributes = &settings.attributes;
+        let mut errors = Vec::new();
+
+        let mut check_enabled = |value: &Option<String>, attribute: &Attribute, field| {
+            if value.is_some() && attribute.enabled != Some(true) {
+                errors.push(FieldError::Disabled(field));
+            }
+        };
+        check_enabled(
+            &self.email_address,
+            &attributes.email_address,
+            SignUpField::EmailAddress,
+        );
+        check_enabled(
+            &self.phone_number,
+            &attributes.phone_number,
+            SignUpField::PhoneNumber,
+        );
+        check_enabled(&self.username, &attributes.username, SignUpField::Username);
+        check_enabled(&self.password, &attributes.password, SignUpField::Password);
+        check_enabled(
+            &self.first_name,
+            &attributes.first_name,
+            SignUpField::FirstName,
+        );
+        check_enabled(
+            &self.last_name,
+            &attributes.last_name,
+            SignUpField::LastName,
+        );
+
+        if let (Some(username), Some(username_settings)) =
+            (&self.username, &settings.username_settings)
+        {
+            check_length(
+                &mut errors,
+                SignUpField::Username,
+                username,
+                username_settings.min_length,
+                username_settings.max_length,
+            );
+        }
+
+        if let Some(password) = &self.password {
+            let password_settings = &settings.password_settings;
+            check_length(
+                &mut errors,
+                SignUpField::Password,
+                password,
+                password_settings.min_length,
+                password_settings.max_length,
+            );
+
+            let is_special = |c: char| {
+                if password_settings.allowed_special_characters.is_empty() {
+                    !c.is_alphanumeric()
+                } else {
+                    password_settings.allowed_special_characters.contains(c)
+                }
+            };
+            let requirements = [
+                (
+                    password_settings.require_numbers,
+                    PasswordCharacter::Number,
+                    password.chars().any(|c| c.is_numeric()),
+                ),
+                (
+                    password_settings.require_uppercase,
+                    PasswordCharacter::Uppercase,
+                    password.chars().any(|c| c.is_uppercase()),
+                ),
+                (
+                    password_settings.require_lowercase,
+                    PasswordCharacter::Lowercase,
+                    password.chars().any(|c| c.is_lowercase()),
+                ),
+                (
+                    password_settings.require_special_char,
+                    PasswordCharacter::Special,
+                    password.chars().any(is_special),
+                ),
+            ];
+            for (required, character, present) in requirements {
+                if required && !present {
+                    errors.push(FieldError::PasswordRequires(character));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl From<SignUpFields> for CreateSignUpParams {
+    fn from(fields: SignUpFields) -> Self {
+        Self {
+            password: fields.password,
+            first_name: fields.first_name,
+            last_name: fields.last_name,
+            username: fields.username,
+            email_address: fields.email_address,
+            phone_number: fields.phone_number,
+            unsafe_metadata: fields.unsafe_metadata,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<SignUpFields> for UpdateSignUpParams {
+    fn from(fields: SignUpFields) -> Self {
+        Self {
+            password: fields.password,
+            first_name: fields.first_name,
+            last_name: fields.last_name,
+            username: fields.username,
+            email_address: fields.email_address,
+            phone_number: fields.phone_number,
+            unsafe_metadata: fields.unsafe_metadata,
+            ..Default::default()
+        }
+    }
+}
+
+fn check_length(
+    errors: &mut Vec<FieldError>,
+    field: SignUpField,
+    value: &str,
+    min_length: i64,
+    max_length: i64,
+) {
+    let length = value.chars().count() as i64;
+    if min_length > 0 && length < min_length {
+        errors.push(FieldError::TooShort {
+            field,
+            min_length: min_length as usize,
+        });
+    } else if max_length > 0 && length > max_length {
+        errors.push(FieldError::TooLong {
+            field,
+            max_length: max_length as usize,
+        });
+    }
+}
+
+impl fmt::Debug for SignUpFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignUpFields")
+            .field("email_address", &self.email_address)
+            .field("phone_number", &self.phone_number)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "[redacted]"))
+            .field("first_name", &self.first_name)
+            .field("last_name", &self.last_name)
+            .field("unsafe_metadata", &self.unsafe_metadata)
+            .finish()
+    }
+}
+
+/// Validates `fields` against the user settings of the loaded environment
+async fn validate(clerk: &Clerk, fields: &SignUpFields) -> Result<(), Error> {
+    let settings = clerk
+        .environment()
+        .await
+        .and_then(|environment| environment.user_settings)
+        .ok_or(Error::NotLoaded)?;
+    fields.validate(&settings).map_err(Error::InvalidSignUp)
+}
+
+/// Builder for a new sign up, created with [`Clerk::sign_up`]
+#[derive(Clone)]
+pub struct SignUpFlow {
+    clerk: Clerk,
+    fields: SignUpFields,
+}
+
+impl SignUpFlow {
+    pub(crate) fn new(clerk: Clerk) -> Self {
+        Self {
+            clerk,
+            fields: SignUpFields::default(),
+        }
+    }
+
+    pub fn email_address(mut self, email_address: impl Into<String>) -> Self {
+        self.fields = self.fields.email_address(email_address);
+        self
+    }
+
+    pub fn phone_number(mut self, phone_number: impl Into<String>) -> Self {
+        self.fields = self.fields.phone_number(phone_number);
+        self
+    }
+
+    pub fn username(mut self, username: impl Into<String>) -> Self {
+        self.fields = self.fields.username(username);
+        self
+    }
+
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.fields = self.fields.password(password);
+        self
+    }
+
+    pub fn first_name(mut self, first_name: impl Into<String>) -> Self {
+        self.fields = self.fields.first_name(first_name);
+        self
+    }
+
+    pub fn last_name(mut self, last_name: impl Into<String>) -> Self {
+        self.fields = self.fields.last_name(last_name);
+        self
+    }
+
+    /// Custom JSON object stored on the user once the sign up completes
+    pub fn unsafe_metadata(mut self, unsafe_metadata: JsonValue) -> Self {
+        self.fields = self.fields.unsafe_metadata(unsafe_metadata);
+        self
+    }
+
+    /// Validates the fields and creates the sign up
+    ///
+    /// Fails with [`Error::InvalidSignUp`] before making a request if the
+    /// fields don't match the user settings, and with [`Error::NotLoaded`] if
+    /// the environment hasn't been loaded.
+    pub async fn create(self) -> Result<SignUpState, Error> {
+        validate(&self.clerk, &self.fields).await?;
+
+        let response = self
+            .clerk
+            .get_fapi_client()
+            .create_sign_ups_with(self.fields.into())
+            .await?;
+        SignUpState::from_sign_up(self.clerk, *response.response).await
+    }
+
+    /// Continues an existing sign up
+    pub async fn resume(self, sign_up_id: &str) -> Result<SignUpState, Error> {
+        let response = self
+            .clerk
+            .get_fapi_client()
+            .get_sign_ups(sign_up_id)
+            .await?;
+        SignUpState::from_sign_up(self.clerk, *response.response).await
+    }
+}
+
+/// The state of a sign up after the last request
+pub enum SignUpState {
+    MissingRequirements(MissingRequirements),
+    Complete(Complete),
+}
+
+impl SignUpState {
+    pub(crate) async fn from_sign_up(clerk: Clerk, sign_up: SignUp) -> Result<Self, Error> {
+        let id = sign_up
+            .id
+            .clone()
+            .ok_or_else(|| Error::UnexpectedResponse("sign up has no id".to_string()))?;
+        let step = SignUpStep { clerk, sign_up, id };
+
+        Ok(match step.sign_up.status {
+            Some(Status::Complete) => {
+                let session_id = step
+                    .sign_up
+                    .created_session_id
+                    .clone()
+                    .flatten()
+                    .ok_or_else(|| {
+                        Error::UnexpectedResponse(
+                            "complete sign up has no created session".to_string(),
+                        )
+                    })?;
+                step.clerk.activate_session(session_id).await?;
+                SignUpState::Complete(Complete(step))
+            }
+            Some(Status::Abandoned) => return Err(Error::SignUpAbandoned(step.id)),
+            Some(Status::MissingRequirements) | None => {
+                SignUpState::MissingRequirements(MissingRequirements(step))
+            }
+        })
+    }
+
+    /// The sign up as returned by the last request
+    pub fn sign_up(&self) -> &SignUp {
+        match self {
+            SignUpState::MissingRequirements(s) => s.sign_up(),
+            SignUpState::Complete(s) => s.sign_up(),
+        }
+    }
+}
+
+impl fmt::Debug for SignUpState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignUpState")
+            .field("id", &self.sign_up().id)
+            .field("status", &self.sign_up().status)
+            .finish()
+    }
+}
+
+/// Shared data of every state
+#[derive(Clone)]
+struct SignUpStep {
+    clerk: Clerk,
+    sign_up: SignUp,
+    id: String,
+}
+
+macro_rules! sign_up_state {
+    ($($state:ident),*) => {$(
+        impl $state {
+            /// The sign up as returned by the last request
+            pub fn sign_up(&self) -> &SignUp {
+                &self.0.sign_up
+            }
+
+            /// Fetches the latest state of the sign up
+            pub async fn reload(self) -> Result<SignUpState, Error> {
+                SignUpFlow::new(self.0.clerk).resume(&self.0.id).await
+            }
+        }
+    )*};
+}
+
+sign_up_state!(MissingRequirements, PendingVerification, Complete);
+
+/// Fields are missing or have to be verified before the sign up completes
+pub struct MissingRequirements(SignUpStep);
+
+impl MissingRequirements {
+    /// Fields that have to be set before the sign up completes
+    pub fn missing_fields(&self) -> Vec<SignUpField> {
+        SignUpField::parse_all(&self.0.sign_up.missing_fields)
+    }
+
+    /// Fields that were set but have to be verified
+    pub fn unverified_fields(&self) -> Vec<SignUpField> {
+        SignUpField::parse_all(&self.0.sign_up.unverified_fields)
+    }
+
+    /// Fields the instance requires for every sign up
+    pub fn required_fields(&self) -> Vec<SignUpField> {
+        SignUpField::parse_all(&self.0.sign_up.required_fields)
+    }
+
+    /// Fields that can optionally be set
+    pub fn optional_fields(&self) -> Vec<SignUpField> {
+        SignUpField::parse_all(&self.0.sign_up.optional_fields)
+    }
+
+    /// Validates and sets more fields on the sign up
+    pub async fn update(self, fields: SignUpFields) -> Result<SignUpState, Error> {
+        validate(&self.0.clerk, &fields).await?;
+
+        let response = self
+            .0
+            .clerk
+            .get_fapi_client()
+            .update_sign_ups_with(&self.0.id, fields.into())
+            .await?;
+        SignUpState::from_sign_up(self.0.clerk, *response.response).await
+    }
+
+    /// Sends a verification code to the email address of the sign up
+    pub async fn prepare_email_code(self) -> Result<PendingVerification, Error> {
+        self.prepare("email_code").await
+    }
+
+    /// Sends a verification code to the phone number of the sign up
+    pub async fn prepare_phone_code(self) -> Result<PendingVerification, Error> {
+        self.prepare("phone_code").await
+    }
+
+    async fn prepare(self, strategy: &'static str) -> Result<PendingVerification, Error> {
+        let response = self
+            .0
+            .clerk
+            .get_fapi_client()
+            .prepare_sign_ups_verification(&self.0.id, Some(strategy))
+            .await?;
+        let step = SignUpStep {
+            sign_up: *response.response,
+            ..self.0
+        };
+        Ok(PendingVerification(step, strategy))
+    }
+}
+
+/// A verification code was sent and has to be entered
+pub struct PendingVerification(SignUpStep, &'static str);
+
+impl PendingVerification {
+    /// Verifies the code that was sent
+    pub async fn attempt(self, code: &str) -> Result<SignUpState, Error> {
+        let response = self
+            .0
+            .clerk
+            .get_fapi_client()
+            .attempt_sign_ups_verification(&self.0.id, Some(self.1), Some(code), None)
+            .await?;
+        SignUpState::from_sign_up(self.0.clerk, *response.response).await
+    }
+}
+
+/// The sign up is complete and its session is active on the [`Clerk`] instance
+pub struct Complete(SignUpStep);
+
+impl Complete {
+    /// The id of the session created by the sign up
+    pub fn session_id(&self) -> &str {
+        self.0
+            .sign_up
+            .created_session_id
+            .as_ref()
+            .and_then(|id| id.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// The id of the user created by the sign up
+    pub fn user_id(&self) -> Option<&str> {
+        self.0
+            .sign_up
+            .created_user_id
+            .as_ref()
+            .and_then(|id| id.as_deref())
+    }
+
+    /// The [`Clerk`] instance the session was activated on
+    pub fn clerk(&self) -> &Clerk {
+        &self.0.clerk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ClientPeriodClient as Client, ClientPeriodEnvironment as Environment};
+    use crate::testing::fixtures::{loaded_clerk, response_body, signed_in_client, ClientBuilder};
+    use mockito::{Matcher, Server};
+
+    fn enabled() -> Box<Attribute> {
+        Box::new(Attribute {
+            enabled: Some(true),
+            ..Default::default()
+        })
+    }
+
+    fn user_settings() -> UserSettings {
+        let mut settings = UserSettings::default();
+        settings.attributes.email_address = enabled();
+        settings.attributes.password = enabled();
+        settings.password_settings.min_length = 8;
+        settings.password_settings.require_numbers = true;
+        settings
+    }
+
+    fn environment() -> Environment {
+        Environment {
+            user_settings: Some(Box::new(user_settings())),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fields_are_validated_against_user_settings() {
+        let settings = user_settings();
+
+        let fields = SignUpFields::new()
+            .email_address("user@example.com")
+            .password("secret1234");
+        assert_eq!(fields.validate(&settings), Ok(()));
+
+        let fields = SignUpFields::new()
+            .username("user")
+            .password("secret")
+            .unsafe_metadata(serde_json::json!({"plan": "pro"}));
+        assert_eq!(
+            fields.validate(&settings),
+            Err(vec![
+                FieldError::Disabled(SignUpField::Username),
+                FieldError::TooShort {
+                    field: SignUpField::Password,
+                    min_length: 8
+                },
+                FieldError::PasswordRequires(PasswordCharacter::Number),
+            ])
+        );
+        assert!(!format!("{:?}", fields).contains("secret"));
+    }
+
+    #[test]
+    fn test_fields_are_typed() {
+        let names = Some(vec!["email_address".to_string(), "saml".to_string()]);
+        assert_eq!(
+            SignUpField::parse_all(&names),
+            vec![
+                SignUpField::EmailAddress,
+                SignUpField::Other("saml".to_string())
+            ]
+        );
+        assert_eq!(SignUpField::Other("saml".to_string()).as_str(), "saml");
+    }
+
+    #[tokio::test]
+    async fn test_sign_up_without_id_is_unexpected() {
+        let result = SignUpState::from_sign_up(Clerk::default(), SignUp::default()).await;
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedResponse(reason)) if reason == "sign up has no id"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_complete_sign_up_without_session_is_unexpected() {
+        let sign_up = SignUp {
+            id: Some("sua_123".to_string()),
+            status: Some(Status::Complete),
+            ..Default::default()
+        };
+        let result = SignUpState::from_sign_up(Clerk::default(), sign_up).await;
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedResponse(reason)) if reason == "complete sign up has no created session"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_sign_up_is_rejected_before_request() {
+        let mut server = Server::new_async().await;
+        let clerk = loaded_clerk(server.url(), environment(), ClientBuilder::new()).await;
+        let create_mock = server
+            .mock("POST", "/v1/client/sign_ups?_is_native=1")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let result = clerk.sign_up().phone_number("+15555550100").create().await;
+        assert!(matches!(
+            result,
+            Err(Error::InvalidSignUp(errors))
+                if errors == vec![FieldError::Disabled(SignUpField::PhoneNumber)]
+        ));
+        create_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_email_code_sign_up_activates_session() {
+        let mut server = Server::new_async().await;
+        let clerk = loaded_clerk(server.url(), environment(), ClientBuilder::new()).await;
+
+        let unverified = SignUp {
+            id: Some("sua_123".to_string()),
+            status: Some(Status::MissingRequirements),
+            unverified_fields: Some(vec!["email_address".to_string()]),
+            ..Default::default()
+        };
+        let create_mock = server
+            .mock("POST", "/v1/client/sign_ups?_is_native=1")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("email_address".to_string(), "user@example.com".to_string()),
+                Matcher::UrlEncoded("password".to_string(), "secret1234".to_string()),
+                Matcher::UrlEncoded(
+                    "unsafe_metadata".to_string(),
+                    r#"{"plan":"pro"}"#.to_string(),
+                ),
+            ]))
+            .with_body(response_body(&unverified, Client::default()))
+            .create_async()
+            .await;
+        let prepare_mock = server
+            .mock(
+                "POST",
+                "/v1/client/sign_ups/sua_123/prepare_verification?_is_native=1",
+            )
+            .match_body(Matcher::UrlEncoded(
+                "strategy".to_string(),
+                "email_code".to_string(),
+            ))
+            .with_body(response_body(&unverified, Client::default()))
+            .create_async()
+            .await;
+
+        let complete = SignUp {
+            id: Some("sua_123".to_string()),
+            status: Some(Status::Complete),
+            created_session_id: Some(Some("sess_123".to_string())),
+            created_user_id: Some(Some("user_123".to_string())),
+            ..Default::default()
+        };
+        let attempt_mock = server
+            .mock(
+                "POST",
+                "/v1/client/sign_ups/sua_123/attempt_verification?_is_native=1",
+            )
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("strategy".to_string(), "email_code".to_string()),
+                Matcher::UrlEncoded("code".to_string(), "424242".to_string()),
+            ]))
+            .with_body(response_body(&complete, signed_in_client()))
+            .create_async()
+            .await;
+
+        let state = clerk
+            .sign_up()
+            .email_address("user@example.com")
+            .password("secret1234")
+            .unsafe_metadata(serde_json::json!({"plan": "pro"}))
+            .create()
+            .await
+            .unwrap();
+        let SignUpState::MissingRequirements(missing) = state else {
+            panic!("expected missing requirements, got {:?}", state);
+        };
+        assert_eq!(missing.unverified_fields(), vec![SignUpField::EmailAddress]);
+        assert!(missing.missing_fields().is_empty());
+
+        let pending = missing.prepare_email_code().await.unwrap();
+        let SignUpState::Complete(complete) = pending.attempt("424242").await.unwrap() else {
+            panic!("expected the sign up to complete");
+        };
+
+        assert_eq!(complete.session_id(), "sess_123");
+        assert_eq!(complete.user_id(), Some("user_123"));
+        assert_eq!(
+            clerk.session().await.and_then(|s| s.id),
+            Some("sess_123".to_string())
+        );
+        create_mock.assert_async().await;
+        prepare_mock.assert_async().await;
+        attempt_mock.assert_async().await;
+    }
+}
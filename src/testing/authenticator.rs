@@ -0,0 +1,356 @@
+use crate::passkey::{
+    AssertionCredential, Authenticator, AuthenticatorError, CreationOptions,
+    RegistrationCredential, RequestOptions,
+};
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use parking_lot::Mutex;
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+
+/// COSE algorithm identifier of ES256, the only algorithm the authenticator supports
+const ES256: i64 = -7;
+
+/// Authenticator data flags
+const USER_PRESENT: u8 = 0x01;
+const USER_VERIFIED: u8 = 0x04;
+const ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+struct Credential {
+    id: Vec<u8>,
+    rp_id: String,
+    user_handle: Vec<u8>,
+    key_pair: EcdsaKeyPair,
+    sign_count: u32,
+}
+
+/// An [`Authenticator`] keeping ES256 passkeys in memory
+///
+/// Every ceremony counts as verified by the user. Credentials are created
+/// with the `none` attestation format and are discoverable, so they can sign
+/// in without an identifier.
+pub struct SoftwareAuthenticator {
+    origin: String,
+    credentials: Mutex<Vec<Credential>>,
+    rng: SystemRandom,
+}
+
+impl SoftwareAuthenticator {
+    /// Creates an authenticator for `origin`, e.g. `https://example.com`,
+    /// which is written to the client data of every credential
+    pub fn new(origin: impl Into<String>) -> Self {
+        Self {
+            origin: origin.into(),
+            credentials: Mutex::new(Vec::new()),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// IDs of the credentials created so far
+    pub fn credential_ids(&self) -> Vec<Vec<u8>> {
+        self.credentials
+            .lock()
+            .iter()
+            .map(|credential| credential.id.clone())
+            .collect()
+    }
+
+    /// Public key of the credential as an uncompressed P-256 point, to
+    /// verify assertions with
+    pub fn public_key(&self, credential_id: &[u8]) -> Option<Vec<u8>> {
+        self.credentials
+            .lock()
+            .iter()
+            .find(|credential| credential.id == credential_id)
+            .map(|credential| credential.key_pair.public_key().as_ref().to_vec())
+    }
+
+    /// The relying party defaults to the host of the origin
+    fn rp_id(&self, rp_id: Option<&str>) -> String {
+        rp_id
+            .map(str::to_string)
+            .or_else(|| {
+                url::Url::parse(&self.origin)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_string))
+            })
+            .unwrap_or_default()
+    }
+
+    fn client_data(&self, kind: &str, challenge: &[u8]) -> Vec<u8> {
+        serde_json::json!({
+            "type": kind,
+            "challenge": URL_SAFE_NO_PAD.encode(challenge),
+            "origin": self.origin,
+            "crossOrigin": false,
+        })
+        .to_string()
+        .into_bytes()
+    }
+}
+
+#[async_trait]
+impl Authenticator for SoftwareAuthenticator {
+    async fn create(
+        &self,
+        options: &CreationOptions,
+    ) -> Result<RegistrationCredential, AuthenticatorError> {
+        // No parameters means the defaults of WebAuthn, which include ES256
+        if !options.pub_key_cred_params.is_empty()
+            && !options.pub_key_cred_params.iter().any(|p| p.alg == ES256)
+        {
+            return Err(AuthenticatorError::new("no supported algorithm"));
+        }
+        let rp_id = self.rp_id(options.rp.id.as_deref());
+        let mut credentials = self.credentials.lock();
+        let excluded = credentials.iter().any(|credential| {
+            options
+                .exclude_credentials
+                .iter()
+                .any(|excluded| excluded.id == credential.id)
+        });
+        if excluded {
+            return Err(AuthenticatorError::new("credential already registered"));
+        }
+
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &self.rng)
+            .map_err(|_| AuthenticatorError::new("key generation failed"))?;
+        let key_pair =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &self.rng)
+                .map_err(|_| AuthenticatorError::new("key generation failed"))?;
+        let mut id = vec![0u8; 16];
+        self.rng
+            .fill(&mut id)
+            .map_err(|_| AuthenticatorError::new("random generation failed"))?;
+
+        // The uncompressed point is 0x04 followed by the x and y coordinates
+        let point = key_pair.public_key().as_ref();
+        let mut public_key = Vec::new();
+        cbor_head(5, 5, &mut public_key);
+        cbor_int(1, &mut public_key); // kty: EC2
+        cbor_int(2, &mut public_key);
+        cbor_int(3, &mut public_key); // alg: ES256
+        cbor_int(ES256, &mut public_key);
+        cbor_int(-1, &mut public_key); // crv: P-256
+        cbor_int(1, &mut public_key);
+        cbor_int(-2, &mut public_key);
+        cbor_bytes(&point[1..33], &mut public_key);
+        cbor_int(-3, &mut public_key);
+        cbor_bytes(&point[33..65], &mut public_key);
+
+        let mut auth_data = authenticator_data(
+            &rp_id,
+            USER_PRESENT | USER_VERIFIED | ATTESTED_CREDENTIAL_DATA,
+            0,
+        );
+        auth_data.extend_from_slice(&[0u8; 16]); // AAGUID
+        auth_data.extend_from_slice(&(id.len() as u16).to_be_bytes());
+        auth_data.extend_from_slice(&id);
+        auth_data.extend_from_slice(&public_key);
+
+        let mut attestation_object = Vec::new();
+        cbor_head(5, 3, &mut attestation_object);
+        cbor_text("fmt", &mut attestation_object);
+        cbor_text("none", &mut attestation_object);
+        cbor_text("attStmt", &mut attestation_object);
+        cbor_head(5, 0, &mut attestation_object);
+        cbor_text("authData", &mut attestation_object);
+        cbor_bytes(&auth_data, &mut attestation_object);
+
+        credentials.push(Credential {
+            id: id.clone(),
+            rp_id,
+            user_handle: options.user.id.clone(),
+            key_pair,
+            sign_count: 0,
+        });
+        Ok(RegistrationCredential {
+            id,
+            client_data_json: self.client_data("webauthn.create", &options.challenge),
+            attestation_object,
+            transports: vec!["internal".to_string()],
+            authenticator_attachment: Some("platform".to_string()),
+        })
+    }
+
+    async fn get(
+        &self,
+        options: &RequestOptions,
+    ) -> Result<AssertionCredential, AuthenticatorError> {
+        let rp_id = self.rp_id(options.rp_id.as_deref());
+        let mut credentials = self.credentials.lock();
+        let credential = credentials
+            .iter_mut()
+            .find(|credential| {
+                credential.rp_id == rp_id
+                    && (options.allow_credentials.is_empty()
+                        || options
+                            .allow_credentials
+                            .iter()
+                            .any(|allowed| allowed.id == credential.id))
+            })
+            .ok_or_else(|| AuthenticatorError::new("no credential for the relying party"))?;
+
+        credential.sign_count += 1;
+        let authenticator_data =
+            authenticator_data(&rp_id, USER_PRESENT | USER_VERIFIED, credential.sign_count);
+        let client_data_json = self.client_data("webauthn.get", &options.challenge);
+        let mut signed = authenticator_data.clone();
+        signed.extend_from_slice(digest(&SHA256, &client_data_json).as_ref());
+        let signature = credential
+            .key_pair
+            .sign(&self.rng, &signed)
+            .map_err(|_| AuthenticatorError::new("signing failed"))?;
+
+        Ok(AssertionCredential {
+            id: credential.id.clone(),
+            client_data_json,
+            authenticator_data,
+            signature: signature.as_ref().to_vec(),
+            user_handle: Some(credential.user_handle.clone()),
+            authenticator_attachment: Some("platform".to_string()),
+        })
+    }
+}
+
+fn authenticator_data(rp_id: &str, flags: u8, sign_count: u32) -> Vec<u8> {
+    let mut data = digest(&SHA256, rp_id.as_bytes()).as_ref().to_vec();
+    data.push(flags);
+    data.extend_from_slice(&sign_count.to_be_bytes());
+    data
+}
+
+fn cbor_head(major: u8, value: u64, out: &mut Vec<u8>) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, value as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 26);
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+    }
+}
+
+fn cbor_int(value: i64, out: &mut Vec<u8>) {
+    if value >= 0 {
+        cbor_head(0, value as u64, out);
+    } else {
+        cbor_head(1, (-1 - value) as u64, out);
+    }
+}
+
+fn cbor_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    cbor_head(2, bytes.len() as u64, out);
+    out.extend_from_slice(bytes);
+}
+
+fn cbor_text(text: &str, out: &mut Vec<u8>) {
+    cbor_head(3, text.len() as u64, out);
+    out.extend_from_slice(text.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
+    use serde_json::Value as JsonValue;
+
+    fn creation_options() -> CreationOptions {
+        serde_json::from_value(serde_json::json!({
+            "challenge": URL_SAFE_NO_PAD.encode(b"registration"),
+            "rp": { "id": "example.com", "name": "Example" },
+            "user": { "id": URL_SAFE_NO_PAD.encode(b"user_123"), "name": "ada", "displayName": "Ada" },
+            "pubKeyCredParams": [{ "type": "public-key", "alg": -7 }],
+        }))
+        .unwrap()
+    }
+
+    fn request_options(allow: &[Vec<u8>]) -> RequestOptions {
+        serde_json::from_value(serde_json::json!({
+            "challenge": URL_SAFE_NO_PAD.encode(b"sign in"),
+            "allowCredentials": allow
+                .iter()
+                .map(|id| serde_json::json!({ "type": "public-key", "id": URL_SAFE_NO_PAD.encode(id) }))
+                .collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_registration_is_a_none_attestation() {
+        let authenticator = SoftwareAuthenticator::new("https://example.com");
+        let credential = authenticator.create(&creation_options()).await.unwrap();
+
+        let client_data: JsonValue = serde_json::from_slice(&credential.client_data_json).unwrap();
+        assert_eq!(client_data["type"], "webauthn.create");
+        assert_eq!(
+            client_data["challenge"],
+            URL_SAFE_NO_PAD.encode(b"registration")
+        );
+        assert_eq!(client_data["origin"], "https://example.com");
+
+        // Map of 3 starting with "fmt": "none"
+        assert_eq!(&credential.attestation_object[..10], b"\xa3cfmtdnone");
+        let rp_id_hash = digest(&SHA256, b"example.com");
+        let auth_data_start = credential
+            .attestation_object
+            .windows(32)
+            .position(|w| w == rp_id_hash.as_ref())
+            .unwrap();
+        let auth_data = &credential.attestation_object[auth_data_start..];
+        assert_eq!(auth_data[32], 0x45);
+        assert_eq!(&auth_data[55..71], credential.id.as_slice());
+
+        let error = authenticator
+            .create(&CreationOptions {
+                exclude_credentials: serde_json::from_value(serde_json::json!([
+                    { "type": "public-key", "id": URL_SAFE_NO_PAD.encode(&credential.id) }
+                ]))
+                .unwrap(),
+                ..creation_options()
+            })
+            .await;
+        assert!(error.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_assertion_verifies_with_public_key() {
+        let authenticator = SoftwareAuthenticator::new("https://example.com");
+        let registered = authenticator.create(&creation_options()).await.unwrap();
+
+        let assertion = authenticator
+            .get(&request_options(std::slice::from_ref(&registered.id)))
+            .await
+            .unwrap();
+
+        assert_eq!(assertion.id, registered.id);
+        assert_eq!(assertion.user_handle.as_deref(), Some(&b"user_123"[..]));
+        assert_eq!(
+            assertion.authenticator_data[32],
+            USER_PRESENT | USER_VERIFIED
+        );
+        assert_eq!(&assertion.authenticator_data[33..], &1u32.to_be_bytes());
+
+        let mut signed = assertion.authenticator_data.clone();
+        signed.extend_from_slice(digest(&SHA256, &assertion.client_data_json).as_ref());
+        let public_key = authenticator.public_key(&registered.id).unwrap();
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, public_key)
+            .verify(&signed, &assertion.signature)
+            .unwrap();
+
+        // Credentials of other relying parties or not in the allow list can't be used
+        assert!(authenticator
+            .get(&request_options(&[vec![1, 2, 3]]))
+            .await
+            .is_err());
+        let other = SoftwareAuthenticator::new("https://other.example");
+        assert!(other.get(&request_options(&[])).await.is_err());
+    }
+}
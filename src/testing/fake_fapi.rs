@@ -0,0 +1,1419 @@
+use super::fixtures::EnvironmentBuilder;
+use crate::configuration::ClerkFapiConfiguration;
+use crate::models::client_period_session::Status as SessionStatus;
+use crate::models::client_period_sign_in::{
+    Object as SignInObject, Status as SignInStatus, SupportedIdentifiers,
+};
+use crate::models::client_period_sign_up::{Object as SignUpObject, Status as SignUpStatus};
+use crate::models::stubs_period_sign_in_factor::Strategy;
+use crate::models::{
+    ClientPeriodClient as Client, ClientPeriodEmailAddress as EmailAddress,
+    ClientPeriodEnvironment as Environment, ClientPeriodOrganization as Organization,
+    ClientPeriodOrganizationMembership as OrganizationMembership, ClientPeriodSession as Session,
+    ClientPeriodSignIn as SignIn, ClientPeriodSignUp as SignUp, ClientPeriodUser as User,
+    ClientSignInUserData as SignInUserData, StubsPeriodSignInFactor as SignInFactor,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use parking_lot::Mutex;
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// The code accepted by every email code verification of the fake
+pub const VERIFICATION_CODE: &str = "424242";
+
+/// Development key of the fake instance, the proxy URL overrides its domain
+pub(super) const PUBLISHABLE_KEY: &str = "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k";
+
+/// ID of the key session tokens are signed with
+const KEY_ID: &str = "ins_fake";
+
+/// Lifetime of the session tokens, like the Frontend API
+const TOKEN_LIFETIME_SECS: i64 = 60;
+
+/// How long sign ins and sign ups can be continued
+const ATTEMPT_LIFETIME_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+/// A user the fake is seeded with, see [`FakeFapi::add_user`]
+#[derive(Debug, Clone)]
+pub struct FakeUser {
+    email_address: String,
+    password: Option<String>,
+    first_name: Option<String>,
+    last_name: Option<String>,
+}
+
+impl FakeUser {
+    /// A user that signs in with an email code sent to `email_address`
+    pub fn new(email_address: impl Into<String>) -> Self {
+        Self {
+            email_address: email_address.into(),
+            password: None,
+            first_name: None,
+            last_name: None,
+        }
+    }
+
+    /// Also allows signing in with a password
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Sets the first and last name shown on the user
+    pub fn with_name(
+        mut self,
+        first_name: impl Into<String>,
+        last_name: impl Into<String>,
+    ) -> Self {
+        self.first_name = Some(first_name.into());
+        self.last_name = Some(last_name.into());
+        self
+    }
+}
+
+/// An in-process fake of the Frontend API for integration tests
+///
+/// Every [`ClerkFapiConfiguration`] from [`FakeFapi::config`] gets its own
+/// client, like separate devices. The server stops when the fake is dropped.
+pub struct FakeFapi {
+    url: String,
+    state: Arc<Mutex<State>>,
+    server: JoinHandle<()>,
+}
+
+impl FakeFapi {
+    /// Starts the fake on a free local port
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let state = Arc::new(Mutex::new(State::new(url.clone())));
+        let server = tokio::spawn(serve(listener, state.clone()));
+        Ok(Self { url, state, server })
+    }
+
+    /// URL of the fake, also the issuer of its session tokens
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// A configuration pointing to the fake with `proxy_url`, with its own
+    /// in memory store
+    pub fn config(&self) -> ClerkFapiConfiguration {
+        ClerkFapiConfiguration::new(PUBLISHABLE_KEY.to_string(), Some(self.url.clone()), None)
+            .expect("the fake publishable key is valid")
+    }
+
+    /// Adds a user that can sign in, returns its ID
+    pub fn add_user(&self, user: FakeUser) -> String {
+        self.state.lock().add_user(user)
+    }
+
+    /// Adds an organization, returns its ID
+    pub fn add_organization(&self, name: &str) -> String {
+        self.state.lock().add_organization(name)
+    }
+
+    /// Makes the user a member of the organization with the given role, e.g. `org:admin`
+    pub fn add_membership(&self, organization_id: &str, user_id: &str, role: &str) {
+        self.state
+            .lock()
+            .add_membership(organization_id, user_id, role);
+    }
+
+    /// Only allows a single session per client, signing in again fails with `session_exists`
+    pub fn set_single_session_mode(&self, enabled: bool) {
+        let mut state = self.state.lock();
+        if let Some(auth_config) = state.environment.auth_config.as_mut() {
+            auth_config.single_session_mode = enabled;
+        }
+    }
+
+    /// Adds a JWT template, its claims are added to the tokens created with it
+    pub fn add_jwt_template(&self, name: &str, claims: JsonMap<String, JsonValue>) {
+        self.state.lock().templates.insert(name.to_string(), claims);
+    }
+
+    /// Returns the user as the Frontend API would
+    pub fn user(&self, user_id: &str) -> Option<User> {
+        let state = self.state.lock();
+        state.user_record(user_id).map(|user| state.user(user))
+    }
+}
+
+impl Drop for FakeFapi {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn serve(listener: TcpListener, state: Arc<Mutex<State>>) {
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            // Errors like running out of file descriptors persist for a
+            // while, back off instead of spinning on them
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            continue;
+        };
+        let state = state.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |request| handle(state.clone(), request));
+            // A broken connection only affects the request it carried
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+        });
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = body
+        .collect()
+        .await
+        .map(|body| body.to_bytes())
+        .unwrap_or_default();
+
+    // Form fields and query parameters are looked up the same way
+    let mut params: Params = url::form_urlencoded::parse(&body).into_owned().collect();
+    if let Some(query) = parts.uri.query() {
+        params.extend(url::form_urlencoded::parse(query.as_bytes()).into_owned());
+    }
+    let authorization = parts
+        .headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let reply = state
+        .lock()
+        .route(&parts.method, parts.uri.path(), authorization, &params);
+
+    let mut response = Response::new(Full::new(Bytes::from(reply.body.to_string())));
+    *response.status_mut() = reply.status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, "application/json".parse().unwrap());
+    if let Some(token) = reply.authorization.and_then(|t| t.parse().ok()) {
+        response.headers_mut().insert(AUTHORIZATION, token);
+    }
+    Ok(response)
+}
+
+type Params = HashMap<String, String>;
+
+struct Reply {
+    status: StatusCode,
+    body: JsonValue,
+    authorization: Option<String>,
+}
+
+/// An error response in the shape of the Frontend API
+struct Rejection {
+    status: StatusCode,
+    code: &'static str,
+    message: &'static str,
+}
+
+impl Rejection {
+    fn unprocessable(code: &'static str, message: &'static str) -> Self {
+        Self {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            code,
+            message,
+        }
+    }
+
+    fn not_found() -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            code: "resource_not_found",
+            message: "Resource not found",
+        }
+    }
+
+    fn signed_out() -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            code: "signed_out",
+            message: "You are signed out",
+        }
+    }
+
+    fn body(&self) -> JsonValue {
+        json!({
+            "errors": [{
+                "message": self.message,
+                "long_message": self.message,
+                "code": self.code,
+            }]
+        })
+    }
+}
+
+struct UserRecord {
+    id: String,
+    email_address_id: String,
+    email_address: String,
+    password: Option<String>,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    created_at: i64,
+}
+
+struct OrganizationRecord {
+    id: String,
+    name: String,
+    slug: String,
+    created_at: i64,
+}
+
+struct MembershipRecord {
+    id: String,
+    organization_id: String,
+    user_id: String,
+    role: String,
+}
+
+struct SessionRecord {
+    id: String,
+    user_id: String,
+    last_active_organization_id: Option<String>,
+    created_at: i64,
+}
+
+struct SignInRecord {
+    id: String,
+    user_id: Option<String>,
+    identifier: Option<String>,
+    status: SignInStatus,
+    code_sent: bool,
+    created_session_id: Option<String>,
+    created_at: i64,
+}
+
+#[derive(Default)]
+struct SignUpRecord {
+    id: String,
+    email_address: Option<String>,
+    password: Option<String>,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    code_sent: bool,
+    verified: bool,
+    created_user_id: Option<String>,
+    created_session_id: Option<String>,
+    created_at: i64,
+}
+
+impl SignUpRecord {
+    fn missing_fields(&self) -> Vec<String> {
+        let mut missing = Vec::new();
+        if self.email_address.is_none() {
+            missing.push("email_address".to_string());
+        }
+        if self.password.is_none() {
+            missing.push("password".to_string());
+        }
+        missing
+    }
+
+    fn unverified_fields(&self) -> Vec<String> {
+        match (&self.email_address, self.verified) {
+            (Some(_), false) => vec!["email_address".to_string()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+struct ClientRecord {
+    id: String,
+    sessions: Vec<SessionRecord>,
+    last_active_session_id: Option<String>,
+    sign_in: Option<SignInRecord>,
+    sign_up: Option<SignUpRecord>,
+    created_at: i64,
+    updated_at: i64,
+}
+
+struct State {
+    url: String,
+    next_id: u64,
+    key_pair: EcdsaKeyPair,
+    environment: Environment,
+    users: Vec<UserRecord>,
+    organizations: Vec<OrganizationRecord>,
+    memberships: Vec<MembershipRecord>,
+    templates: HashMap<String, JsonMap<String, JsonValue>>,
+    /// Clients by the token in their authorization header
+    clients: HashMap<String, ClientRecord>,
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// The slug the Frontend API derives from an organization name, `Acme Inc` becomes `acme-inc`
+pub(super) fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+impl State {
+    fn new(url: String) -> Self {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)
+            .expect("generating a P-256 key");
+        let key_pair =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+                .expect("parsing the generated key");
+        Self {
+            url,
+            next_id: 0,
+            key_pair,
+            environment: EnvironmentBuilder::new().build(),
+            users: Vec::new(),
+            organizations: Vec::new(),
+            memberships: Vec::new(),
+            templates: HashMap::new(),
+            clients: HashMap::new(),
+        }
+    }
+
+    /// Returns a new ID with the prefix the Frontend API uses for the object
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}_{}", prefix, self.next_id)
+    }
+
+    fn add_user(&mut self, user: FakeUser) -> String {
+        let id = self.next_id("user");
+        let email_address_id = self.next_id("idn");
+        self.users.push(UserRecord {
+            id: id.clone(),
+            email_address_id,
+            email_address: user.email_address,
+            password: user.password,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            created_at: now(),
+        });
+        id
+    }
+
+    fn add_organization(&mut self, name: &str) -> String {
+        let id = self.next_id("org");
+        let mut slug = slugify(name);
+        if self.organizations.iter().any(|o| o.slug == slug) {
+            slug = format!("{}-{}", slug, self.next_id);
+        }
+        self.organizations.push(OrganizationRecord {
+            id: id.clone(),
+            name: name.to_string(),
+            slug,
+            created_at: now(),
+        });
+        id
+    }
+
+    fn add_membership(&mut self, organization_id: &str, user_id: &str, role: &str) {
+        let id = self.next_id("orgmem");
+        self.memberships.push(MembershipRecord {
+            id,
+            organization_id: organization_id.to_string(),
+            user_id: user_id.to_string(),
+            role: role.to_string(),
+        });
+    }
+
+    fn user_record(&self, user_id: &str) -> Option<&UserRecord> {
+        self.users.iter().find(|u| u.id == user_id)
+    }
+
+    fn user_by_email(&self, email_address: &str) -> Option<&UserRecord> {
+        self.users
+            .iter()
+            .find(|u| u.email_address.eq_ignore_ascii_case(email_address))
+    }
+
+    fn membership(&self, user_id: &str, organization_id: &str) -> Option<&MembershipRecord> {
+        self.memberships
+            .iter()
+            .find(|m| m.user_id == user_id && m.organization_id == organization_id)
+    }
+
+    fn client_record(&mut self, token: &str) -> &mut ClientRecord {
+        self.clients
+            .get_mut(token)
+            .expect("the client is created before routing")
+    }
+
+    fn route(
+        &mut self,
+        method: &Method,
+        path: &str,
+        authorization: Option<String>,
+        params: &Params,
+    ) -> Reply {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let known_token = authorization.filter(|token| self.clients.contains_key(token));
+
+        // Reads that don't need a client
+        let body = match (method, segments.as_slice()) {
+            (&Method::GET, [".well-known", "jwks.json"]) => Some(self.jwks()),
+            (&Method::GET, ["v1", "environment"]) => {
+                Some(serde_json::to_value(&self.environment).unwrap_or_default())
+            }
+            (&Method::GET, ["v1", "client"]) => Some(json!({
+                "response": known_token.as_deref().map(|token| self.client(token)),
+                "client": null,
+            })),
+            _ => None,
+        };
+        if let Some(body) = body {
+            return Reply {
+                status: StatusCode::OK,
+                body,
+                authorization: known_token,
+            };
+        }
+
+        // Everything else works on the client of the request, a new one if it has none
+        let token = match known_token {
+            Some(token) => token,
+            None => self.create_client(),
+        };
+        let result = self.client_route(method, &segments, &token, params);
+        if result.is_ok() {
+            self.client_record(&token).updated_at = now();
+        }
+        match result {
+            Ok(body) => Reply {
+                status: StatusCode::OK,
+                body,
+                authorization: Some(token),
+            },
+            Err(rejection) => Reply {
+                status: rejection.status,
+                body: rejection.body(),
+                authorization: Some(token),
+            },
+        }
+    }
+
+    fn client_route(
+        &mut self,
+        method: &Method,
+        segments: &[&str],
+        token: &str,
+        params: &Params,
+    ) -> Result<JsonValue, Rejection> {
+        let param = |name: &str| params.get(name).map(String::as_str);
+
+        let response = match (method, segments) {
+            (&Method::POST, ["v1", "client", "sign_ins"]) => self.create_sign_in(token, params)?,
+            (&Method::GET, ["v1", "client", "sign_ins", id]) => self.sign_in(token, id)?,
+            (&Method::POST, ["v1", "client", "sign_ins", id, "prepare_first_factor"]) => {
+                self.prepare_first_factor(token, id, param("strategy"))?
+            }
+            (&Method::POST, ["v1", "client", "sign_ins", id, "attempt_first_factor"]) => {
+                self.attempt_first_factor(token, id, params)?
+            }
+            (&Method::POST, ["v1", "client", "sign_ups"]) => self.sign_ups(token, None, params)?,
+            (&Method::GET, ["v1", "client", "sign_ups", id]) => self.sign_up(token, id)?,
+            (&Method::PATCH, ["v1", "client", "sign_ups", id]) => {
+                self.sign_ups(token, Some(id), params)?
+            }
+            (&Method::POST, ["v1", "client", "sign_ups", id, "prepare_verification"]) => {
+                self.prepare_verification(token, id, param("strategy"))?
+            }
+            (&Method::POST, ["v1", "client", "sign_ups", id, "attempt_verification"]) => {
+                self.attempt_verification(token, id, param("code"))?
+            }
+            (&Method::DELETE, ["v1", "client", "sessions"]) => {
+                self.client_record(token).sessions.clear();
+                self.client_record(token).last_active_session_id = None;
+                serde_json::to_value(self.client(token)).unwrap_or_default()
+            }
+            (&Method::POST, ["v1", "client", "sessions", id, "touch"]) => {
+                self.touch_session(token, id, param("active_organization_id"))?
+            }
+            (&Method::POST, ["v1", "client", "sessions", id, "remove" | "end"]) => {
+                self.remove_session(token, id)?
+            }
+            (&Method::POST, ["v1", "client", "sessions", id, "tokens"]) => {
+                return self.create_token(token, id, param("organization_id"), None);
+            }
+            (&Method::POST, ["v1", "client", "sessions", id, "tokens", template]) => {
+                return self.create_token(token, id, None, Some(template));
+            }
+            (&Method::GET, ["v1", "me"]) => {
+                let user_id = self.active_user_id(token)?;
+                let user = self
+                    .user_record(&user_id)
+                    .ok_or_else(Rejection::signed_out)?;
+                serde_json::to_value(self.user(user)).unwrap_or_default()
+            }
+            (&Method::GET, ["v1", "me", "organization_memberships"]) => {
+                self.organization_memberships(token, param("limit"), param("offset"))?
+            }
+            (&Method::POST, ["v1", "organizations"]) => {
+                self.create_organization(token, param("name"))?
+            }
+            _ => return Err(Rejection::not_found()),
+        };
+
+        Ok(json!({ "response": response, "client": self.client(token) }))
+    }
+
+    fn create_client(&mut self) -> String {
+        let id = self.next_id("client");
+        let token = format!("fake_client_token_{}", id);
+        self.clients.insert(
+            token.clone(),
+            ClientRecord {
+                id,
+                sessions: Vec::new(),
+                last_active_session_id: None,
+                sign_in: None,
+                sign_up: None,
+                created_at: now(),
+                updated_at: now(),
+            },
+        );
+        token
+    }
+
+    fn active_user_id(&mut self, token: &str) -> Result<String, Rejection> {
+        let client = self.client_record(token);
+        let session_id = client.last_active_session_id.clone();
+        client
+            .sessions
+            .iter()
+            .find(|s| Some(&s.id) == session_id.as_ref())
+            .map(|s| s.user_id.clone())
+            .ok_or_else(Rejection::signed_out)
+    }
+
+    /// Signs the user in on the client, returns the ID of the new session
+    fn create_session(&mut self, token: &str, user_id: &str) -> Result<String, Rejection> {
+        let single_session_mode = self
+            .environment
+            .auth_config
+            .as_ref()
+            .is_some_and(|c| c.single_session_mode);
+        if single_session_mode && !self.client_record(token).sessions.is_empty() {
+            return Err(Rejection::unprocessable(
+                "session_exists",
+                "You're already signed in.",
+            ));
+        }
+
+        let id = self.next_id("sess");
+        let client = self.client_record(token);
+        client.sessions.push(SessionRecord {
+            id: id.clone(),
+            user_id: user_id.to_string(),
+            last_active_organization_id: None,
+            created_at: now(),
+        });
+        client.last_active_session_id = Some(id.clone());
+        Ok(id)
+    }
+
+    fn create_sign_in(&mut self, token: &str, params: &Params) -> Result<JsonValue, Rejection> {
+        let mut sign_in = SignInRecord {
+            id: self.next_id("sia"),
+            user_id: None,
+            identifier: None,
+            status: SignInStatus::NeedsIdentifier,
+            code_sent: false,
+            created_session_id: None,
+            created_at: now(),
+        };
+
+        if let Some(identifier) = params.get("identifier") {
+            let user = self
+                .user_by_email(identifier)
+                .ok_or(Rejection::unprocessable(
+                    "form_identifier_not_found",
+                    "Couldn't find your account.",
+                ))?;
+            sign_in.user_id = Some(user.id.clone());
+            sign_in.identifier = Some(identifier.clone());
+            sign_in.status = SignInStatus::NeedsFirstFactor;
+
+            // A password with the identifier completes the sign in right away
+            if let Some(password) = params.get("password") {
+                self.complete_with_password(token, &mut sign_in, password)?;
+            }
+        }
+
+        let response = self.render_sign_in(&sign_in);
+        self.client_record(token).sign_in = Some(sign_in);
+        Ok(serde_json::to_value(response).unwrap_or_default())
+    }
+
+    fn complete_with_password(
+        &mut self,
+        token: &str,
+        sign_in: &mut SignInRecord,
+        password: &str,
+    ) -> Result<(), Rejection> {
+        let user_id = sign_in.user_id.clone().unwrap_or_default();
+        let matches = self
+            .user_record(&user_id)
+            .and_then(|user| user.password.as_deref())
+            == Some(password);
+        if !matches {
+            return Err(Rejection::unprocessable(
+                "form_password_incorrect",
+                "Password is incorrect. Try again, or use another method.",
+            ));
+        }
+        self.complete_sign_in(token, sign_in)
+    }
+
+    fn complete_sign_in(
+        &mut self,
+        token: &str,
+        sign_in: &mut SignInRecord,
+    ) -> Result<(), Rejection> {
+        let user_id = sign_in.user_id.clone().unwrap_or_default();
+        sign_in.created_session_id = Some(self.create_session(token, &user_id)?);
+        sign_in.status = SignInStatus::Complete;
+        Ok(())
+    }
+
+    /// Takes the sign in of the client out for updating, it's put back by the caller
+    fn take_sign_in(&mut self, token: &str, id: &str) -> Result<SignInRecord, Rejection> {
+        let client = self.client_record(token);
+        match client.sign_in.take() {
+            Some(sign_in) if sign_in.id == id => Ok(sign_in),
+            other => {
+                client.sign_in = other;
+                Err(Rejection::not_found())
+            }
+        }
+    }
+
+    /// Runs `update` on the sign in and stores it even if the update fails
+    fn update_sign_in(
+        &mut self,
+        token: &str,
+        id: &str,
+        update: impl FnOnce(&mut Self, &mut SignInRecord) -> Result<(), Rejection>,
+    ) -> Result<JsonValue, Rejection> {
+        let mut sign_in = self.take_sign_in(token, id)?;
+        let result = update(self, &mut sign_in);
+        let response = self.render_sign_in(&sign_in);
+        self.client_record(token).sign_in = Some(sign_in);
+        result.map(|()| serde_json::to_value(response).unwrap_or_default())
+    }
+
+    fn sign_in(&mut self, token: &str, id: &str) -> Result<JsonValue, Rejection> {
+        self.update_sign_in(token, id, |_, _| Ok(()))
+    }
+
+    fn prepare_first_factor(
+        &mut self,
+        token: &str,
+        id: &str,
+        strategy: Option<&str>,
+    ) -> Result<JsonValue, Rejection> {
+        self.update_sign_in(token, id, |_, sign_in| match strategy {
+            Some("email_code") if sign_in.user_id.is_some() => {
+                sign_in.code_sent = true;
+                Ok(())
+            }
+            _ => Err(Rejection::unprocessable(
+                "strategy_for_user_invalid",
+                "The verification strategy is not valid for this account.",
+            )),
+        })
+    }
+
+    fn attempt_first_factor(
+        &mut self,
+        token: &str,
+        id: &str,
+        params: &Params,
+    ) -> Result<JsonValue, Rejection> {
+        let strategy = params.get("strategy").cloned();
+        let code = params.get("code").cloned();
+        let password = params.get("password").cloned();
+        self.update_sign_in(token, id, |state, sign_in| {
+            match (strategy.as_deref(), code, password) {
+                (Some("email_code"), Some(code), _) => {
+                    if !sign_in.code_sent {
+                        return Err(Rejection::unprocessable(
+                            "verification_missing",
+                            "The verification was not prepared.",
+                        ));
+                    }
+                    if code != VERIFICATION_CODE {
+                        return Err(Rejection::unprocessable(
+                            "form_code_incorrect",
+                            "Incorrect code",
+                        ));
+                    }
+                    state.complete_sign_in(token, sign_in)
+                }
+                (Some("password"), _, Some(password)) => {
+                    state.complete_with_password(token, sign_in, &password)
+                }
+                _ => Err(Rejection::unprocessable(
+                    "strategy_for_user_invalid",
+                    "The verification strategy is not valid for this account.",
+                )),
+            }
+        })
+    }
+
+    fn sign_ups(
+        &mut self,
+        token: &str,
+        id: Option<&str>,
+        params: &Params,
+    ) -> Result<JsonValue, Rejection> {
+        if let Some(email_address) = params.get("email_address") {
+            if self.user_by_email(email_address).is_some() {
+                return Err(Rejection::unprocessable(
+                    "form_identifier_exists",
+                    "That email address is taken. Please try another.",
+                ));
+            }
+        }
+
+        let mut sign_up = match id {
+            Some(id) => self.take_sign_up(token, id)?,
+            None => SignUpRecord {
+                id: self.next_id("sua"),
+                created_at: now(),
+                ..Default::default()
+            },
+        };
+        for (name, field) in [
+            ("email_address", &mut sign_up.email_address),
+            ("password", &mut sign_up.password),
+            ("first_name", &mut sign_up.first_name),
+            ("last_name", &mut sign_up.last_name),
+        ] {
+            if let Some(value) = params.get(name) {
+                *field = Some(value.clone());
+            }
+        }
+        if params.contains_key("email_address") {
+            sign_up.code_sent = false;
+            sign_up.verified = false;
+        }
+        self.store_sign_up(token, sign_up)
+    }
+
+    fn take_sign_up(&mut self, token: &str, id: &str) -> Result<SignUpRecord, Rejection> {
+        let client = self.client_record(token);
+        match client.sign_up.take() {
+            Some(sign_up) if sign_up.id == id => Ok(sign_up),
+            other => {
+                client.sign_up = other;
+                Err(Rejection::not_found())
+            }
+        }
+    }
+
+    /// Completes the sign up once nothing is missing, and stores it on the client
+    fn store_sign_up(
+        &mut self,
+        token: &str,
+        mut sign_up: SignUpRecord,
+    ) -> Result<JsonValue, Rejection> {
+        let ready = sign_up.missing_fields().is_empty() && sign_up.unverified_fields().is_empty();
+        let mut result = Ok(());
+        if ready && sign_up.created_user_id.is_none() {
+            let user_id = self.add_user(FakeUser {
+                email_address: sign_up.email_address.clone().unwrap_or_default(),
+                password: sign_up.password.clone(),
+                first_name: sign_up.first_name.clone(),
+                last_name: sign_up.last_name.clone(),
+            });
+            match self.create_session(token, &user_id) {
+                Ok(session_id) => sign_up.created_session_id = Some(session_id),
+                Err(rejection) => result = Err(rejection),
+            }
+            sign_up.created_user_id = Some(user_id);
+        }
+
+        let response = self.render_sign_up(&sign_up);
+        self.client_record(token).sign_up = Some(sign_up);
+        result.map(|()| serde_json::to_value(response).unwrap_or_default())
+    }
+
+    fn sign_up(&mut self, token: &str, id: &str) -> Result<JsonValue, Rejection> {
+        let sign_up = self.take_sign_up(token, id)?;
+        self.store_sign_up(token, sign_up)
+    }
+
+    fn prepare_verification(
+        &mut self,
+        token: &str,
+        id: &str,
+        strategy: Option<&str>,
+    ) -> Result<JsonValue, Rejection> {
+        let mut sign_up = self.take_sign_up(token, id)?;
+        let prepared = strategy == Some("email_code") && sign_up.email_address.is_some();
+        sign_up.code_sent |= prepared;
+        let response = self.store_sign_up(token, sign_up);
+        if !prepared {
+            return Err(Rejection::unprocessable(
+                "verification_strategy_invalid",
+                "Only email codes can be sent to this sign up.",
+            ));
+        }
+        response
+    }
+
+    fn attempt_verification(
+        &mut self,
+        token: &str,
+        id: &str,
+        code: Option<&str>,
+    ) -> Result<JsonValue, Rejection> {
+        let mut sign_up = self.take_sign_up(token, id)?;
+        let rejection = if !sign_up.code_sent {
+            Some(Rejection::unprocessable(
+                "verification_missing",
+                "The verification was not prepared.",
+            ))
+        } else if code != Some(VERIFICATION_CODE) {
+            Some(Rejection::unprocessable(
+                "form_code_incorrect",
+                "Incorrect code",
+            ))
+        } else {
+            sign_up.verified = true;
+            None
+        };
+        let response = self.store_sign_up(token, sign_up);
+        match rejection {
+            Some(rejection) => Err(rejection),
+            None => response,
+        }
+    }
+
+    fn touch_session(
+        &mut self,
+        token: &str,
+        id: &str,
+        active_organization_id: Option<&str>,
+    ) -> Result<JsonValue, Rejection> {
+        let user_id = self
+            .client_record(token)
+            .sessions
+            .iter()
+            .find(|s| s.id == id)
+            .map(|s| s.user_id.clone())
+            .ok_or_else(Rejection::not_found)?;
+        // Without an organization the personal account becomes active
+        let organization_id = active_organization_id.filter(|id| !id.is_empty());
+        if let Some(organization_id) = organization_id {
+            self.membership(&user_id, organization_id)
+                .ok_or_else(Rejection::not_found)?;
+        }
+
+        let client = self.client_record(token);
+        client.last_active_session_id = Some(id.to_string());
+        let session = client.sessions.iter_mut().find(|s| s.id == id).unwrap();
+        session.last_active_organization_id = organization_id.map(str::to_string);
+        Ok(self.session_value(token, id))
+    }
+
+    fn remove_session(&mut self, token: &str, id: &str) -> Result<JsonValue, Rejection> {
+        let client = self.client_record(token);
+        let index = client
+            .sessions
+            .iter()
+            .position(|s| s.id == id)
+            .ok_or_else(Rejection::not_found)?;
+        let removed = client.sessions.remove(index);
+        if client.last_active_session_id.as_deref() == Some(id) {
+            client.last_active_session_id = client.sessions.last().map(|s| s.id.clone());
+        }
+
+        let mut session = self.session(&removed);
+        session.status = Some(SessionStatus::Removed);
+        Ok(serde_json::to_value(session).unwrap_or_default())
+    }
+
+    fn session_value(&mut self, token: &str, id: &str) -> JsonValue {
+        let client = &self.clients[token];
+        let session = client.sessions.iter().find(|s| s.id == id);
+        serde_json::to_value(session.map(|s| self.session(s))).unwrap_or_default()
+    }
+
+    fn create_token(
+        &mut self,
+        token: &str,
+        session_id: &str,
+        organization_id: Option<&str>,
+        template: Option<&str>,
+    ) -> Result<JsonValue, Rejection> {
+        let session = self.clients[token]
+            .sessions
+            .iter()
+            .find(|s| s.id == session_id)
+            .ok_or_else(Rejection::not_found)?;
+        let organization_id = organization_id
+            .map(str::to_string)
+            .or_else(|| session.last_active_organization_id.clone());
+
+        let now = chrono::Utc::now().timestamp();
+        let mut claims = json!({
+            "azp": self.url,
+            "exp": now + TOKEN_LIFETIME_SECS,
+            "iat": now,
+            "iss": self.url,
+            "nbf": now - 10,
+            "sid": session.id,
+            "sub": session.user_id,
+        });
+        if let Some(organization_id) = organization_id {
+            let membership = self
+                .membership(&session.user_id, &organization_id)
+                .ok_or_else(Rejection::not_found)?;
+            let organization = self
+                .organizations
+                .iter()
+                .find(|o| o.id == organization_id)
+                .ok_or_else(Rejection::not_found)?;
+            claims["org_id"] = json!(organization.id);
+            claims["org_slug"] = json!(organization.slug);
+            claims["org_role"] = json!(membership.role);
+        }
+        if let Some(template) = template {
+            let custom = self
+                .templates
+                .get(template)
+                .ok_or_else(Rejection::not_found)?;
+            for (name, value) in custom {
+                claims[name] = value.clone();
+            }
+        }
+
+        Ok(json!({ "object": "token", "jwt": self.sign(&claims) }))
+    }
+
+    fn organization_memberships(
+        &mut self,
+        token: &str,
+        limit: Option<&str>,
+        offset: Option<&str>,
+    ) -> Result<JsonValue, Rejection> {
+        let user_id = self.active_user_id(token)?;
+        let memberships = self.user_memberships(&user_id);
+        let offset = offset.and_then(|o| o.parse().ok()).unwrap_or(0);
+        let limit = limit.and_then(|l| l.parse().ok()).unwrap_or(10);
+        Ok(json!({
+            "total_count": memberships.len(),
+            "data": memberships.into_iter().skip(offset).take(limit).collect::<Vec<_>>(),
+        }))
+    }
+
+    fn create_organization(
+        &mut self,
+        token: &str,
+        name: Option<&str>,
+    ) -> Result<JsonValue, Rejection> {
+        let user_id = self.active_user_id(token)?;
+        let name = name.ok_or(Rejection::unprocessable(
+            "form_param_missing",
+            "Enter an organization name.",
+        ))?;
+        let id = self.add_organization(name);
+        self.add_membership(&id, &user_id, "org:admin");
+        Ok(serde_json::to_value(self.organization(&id)).unwrap_or_default())
+    }
+
+    fn jwks(&self) -> JsonValue {
+        let point = self.key_pair.public_key().as_ref();
+        json!({
+            "keys": [{
+                "use": "sig",
+                "kty": "EC",
+                "kid": KEY_ID,
+                "alg": "ES256",
+                "crv": "P-256",
+                "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+                "y": URL_SAFE_NO_PAD.encode(&point[33..65]),
+            }]
+        })
+    }
+
+    fn sign(&self, claims: &JsonValue) -> String {
+        let header = json!({ "alg": "ES256", "kid": KEY_ID, "typ": "JWT" });
+        let message = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let signature = self
+            .key_pair
+            .sign(&SystemRandom::new(), message.as_bytes())
+            .expect("signing with the generated key");
+        format!("{}.{}", message, URL_SAFE_NO_PAD.encode(signature.as_ref()))
+    }
+
+    fn client(&self, token: &str) -> Client {
+        let client = &self.clients[token];
+        Client {
+            id: Some(client.id.clone()),
+            sign_in: client
+                .sign_in
+                .as_ref()
+                .map(|sign_in| Box::new(self.render_sign_in(sign_in))),
+            sign_up: client
+                .sign_up
+                .as_ref()
+                .map(|sign_up| Box::new(self.render_sign_up(sign_up))),
+            sessions: client.sessions.iter().map(|s| self.session(s)).collect(),
+            last_active_session_id: client.last_active_session_id.clone(),
+            created_at: client.created_at,
+            updated_at: client.updated_at,
+            ..Default::default()
+        }
+    }
+
+    fn session(&self, session: &SessionRecord) -> Session {
+        let user = self.user_record(&session.user_id).map(|u| self.user(u));
+        Session {
+            id: Some(session.id.clone()),
+            status: Some(SessionStatus::Active),
+            last_active_organization_id: session.last_active_organization_id.clone(),
+            last_active_at: Some(now()),
+            expire_at: Some(now() + ATTEMPT_LIFETIME_MILLIS * 7),
+            abandon_at: Some(now() + ATTEMPT_LIFETIME_MILLIS * 30),
+            user: Some(user.map(Box::new)),
+            created_at: Some(session.created_at),
+            updated_at: Some(now()),
+            ..Default::default()
+        }
+    }
+
+    fn user(&self, user: &UserRecord) -> User {
+        User {
+            id: Some(user.id.clone()),
+            primary_email_address_id: Some(Some(user.email_address_id.clone())),
+            first_name: Some(user.first_name.clone()),
+            last_name: Some(user.last_name.clone()),
+            email_addresses: Some(vec![EmailAddress {
+                id: Some(user.email_address_id.clone()),
+                email_address: user.email_address.clone(),
+                created_at: user.created_at,
+                updated_at: user.created_at,
+                ..Default::default()
+            }]),
+            organization_memberships: Some(self.user_memberships(&user.id)),
+            password_enabled: Some(user.password.is_some()),
+            two_factor_enabled: Some(false),
+            create_organization_enabled: Some(true),
+            created_at: Some(user.created_at),
+            updated_at: Some(user.created_at),
+            ..Default::default()
+        }
+    }
+
+    fn user_memberships(&self, user_id: &str) -> Vec<OrganizationMembership> {
+        self.memberships
+            .iter()
+            .filter(|m| m.user_id == user_id)
+            .map(|m| OrganizationMembership {
+                id: Some(m.id.clone()),
+                role: Some(m.role.clone()),
+                organization: self.organization(&m.organization_id).map(Box::new),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn organization(&self, id: &str) -> Option<Organization> {
+        let organization = self.organizations.iter().find(|o| o.id == id)?;
+        Some(Organization {
+            id: Some(organization.id.clone()),
+            name: Some(organization.name.clone()),
+            slug: Some(organization.slug.clone()),
+            members_count: Some(
+                self.memberships
+                    .iter()
+                    .filter(|m| m.organization_id == id)
+                    .count() as i64,
+            ),
+            created_at: Some(organization.created_at),
+            updated_at: Some(organization.created_at),
+            ..Default::default()
+        })
+    }
+
+    fn render_sign_in(&self, sign_in: &SignInRecord) -> SignIn {
+        let user = sign_in
+            .user_id
+            .as_deref()
+            .and_then(|id| self.user_record(id));
+        let factors = user.map(|user| {
+            let mut email_code = SignInFactor::new(Strategy::EmailCode);
+            email_code.email_address_id = Some(user.email_address_id.clone());
+            email_code.safe_identifier = Some(user.email_address.clone());
+            let mut factors = vec![email_code];
+            if user.password.is_some() {
+                factors.push(SignInFactor::new(Strategy::Password));
+            }
+            factors
+        });
+
+        SignIn {
+            object: SignInObject::SignInAttempt,
+            id: sign_in.id.clone(),
+            status: sign_in.status,
+            supported_identifiers: vec![SupportedIdentifiers::EmailAddress],
+            identifier: sign_in.identifier.clone(),
+            user_data: user.map(|user| {
+                Some(Box::new(SignInUserData {
+                    first_name: Some(user.first_name.clone()),
+                    last_name: Some(user.last_name.clone()),
+                    ..Default::default()
+                }))
+            }),
+            supported_first_factors: factors,
+            supported_second_factors: Some(Vec::new()),
+            created_session_id: sign_in.created_session_id.clone(),
+            abandon_at: sign_in.created_at + ATTEMPT_LIFETIME_MILLIS,
+            ..Default::default()
+        }
+    }
+
+    fn render_sign_up(&self, sign_up: &SignUpRecord) -> SignUp {
+        let status = if sign_up.created_session_id.is_some() {
+            SignUpStatus::Complete
+        } else {
+            SignUpStatus::MissingRequirements
+        };
+        SignUp {
+            object: Some(SignUpObject::SignUpAttempt),
+            id: Some(sign_up.id.clone()),
+            status: Some(status),
+            required_fields: Some(vec!["email_address".to_string(), "password".to_string()]),
+            optional_fields: Some(vec!["first_name".to_string(), "last_name".to_string()]),
+            missing_fields: Some(sign_up.missing_fields()),
+            unverified_fields: Some(sign_up.unverified_fields()),
+            email_address: Some(sign_up.email_address.clone()),
+            first_name: Some(sign_up.first_name.clone()),
+            last_name: Some(sign_up.last_name.clone()),
+            password_enabled: Some(Some(sign_up.password.is_some())),
+            created_user_id: Some(sign_up.created_user_id.clone()),
+            created_session_id: Some(sign_up.created_session_id.clone()),
+            abandon_at: Some(Some(sign_up.created_at + ATTEMPT_LIFETIME_MILLIS)),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clerk::{Clerk, Error};
+    use crate::jwt::JwtVerifier;
+    use crate::sign_in::{FirstFactor, SignInState};
+    use crate::sign_up::{SignUpFields, SignUpState};
+
+    async fn signed_in(fapi: &FakeFapi, email: &str, password: &str) -> Clerk {
+        let clerk = Clerk::new(fapi.config()).unwrap().load().await.unwrap();
+        let state = clerk.sign_in().password(email, password).await.unwrap();
+        assert!(matches!(state, SignInState::Complete(_)));
+        clerk
+    }
+
+    #[tokio::test]
+    async fn test_password_sign_in() {
+        let fapi = FakeFapi::start().await.unwrap();
+        let user_id = fapi.add_user(FakeUser::new("ada@example.com").with_password("secret"));
+
+        let clerk = Clerk::new(fapi.config()).unwrap().load().await.unwrap();
+        let error = clerk
+            .sign_in()
+            .password("ada@example.com", "wrong")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.api_error().and_then(|e| e.code()),
+            Some("form_password_incorrect")
+        );
+
+        let clerk = signed_in(&fapi, "ada@example.com", "secret").await;
+        assert_eq!(clerk.user().await.unwrap().id, Some(user_id));
+
+        // Another configuration is another client, without the session
+        let other = Clerk::new(fapi.config()).unwrap().load().await.unwrap();
+        assert!(other.session().await.is_none());
+
+        clerk.sign_out(None).await.unwrap();
+        assert!(clerk.session().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_email_code_sign_in() {
+        let fapi = FakeFapi::start().await.unwrap();
+        fapi.add_user(FakeUser::new("ada@example.com"));
+        let clerk = Clerk::new(fapi.config()).unwrap().load().await.unwrap();
+
+        let SignInState::NeedsFirstFactor(needs_factor) =
+            clerk.sign_in().identify("ada@example.com").await.unwrap()
+        else {
+            panic!("expected the first factor step");
+        };
+        let code_factor = needs_factor
+            .factors()
+            .into_iter()
+            .find_map(|factor| match factor {
+                FirstFactor::Code(code) => Some(code),
+                _ => None,
+            })
+            .unwrap();
+        let pending = needs_factor.prepare_code(&code_factor).await.unwrap();
+        let state = pending.attempt(VERIFICATION_CODE).await.unwrap();
+
+        assert!(matches!(state, SignInState::Complete(_)));
+        assert!(clerk.session().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_email_code_sign_up() {
+        let fapi = FakeFapi::start().await.unwrap();
+        let clerk = Clerk::new(fapi.config()).unwrap().load().await.unwrap();
+
+        let SignUpState::MissingRequirements(missing) = clerk
+            .sign_up()
+            .email_address("grace@example.com")
+            .password("a long enough password")
+            .create()
+            .await
+            .unwrap()
+        else {
+            panic!("expected the email address to need verification");
+        };
+        assert!(missing.missing_fields().is_empty());
+        let pending = missing.prepare_email_code().await.unwrap();
+        let SignUpState::Complete(complete) = pending.attempt(VERIFICATION_CODE).await.unwrap()
+        else {
+            panic!("expected the sign up to complete");
+        };
+
+        let user = fapi.user(complete.user_id().unwrap()).unwrap();
+        assert_eq!(
+            user.email_addresses.unwrap()[0].email_address,
+            "grace@example.com"
+        );
+        assert_eq!(
+            clerk.session().await.unwrap().id.as_deref(),
+            Some(complete.session_id())
+        );
+
+        // The new user can sign in with the password
+        signed_in(&fapi, "grace@example.com", "a long enough password").await;
+    }
+
+    #[tokio::test]
+    async fn test_tokens_verify_with_jwks() {
+        let fapi = FakeFapi::start().await.unwrap();
+        let user_id = fapi.add_user(FakeUser::new("ada@example.com").with_password("secret"));
+        let org_id = fapi.add_organization("Analytical Engines");
+        fapi.add_membership(&org_id, &user_id, "org:admin");
+        fapi.add_jwt_template(
+            "backend",
+            JsonMap::from_iter([("plan".into(), json!("pro"))]),
+        );
+
+        let clerk = signed_in(&fapi, "ada@example.com", "secret").await;
+        let verifier = JwtVerifier::new(fapi.config()).unwrap();
+
+        let token = clerk.get_token(None, None).await.unwrap().unwrap();
+        let claims = verifier.verify(&token).await.unwrap();
+        assert_eq!(claims.sub, user_id);
+        assert_eq!(claims.iss.as_deref(), Some(fapi.url()));
+        assert_eq!(claims.org_id, None);
+
+        clerk
+            .set_active(None, Some("analytical-engines".to_string()))
+            .await
+            .unwrap();
+        let token = clerk.get_token(None, None).await.unwrap().unwrap();
+        let claims = verifier.verify(&token).await.unwrap();
+        assert_eq!(claims.org_id, Some(org_id));
+        assert_eq!(claims.org_role.as_deref(), Some("org:admin"));
+
+        let token = clerk
+            .get_token(None, Some("backend"))
+            .await
+            .unwrap()
+            .unwrap();
+        let claims = verifier.verify(&token).await.unwrap();
+        assert_eq!(claims.custom["plan"], "pro");
+    }
+
+    #[tokio::test]
+    async fn test_organizations() {
+        let fapi = FakeFapi::start().await.unwrap();
+        let user_id = fapi.add_user(FakeUser::new("ada@example.com").with_password("secret"));
+        let clerk = signed_in(&fapi, "ada@example.com", "secret").await;
+
+        let error = clerk
+            .set_active(None, Some("unknown".to_string()))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::OrganizationNotFound(_)));
+
+        let org_id = fapi.add_organization("Analytical Engines");
+        fapi.add_membership(&org_id, &user_id, "org:member");
+        clerk.set_active(None, Some(org_id.clone())).await.unwrap();
+        assert_eq!(clerk.organization().await.unwrap().id, Some(org_id));
+
+        clerk.set_personal_account().await.unwrap();
+        assert!(clerk.organization().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_single_session_mode() {
+        let fapi = FakeFapi::start().await.unwrap();
+        fapi.set_single_session_mode(true);
+        fapi.add_user(FakeUser::new("ada@example.com").with_password("secret"));
+        fapi.add_user(FakeUser::new("grace@example.com").with_password("secret"));
+
+        let clerk = signed_in(&fapi, "ada@example.com", "secret").await;
+        let error = clerk
+            .sign_in()
+            .password("grace@example.com", "secret")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.api_error().and_then(|e| e.code()),
+            Some("session_exists")
+        );
+    }
+}
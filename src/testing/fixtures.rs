@@ -0,0 +1,587 @@
+//! Builders for models in the shape the Frontend API returns them
+//!
+//! The generated models require every field in their `new()` constructors,
+//! these builders fill in realistic defaults so tests only spell out what they
+//! care about. IDs are unique within the process unless set explicitly.
+//!
+//! ```no_run
+//! use clerk_fapi_rs::clerk::Clerk;
+//! use clerk_fapi_rs::testing::fixtures::{
+//!     ClientBuilder, EnvironmentBuilder, OrganizationBuilder, SessionBuilder, UserBuilder,
+//! };
+//!
+//! # async fn example(clerk: Clerk) -> Result<(), clerk_fapi_rs::clerk::Error> {
+//! let acme = OrganizationBuilder::new("Acme").id("org_acme");
+//! let client = ClientBuilder::new()
+//!     .with_session(
+//!         SessionBuilder::active()
+//!             .user(UserBuilder::new().email("ada@example.com").member_of(acme, "admin"))
+//!             .organization("org_acme"),
+//!     )
+//!     .build();
+//!
+//! let clerk = clerk.load_with(EnvironmentBuilder::new().build(), client).await?;
+//! assert_eq!(clerk.organization().await.unwrap().slug.as_deref(), Some("acme"));
+//! # Ok(())
+//! # }
+//! ```
+
+use super::fake_fapi::{slugify, PUBLISHABLE_KEY};
+use crate::clerk::Clerk;
+use crate::configuration::ClerkFapiConfiguration;
+use crate::models::client_period_organization::Object as OrganizationObject;
+use crate::models::client_period_organization_membership::Object as MembershipObject;
+use crate::models::client_period_session::{Object as SessionObject, Status as SessionStatus};
+use crate::models::client_period_user::Object as UserObject;
+use crate::models::{
+    ClientPeriodAuthConfig as AuthConfig, ClientPeriodClient as Client,
+    ClientPeriodEmailAddress as EmailAddress, ClientPeriodEnvironment as Environment,
+    ClientPeriodOrganization as Organization,
+    ClientPeriodOrganizationMembership as OrganizationMembership,
+    ClientPeriodOrganizationSettings as OrganizationSettings, ClientPeriodSession as Session,
+    ClientPeriodUser as User, ClientPeriodUserSettings as UserSettings,
+    UserSettingsPeriodAttribute as Attribute,
+};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Creation time of every fixture, 2024-01-01T00:00:00Z in milliseconds
+pub const CREATED_AT: i64 = 1_704_067_200_000;
+
+/// Lifetime of fixture sessions
+const SESSION_LIFETIME_MILLIS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Returns a new ID with the prefix the Frontend API uses for the object
+fn next_id(prefix: &str) -> String {
+    format!("{}_{}", prefix, NEXT_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Roles are namespaced by the Frontend API, `admin` becomes `org:admin`
+fn role(role: &str) -> String {
+    if role.contains(':') {
+        role.to_string()
+    } else {
+        format!("org:{}", role)
+    }
+}
+
+/// The environment of an instance with email address and password sign ins
+/// and organizations enabled, like [`crate::testing::FakeFapi`] serves
+#[derive(Debug, Clone)]
+pub struct EnvironmentBuilder {
+    single_session_mode: bool,
+    organizations: bool,
+}
+
+impl Default for EnvironmentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnvironmentBuilder {
+    /// A multi session instance with organizations enabled
+    pub fn new() -> Self {
+        Self {
+            single_session_mode: false,
+            organizations: true,
+        }
+    }
+
+    /// Only allows one signed in session per client
+    pub fn single_session_mode(mut self, enabled: bool) -> Self {
+        self.single_session_mode = enabled;
+        self
+    }
+
+    /// Enables or disables organizations
+    pub fn organizations(mut self, enabled: bool) -> Self {
+        self.organizations = enabled;
+        self
+    }
+
+    /// Returns the environment
+    pub fn build(self) -> Environment {
+        let attribute = |required: bool| Attribute {
+            enabled: Some(true),
+            required: Some(required),
+            ..Default::default()
+        };
+        let mut user_settings = UserSettings::default();
+        user_settings.attributes.email_address = Box::new(Attribute {
+            used_for_first_factor: Some(true),
+            first_factors: Some(vec!["email_code".to_string()]),
+            verifications: Some(vec!["email_code".to_string()]),
+            verify_at_sign_up: Some(true),
+            ..attribute(true)
+        });
+        user_settings.attributes.password = Box::new(attribute(true));
+        user_settings.attributes.first_name = Box::new(attribute(false));
+        user_settings.attributes.last_name = Box::new(attribute(false));
+
+        Environment {
+            auth_config: Some(Box::new(AuthConfig {
+                single_session_mode: self.single_session_mode,
+                identification_strategies: vec!["email_address".to_string()],
+                first_factors: vec!["email_code".to_string(), "password".to_string()],
+                email_address_verification_strategies: vec!["email_code".to_string()],
+                test_mode: true,
+                ..Default::default()
+            })),
+            user_settings: Some(Box::new(user_settings)),
+            organization_settings: Some(Box::new(OrganizationSettings {
+                enabled: self.organizations,
+                max_allowed_memberships: 100,
+                creator_role: Some("org:admin".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<EnvironmentBuilder> for Environment {
+    fn from(builder: EnvironmentBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// An organization as it appears in the memberships of a user
+#[derive(Debug, Clone)]
+pub struct OrganizationBuilder {
+    organization: Organization,
+}
+
+impl OrganizationBuilder {
+    /// An organization with a slug derived from the name
+    pub fn new(name: &str) -> Self {
+        let slug = slugify(name);
+        Self {
+            organization: Organization {
+                id: Some(next_id("org")),
+                object: Some(OrganizationObject::Organization),
+                name: Some(name.to_string()),
+                slug: Some(slug),
+                members_count: Some(1),
+                pending_invitations_count: Some(0),
+                admin_delete_enabled: Some(true),
+                has_image: Some(false),
+                public_metadata: Some(Default::default()),
+                created_at: Some(CREATED_AT),
+                updated_at: Some(CREATED_AT),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Sets the ID instead of a generated `org_` one
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.organization.id = Some(id.into());
+        self
+    }
+
+    /// Sets the slug instead of the one derived from the name
+    pub fn slug(mut self, slug: impl Into<String>) -> Self {
+        self.organization.slug = Some(slug.into());
+        self
+    }
+
+    /// Returns the organization
+    pub fn build(self) -> Organization {
+        self.organization
+    }
+}
+
+impl From<OrganizationBuilder> for Organization {
+    fn from(builder: OrganizationBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// The user of a session
+#[derive(Debug, Clone)]
+pub struct UserBuilder {
+    user: User,
+}
+
+impl Default for UserBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UserBuilder {
+    /// A user without email addresses or memberships
+    pub fn new() -> Self {
+        Self {
+            user: User {
+                id: Some(next_id("user")),
+                object: Some(UserObject::User),
+                primary_email_address_id: Some(None),
+                first_name: Some(None),
+                last_name: Some(None),
+                username: Some(None),
+                has_image: Some(false),
+                public_metadata: Some(Default::default()),
+                unsafe_metadata: Some(Default::default()),
+                email_addresses: Some(Vec::new()),
+                phone_numbers: Some(Vec::new()),
+                organization_memberships: Some(Vec::new()),
+                password_enabled: Some(false),
+                two_factor_enabled: Some(false),
+                totp_enabled: Some(false),
+                backup_code_enabled: Some(false),
+                create_organization_enabled: Some(true),
+                delete_self_enabled: Some(true),
+                created_at: Some(CREATED_AT),
+                updated_at: Some(CREATED_AT),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Sets the ID instead of a generated `user_` one
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.user.id = Some(id.into());
+        self
+    }
+
+    /// Adds a verified email address, the first one is the primary
+    pub fn email(mut self, email_address: impl Into<String>) -> Self {
+        let id = next_id("idn");
+        if matches!(self.user.primary_email_address_id, None | Some(None)) {
+            self.user.primary_email_address_id = Some(Some(id.clone()));
+        }
+        self.user
+            .email_addresses
+            .get_or_insert_with(Vec::new)
+            .push(EmailAddress {
+                id: Some(id),
+                email_address: email_address.into(),
+                created_at: CREATED_AT,
+                updated_at: CREATED_AT,
+                ..Default::default()
+            });
+        self
+    }
+
+    /// Sets the first and last name
+    pub fn name(mut self, first_name: impl Into<String>, last_name: impl Into<String>) -> Self {
+        self.user.first_name = Some(Some(first_name.into()));
+        self.user.last_name = Some(Some(last_name.into()));
+        self
+    }
+
+    /// Sets the username
+    pub fn username(mut self, username: impl Into<String>) -> Self {
+        self.user.username = Some(Some(username.into()));
+        self
+    }
+
+    /// Whether the user has a password, false by default
+    pub fn password_enabled(mut self, enabled: bool) -> Self {
+        self.user.password_enabled = Some(enabled);
+        self
+    }
+
+    /// Adds a membership with the role, `admin` and `org:admin` are the same
+    pub fn member_of(mut self, organization: impl Into<Organization>, role_key: &str) -> Self {
+        self.user
+            .organization_memberships
+            .get_or_insert_with(Vec::new)
+            .push(OrganizationMembership {
+                id: Some(next_id("orgmem")),
+                object: Some(MembershipObject::OrganizationMembership),
+                role: Some(role(role_key)),
+                organization: Some(Box::new(organization.into())),
+                created_at: Some(CREATED_AT),
+                updated_at: Some(CREATED_AT),
+                ..Default::default()
+            });
+        self
+    }
+
+    /// Returns the user
+    pub fn build(self) -> User {
+        self.user
+    }
+}
+
+impl From<UserBuilder> for User {
+    fn from(builder: UserBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// A session of a client, without a user until [`SessionBuilder::user`] is set
+#[derive(Debug, Clone)]
+pub struct SessionBuilder {
+    session: Session,
+}
+
+impl SessionBuilder {
+    /// A signed in session
+    pub fn active() -> Self {
+        Self::with_status(SessionStatus::Active)
+    }
+
+    /// A session with the status, e.g. an ended or revoked one
+    pub fn with_status(status: SessionStatus) -> Self {
+        Self {
+            session: Session {
+                id: Some(next_id("sess")),
+                object: Some(SessionObject::Session),
+                status: Some(status),
+                actor: Some(None),
+                last_active_at: Some(CREATED_AT),
+                expire_at: Some(CREATED_AT + SESSION_LIFETIME_MILLIS),
+                abandon_at: Some(CREATED_AT + SESSION_LIFETIME_MILLIS),
+                user: Some(None),
+                created_at: Some(CREATED_AT),
+                updated_at: Some(CREATED_AT),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Sets the ID instead of a generated `sess_` one
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.session.id = Some(id.into());
+        self
+    }
+
+    /// Sets the signed in user
+    pub fn user(mut self, user: impl Into<User>) -> Self {
+        self.session.user = Some(Some(Box::new(user.into())));
+        self
+    }
+
+    /// Makes the organization active, the user should be a member of it
+    pub fn organization(mut self, organization_id: impl Into<String>) -> Self {
+        self.session.last_active_organization_id = Some(organization_id.into());
+        self
+    }
+
+    /// Returns the session
+    pub fn build(self) -> Session {
+        self.session
+    }
+}
+
+impl From<SessionBuilder> for Session {
+    fn from(builder: SessionBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// The client of a device, holding its sessions
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    client: Client,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientBuilder {
+    /// A client without sessions, like a signed out device
+    pub fn new() -> Self {
+        Self {
+            client: Client {
+                id: Some(next_id("client")),
+                created_at: CREATED_AT,
+                updated_at: CREATED_AT,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Sets the ID instead of a generated `client_` one
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.client.id = Some(id.into());
+        self
+    }
+
+    /// Adds a session, the first active one becomes the last active session
+    pub fn with_session(mut self, session: impl Into<Session>) -> Self {
+        let session = session.into();
+        if self.client.last_active_session_id.is_none()
+            && session.status == Some(SessionStatus::Active)
+        {
+            self.client.last_active_session_id = session.id.clone();
+        }
+        self.client.sessions.push(session);
+        self
+    }
+
+    /// Sets the last active session, `None` for a client without an active session
+    pub fn last_active_session(mut self, session_id: Option<&str>) -> Self {
+        self.client.last_active_session_id = session_id.map(str::to_string);
+        self
+    }
+
+    /// Returns the client
+    pub fn build(self) -> Client {
+        self.client
+    }
+}
+
+impl From<ClientBuilder> for Client {
+    fn from(builder: ClientBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// A client signed in to the session `sess_123`
+pub fn signed_in_client() -> Client {
+    ClientBuilder::new()
+        .with_session(
+            SessionBuilder::active()
+                .id("sess_123")
+                .user(UserBuilder::new()),
+        )
+        .build()
+}
+
+/// A [`Clerk`] talking to the Frontend API at `base_url`, loaded with
+/// [`Clerk::load_with`] so creating it makes no requests
+pub async fn loaded_clerk(
+    base_url: impl Into<String>,
+    environment: impl Into<Environment>,
+    client: impl Into<Client>,
+) -> Clerk {
+    let config =
+        ClerkFapiConfiguration::new(PUBLISHABLE_KEY.to_string(), Some(base_url.into()), None)
+            .expect("the fake publishable key is valid");
+    Clerk::new(config)
+        .expect("the HTTP client can be created")
+        .load_with(environment.into(), client.into())
+        .await
+        .expect("the state fits the default store")
+}
+
+/// The JSON body of a Frontend API response wrapped with the client
+pub fn response_body<T: Serialize>(response: &T, client: impl Into<Client>) -> String {
+    serde_json::json!({ "response": response, "client": client.into() }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clerk::Clerk;
+    use crate::configuration::ClerkFapiConfiguration;
+
+    fn clerk() -> Clerk {
+        // Nothing listens on the port, any request would fail
+        let config = ClerkFapiConfiguration::new(
+            "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+            Some("http://127.0.0.1:9".to_string()),
+            None,
+        )
+        .unwrap();
+        Clerk::new(config).unwrap()
+    }
+
+    #[test]
+    fn test_models_round_trip() {
+        let client = ClientBuilder::new()
+            .with_session(
+                SessionBuilder::active().user(
+                    UserBuilder::new()
+                        .email("ada@example.com")
+                        .name("Ada", "Lovelace")
+                        .member_of(OrganizationBuilder::new("Analytical Engines"), "admin"),
+                ),
+            )
+            .build();
+
+        let json = serde_json::to_value(&client).unwrap();
+        assert_eq!(serde_json::from_value::<Client>(json).unwrap(), client);
+        let membership = &client.sessions[0]
+            .user
+            .clone()
+            .flatten()
+            .unwrap()
+            .organization_memberships
+            .unwrap()[0];
+        assert_eq!(membership.role.as_deref(), Some("org:admin"));
+        assert_eq!(
+            membership.organization.as_ref().unwrap().slug.as_deref(),
+            Some("analytical-engines")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_with() {
+        let client = ClientBuilder::new()
+            .with_session(
+                SessionBuilder::active().id("sess_1").user(
+                    UserBuilder::new()
+                        .id("user_1")
+                        .member_of(OrganizationBuilder::new("Acme").id("org_1"), "org:member"),
+                ),
+            )
+            .with_session(
+                SessionBuilder::active()
+                    .id("sess_2")
+                    .user(UserBuilder::new())
+                    .organization("org_1"),
+            )
+            .with_session(
+                SessionBuilder::with_status(SessionStatus::Ended).user(UserBuilder::new()),
+            )
+            .build();
+
+        let clerk = clerk()
+            .load_with(EnvironmentBuilder::new().build(), client.clone())
+            .await
+            .unwrap();
+
+        assert!(clerk.loaded().await);
+        assert_eq!(clerk.client().await, Some(client));
+        assert_eq!(clerk.session().await.unwrap().id.as_deref(), Some("sess_1"));
+        assert_eq!(clerk.user().await.unwrap().id.as_deref(), Some("user_1"));
+        assert!(clerk.organization().await.is_none());
+        assert_eq!(clerk.sessions().await.len(), 2);
+
+        // Loading again doesn't make requests either
+        assert!(clerk.load().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_load_with_active_organization() {
+        let acme = OrganizationBuilder::new("Acme");
+        let acme_id = acme.clone().build().id;
+        let client = ClientBuilder::new()
+            .with_session(
+                SessionBuilder::active()
+                    .user(UserBuilder::new().member_of(acme, "admin"))
+                    .organization(acme_id.clone().unwrap()),
+            )
+            .build();
+
+        let clerk = clerk()
+            .load_with(
+                EnvironmentBuilder::new().single_session_mode(true).build(),
+                client,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(clerk.organization().await.unwrap().id, acme_id);
+        assert!(
+            clerk
+                .environment()
+                .await
+                .unwrap()
+                .auth_config
+                .unwrap()
+                .single_session_mode
+        );
+    }
+}
@@ -0,0 +1,40 @@
+//! Helpers for testing code built on this crate, enabled with the `testing` feature
+//!
+//! [`FakeFapi`] is an in-process fake of the Frontend API listening on a local
+//! port. It keeps clients, sessions, sign ins, sign ups, users and
+//! organizations in memory, accepts [`VERIFICATION_CODE`] for every email code
+//! and signs session tokens with its own key, published at
+//! `/.well-known/jwks.json` so they verify with [`crate::jwt::JwtVerifier`].
+//!
+//! Code that only reads the state of a [`crate::clerk::Clerk`] can be tested
+//! without a server, by loading a client built with [`fixtures`] with
+//! [`crate::clerk::Clerk::load_with`].
+//!
+//! [`SoftwareAuthenticator`] creates and uses passkeys in memory, for the
+//! flows in [`crate::passkey`].
+//!
+//! ```no_run
+//! use clerk_fapi_rs::clerk::Clerk;
+//! use clerk_fapi_rs::sign_in::SignInState;
+//! use clerk_fapi_rs::testing::{FakeFapi, FakeUser};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let fapi = FakeFapi::start().await?;
+//! fapi.add_user(FakeUser::new("ada@example.com").with_password("correct horse"));
+//!
+//! let clerk = Clerk::new(fapi.config())?.load().await?;
+//! let state = clerk
+//!     .sign_in()
+//!     .password("ada@example.com", "correct horse")
+//!     .await?;
+//! assert!(matches!(state, SignInState::Complete(_)));
+//! # Ok(())
+//! # }
+//! ```
+
+mod authenticator;
+mod fake_fapi;
+pub mod fixtures;
+
+pub use authenticator::SoftwareAuthenticator;
+pub use fake_fapi::{FakeFapi, FakeUser, VERIFICATION_CODE};
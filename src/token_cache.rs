@@ -0,0 +1,210 @@
+use crate::jwt::SessionClaims;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex as AsyncMutex;
+
+/// Identifies a cached token by the parameters it was minted with
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct TokenCacheKey {
+    pub(crate) session_id: String,
+    pub(crate) organization_id: Option<String>,
+    pub(crate) template: Option<String>,
+}
+
+impl TokenCacheKey {
+    pub(crate) fn new(
+        session_id: &str,
+        organization_id: Option<&str>,
+        template: Option<&str>,
+    ) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            organization_id: organization_id.map(str::to_string),
+            template: template.map(str::to_string),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    jwt: String,
+    expires_at: u64,
+}
+
+impl CachedToken {
+    fn is_fresh(&self, margin: Duration) -> bool {
+        now_secs() + margin.as_secs() < self.expires_at
+    }
+}
+
+type Slot = Arc<AsyncMutex<Option<CachedToken>>>;
+
+/// In memory cache of session tokens
+///
+/// Every key has its own async lock, so concurrent requests for the same
+/// token wait on a single refresh instead of each hitting the API.
+#[derive(Debug, Default)]
+pub(crate) struct TokenCache {
+    slots: Mutex<HashMap<TokenCacheKey, Slot>>,
+}
+
+impl TokenCache {
+    /// Returns the cached token for `key` if it is valid for longer than
+    /// `margin`, otherwise calls `fetch` and caches the result
+    pub(crate) async fn get_or_refresh<F, Fut, E>(
+        &self,
+        key: TokenCacheKey,
+        margin: Duration,
+        fetch: F,
+    ) -> Result<Option<String>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Option<String>, E>>,
+    {
+        let slot = self.slots.lock().entry(key).or_default().clone();
+        let mut cached = slot.lock().await;
+
+        if let Some(token) = cached.as_ref().filter(|t| t.is_fresh(margin)) {
+            return Ok(Some(token.jwt.clone()));
+        }
+
+        let jwt = fetch().await?;
+        *cached = jwt.as_ref().and_then(|jwt| {
+            SessionClaims::<serde_json::Value>::decode(jwt)
+                .ok()
+                .and_then(|claims| u64::try_from(claims.exp).ok())
+                .map(|expires_at| CachedToken {
+                    jwt: jwt.clone(),
+                    expires_at,
+                })
+        });
+
+        Ok(jwt)
+    }
+
+    /// Drops every cached token
+    pub(crate) fn clear(&self) {
+        self.slots.lock().clear();
+    }
+
+    /// Drops the cached tokens of sessions for which `keep` returns false
+    pub(crate) fn retain_sessions(&self, keep: impl Fn(&str) -> bool) {
+        self.slots.lock().retain(|key, _| keep(&key.session_id));
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+pub(crate) fn test_jwt(exp: u64) -> String {
+    use base64::{engine::general_purpose, Engine as _};
+
+    let header = general_purpose::URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256","typ":"JWT"}"#);
+    let payload =
+        general_purpose::URL_SAFE_NO_PAD.encode(format!(r#"{{"sub":"user_123","exp":{}}}"#, exp));
+    format!("{}.{}.signature", header, payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn key() -> TokenCacheKey {
+        TokenCacheKey::new("sess_123", None, None)
+    }
+
+    #[tokio::test]
+    async fn test_returns_cached_token_while_fresh() {
+        let cache = TokenCache::default();
+        let jwt = test_jwt(now_secs() + 60);
+        let calls = AtomicUsize::new(0);
+
+        for _ in 0..3 {
+            let token = cache
+                .get_or_refresh(key(), Duration::from_secs(10), || async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, ()>(Some(jwt.clone()))
+                })
+                .await
+                .unwrap();
+            assert_eq!(token, Some(jwt.clone()));
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refreshes_within_margin() {
+        let cache = TokenCache::default();
+        let calls = AtomicUsize::new(0);
+
+        for _ in 0..2 {
+            cache
+                .get_or_refresh(key(), Duration::from_secs(30), || async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, ()>(Some(test_jwt(now_secs() + 20)))
+                })
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_refreshes_are_deduplicated() {
+        let cache = Arc::new(TokenCache::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let handles = (0..5)
+            .map(|_| {
+                let cache = cache.clone();
+                let calls = calls.clone();
+                tokio::spawn(async move {
+                    cache
+                        .get_or_refresh(key(), Duration::from_secs(10), || async {
+                            calls.fetch_add(1, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(20)).await;
+                            Ok::<_, ()>(Some(test_jwt(now_secs() + 60)))
+                        })
+                        .await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            assert!(handle.await.unwrap().unwrap().is_some());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retain_sessions() {
+        let cache = TokenCache::default();
+        for session_id in ["sess_1", "sess_2"] {
+            cache
+                .get_or_refresh(
+                    TokenCacheKey::new(session_id, None, None),
+                    Duration::ZERO,
+                    || async { Ok::<_, ()>(Some(test_jwt(now_secs() + 60))) },
+                )
+                .await
+                .unwrap();
+        }
+
+        cache.retain_sessions(|id| id == "sess_2");
+
+        let slots = cache.slots.lock();
+        assert_eq!(slots.len(), 1);
+        assert!(slots.contains_key(&TokenCacheKey::new("sess_2", None, None)));
+    }
+}